@@ -254,5 +254,38 @@ fn bench_bitfield(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_nothing, bench_set_bit, bench_bitfield);
+fn bench_get_bits_batch(c: &mut Criterion) {
+    let words: Vec<u32> = (0..4096_u32).map(|i| i.wrapping_mul(0x9e37_79b9)).collect();
+    let mut out = vec![0_u32; words.len()];
+
+    benchmark_group(c, "get bits batch", |g| {
+        g.bench_function("actual", |b| {
+            b.iter(|| bitfield::get_bits_batch(black_box(&words), 0, 7, &mut out))
+        });
+
+        g.bench_function("per element", |b| {
+            b.iter(|| {
+                for (o, &w) in out.iter_mut().zip(black_box(&words)) {
+                    *o = bitfield::get_bits(w, 0, 7);
+                }
+            })
+        });
+
+        g.bench_function("manual", |b| {
+            b.iter(|| {
+                for (o, &w) in out.iter_mut().zip(black_box(&words)) {
+                    *o = w & 0x7f;
+                }
+            })
+        });
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_nothing,
+    bench_set_bit,
+    bench_bitfield,
+    bench_get_bits_batch
+);
 criterion_main!(benches);
@@ -0,0 +1,251 @@
+//! Derive macro front end for [`tartan-bitfield`](https://docs.rs/tartan-bitfield).
+//!
+//! Use this through the `derive` feature of `tartan-bitfield`, which re-exports the
+//! macro as `tartan_bitfield::Bitfield`. See the documentation there.
+
+#![warn(clippy::pedantic)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Expr, Fields, Ident, LitInt,
+    Path, Result, Token, Type, Visibility,
+};
+
+/// Generate accessors and trait implementations for a tuple struct wrapping an integer,
+/// the same as the `bitfield!` macro would.
+///
+/// Each field is declared with a `#[field(...)]` attribute on the struct, which takes
+/// the bit range followed by the field declaration as it would appear in `bitfield!`.
+/// An invariant function can be given with `#[invariant(...)]`.
+///
+/// ```ignore
+/// use tartan_bitfield::Bitfield;
+///
+/// #[derive(Default, Clone, Copy, PartialEq, Eq, Bitfield)]
+/// #[repr(transparent)]
+/// #[field(0..4, pub a: u8)]
+/// #[field(6..=17, b: u16 where 1..)]
+/// #[field(25, pub d)]
+/// #[field(26..32, pub e: u8 as SubFields)]
+/// #[invariant(Example::check)]
+/// pub struct Example(u32);
+/// ```
+///
+/// Unlike `bitfield!`, the derive can't add other derives to the struct, so
+/// `Default`, `Clone`, `Copy`, `PartialEq`, and `Eq` must be derived explicitly.
+#[proc_macro_derive(Bitfield, attributes(field, invariant))]
+pub fn derive_bitfield(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "Bitfield can't be derived for generic types",
+        ));
+    }
+    let repr = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+            _ => return Err(not_a_newtype(input)),
+        },
+        _ => return Err(not_a_newtype(input)),
+    };
+    let repr_bits = primitive_bits(repr);
+
+    let mut fields = Vec::new();
+    let mut invariant = None;
+    for attr in &input.attrs {
+        if attr.path.is_ident("field") {
+            let field: FieldSpec = attr.parse_args()?;
+            field.check(repr, repr_bits)?;
+            fields.push(field);
+        } else if attr.path.is_ident("invariant") {
+            if invariant.is_some() {
+                return Err(Error::new(attr.span(), "only one invariant is allowed"));
+            }
+            invariant = Some(parse_invariant(attr)?);
+        }
+    }
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let invariant = invariant.map(|path| quote!(where #path));
+    Ok(quote! {
+        ::tartan_bitfield::bitfield! {
+            @impls #vis struct #name(#repr) #invariant {
+                #( #fields ),*
+            }
+        }
+    })
+}
+
+fn not_a_newtype(input: &DeriveInput) -> Error {
+    Error::new(
+        input.ident.span(),
+        "Bitfield can only be derived for a tuple struct with one field, like \
+         `struct Example(u32);`",
+    )
+}
+
+/// Check that the invariant is a path, but keep the original tokens so that the name
+/// reported by `ValidationError::Invariant` matches the source
+fn parse_invariant(attr: &Attribute) -> Result<TokenStream2> {
+    attr.parse_args::<Path>().map_err(|e| {
+        Error::new(e.span(), "expected a function path, like `#[invariant(Self::check)]`")
+    })?;
+    match attr.tokens.clone().into_iter().next() {
+        Some(TokenTree::Group(group)) => Ok(group.stream()),
+        _ => unreachable!("parse_args succeeded"),
+    }
+}
+
+/// The number of bits in the type, if it's a primitive unsigned integer
+fn primitive_bits(ty: &Type) -> Option<u32> {
+    let Type::Path(path) = ty else { return None };
+    let ident = path.path.get_ident()?;
+    match ident.to_string().as_str() {
+        "u8" => Some(8),
+        "u16" => Some(16),
+        "u32" => Some(32),
+        "u64" => Some(64),
+        "u128" => Some(128),
+        _ => None,
+    }
+}
+
+/// The contents of a `#[field(...)]` attribute
+struct FieldSpec {
+    lsb: LitInt,
+    range: Option<(RangeLimit, LitInt)>,
+    vis: Visibility,
+    name: Ident,
+    ty: Option<FieldType>,
+}
+
+enum RangeLimit {
+    HalfOpen(Token![..]),
+    Closed(Token![..=]),
+}
+
+struct FieldType {
+    underlying: Type,
+    interface: Option<Type>,
+    check: Option<Expr>,
+}
+
+impl Parse for FieldSpec {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let lsb: LitInt = input.parse().map_err(|e| {
+            Error::new(e.span(), "expected a bit number or range, like `3` or `0..4`")
+        })?;
+        let range = if input.peek(Token![..=]) {
+            Some((RangeLimit::Closed(input.parse()?), input.parse()?))
+        } else if input.peek(Token![..]) {
+            Some((RangeLimit::HalfOpen(input.parse()?), input.parse()?))
+        } else {
+            None
+        };
+        input.parse::<Token![,]>()?;
+        let vis = input.parse()?;
+        let name = input.parse()?;
+        let ty = if input.peek(Token![:]) {
+            input.parse::<Token![:]>()?;
+            let underlying = input.parse()?;
+            let interface = if input.peek(Token![as]) {
+                input.parse::<Token![as]>()?;
+                Some(input.parse()?)
+            } else {
+                None
+            };
+            let check = if input.peek(Token![where]) {
+                input.parse::<Token![where]>()?;
+                Some(input.parse()?)
+            } else {
+                None
+            };
+            Some(FieldType { underlying, interface, check })
+        } else {
+            None
+        };
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens after field declaration"));
+        }
+        Ok(Self { lsb, range, vis, name, ty })
+    }
+}
+
+impl FieldSpec {
+    /// Check the field against the bitfield's underlying type, for errors that would
+    /// otherwise be reported deep inside the macro expansion or not at all
+    fn check(&self, repr: &Type, repr_bits: Option<u32>) -> Result<()> {
+        let lsb: u32 = self.lsb.base10_parse()?;
+        let msb = match &self.range {
+            None => lsb + 1,
+            Some((RangeLimit::HalfOpen(_), msb)) => msb.base10_parse()?,
+            Some((RangeLimit::Closed(_), msb)) => msb.base10_parse::<u32>()? + 1,
+        };
+        let range_span = match &self.range {
+            None => self.lsb.span(),
+            Some((_, msb)) => self.lsb.span().join(msb.span()).unwrap_or(msb.span()),
+        };
+
+        if msb <= lsb {
+            return Err(Error::new(range_span, "bit range is empty"));
+        }
+        if let Some(repr_bits) = repr_bits {
+            if msb > repr_bits {
+                let repr = quote!(#repr);
+                return Err(Error::new(
+                    range_span,
+                    format!("bit range extends past the {repr_bits} bits of `{repr}`"),
+                ));
+            }
+        }
+
+        match &self.ty {
+            Some(ty) => match primitive_bits(&ty.underlying) {
+                Some(bits) if msb - lsb > bits => {
+                    let underlying = &ty.underlying;
+                    Err(Error::new(
+                        underlying.span(),
+                        format!(
+                            "field `{}` is {} bits wide, but `{}` only holds {bits}",
+                            self.name,
+                            msb - lsb,
+                            quote!(#underlying),
+                        ),
+                    ))
+                }
+                _ => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+}
+
+impl quote::ToTokens for FieldSpec {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let Self { lsb, vis, name, .. } = self;
+        let range = match &self.range {
+            // A single bit with a type is an integer, so pass it on as a one-bit range
+            None if self.ty.is_some() => quote!(#lsb ..= #lsb),
+            None => quote!(#lsb),
+            Some((RangeLimit::HalfOpen(dots), msb)) => quote!(#lsb #dots #msb),
+            Some((RangeLimit::Closed(dots), msb)) => quote!(#lsb #dots #msb),
+        };
+        let ty = self.ty.as_ref().map(|ty| {
+            let underlying = &ty.underlying;
+            let interface = ty.interface.as_ref().map(|i| quote!(as #i));
+            let check = ty.check.as_ref().map(|c| quote!(where #c));
+            quote!(: #underlying #interface #check)
+        });
+        tokens.extend(quote!([#range] #vis #name #ty));
+    }
+}
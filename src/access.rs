@@ -0,0 +1,206 @@
+// Field access on the packed value of a bitfield, for the runtime APIs and for the
+// accessors `bitfield_accessors` generates in a trait, which can only reach the value
+// through the `Bitfield` trait. The accessors of a struct defined by `bitfield` shift
+// and mask its field directly instead.
+//
+// Accessors operate on the primitive storage type of the bitfield's underlying type,
+// which is the underlying type itself unless it is an exact-width integer like `u24`.
+
+use crate::{Bitfield, FieldStorage, TruncateInto};
+
+/// The value of a bitfield as its primitive storage type, for code that only has the
+/// `Bitfield` trait to go through
+#[doc(hidden)]
+#[inline(always)]
+pub fn packed_value<B, T>(bitfield: B) -> T::Storage
+where
+    B: Bitfield<T>,
+    T: FieldStorage + Into<T::Storage>,
+{
+    bitfield.value().into()
+}
+
+/// Construct a bitfield from its primitive storage type, discarding any bits that don't
+/// fit in the underlying type. The counterpart of [`packed_value`].
+#[doc(hidden)]
+#[inline(always)]
+pub fn from_packed_value<B, T>(packed: T::Storage) -> B
+where
+    B: Bitfield<T>,
+    T: FieldStorage,
+    T::Storage: TruncateInto<T>,
+{
+    B::new(packed.truncate_into())
+}
+
+/// Widen a field's underlying value to the primitive storage type of the bitfield `B`
+#[doc(hidden)]
+#[inline(always)]
+pub fn widen_to_packed<B, T, V>(value: V) -> T::Storage
+where
+    B: Bitfield<T>,
+    T: FieldStorage,
+    V: Into<T::Storage>,
+{
+    value.into()
+}
+
+#[doc(hidden)]
+pub trait PackedBits
+where
+    Self: Sized,
+{
+    /// Extract the bits `lsb..msb`, shifted down to the least significant bit
+    #[must_use]
+    fn packed_field(self, lsb: u8, msb: u8) -> Self;
+
+    /// Replace the bits `lsb..msb` with the low bits of `field`
+    #[must_use]
+    fn with_packed_field(self, lsb: u8, msb: u8, field: Self) -> Self;
+
+    /// Widen the value to the largest storage type
+    fn into_u128(self) -> u128;
+
+    /// Truncate a value of the largest storage type to this type
+    fn from_u128(value: u128) -> Self;
+}
+
+macro_rules! packed_bits_impl {
+    ($type:ty) => {
+        impl PackedBits for $type {
+            #[inline(always)]
+            fn packed_field(self, lsb: u8, msb: u8) -> Self {
+                (self >> lsb) & low_mask!($type, msb - lsb)
+            }
+
+            #[inline(always)]
+            fn with_packed_field(self, lsb: u8, msb: u8, field: Self) -> Self {
+                let mask = low_mask!($type, msb - lsb);
+                self & !(mask << lsb) | (field & mask) << lsb
+            }
+
+            // `usize` has no `From` conversion to `u128`
+            #[inline(always)]
+            #[allow(clippy::cast_lossless)]
+            fn into_u128(self) -> u128 {
+                self as u128
+            }
+
+            #[inline(always)]
+            #[allow(clippy::cast_possible_truncation)]
+            fn from_u128(value: u128) -> Self {
+                value as $type
+            }
+        }
+    };
+}
+
+// A mask of the given number of low bits. The shift is split in two so that a mask
+// covering the whole type doesn't overflow.
+macro_rules! low_mask {
+    ($type:ty, $width:expr) => {
+        !((<$type>::MAX << ($width - 1)) << 1)
+    };
+}
+
+packed_bits_impl!(u8);
+packed_bits_impl!(u16);
+packed_bits_impl!(u32);
+packed_bits_impl!(u64);
+packed_bits_impl!(usize);
+
+// Most targets have no native 128-bit operations, so a shift and mask on a u128 takes
+// several instructions to carry bits between the halves. Fields that lie within a single
+// 64-bit half only need to operate on that half.
+#[allow(clippy::cast_possible_truncation)]
+impl PackedBits for u128 {
+    #[inline(always)]
+    fn packed_field(self, lsb: u8, msb: u8) -> Self {
+        if msb <= 64 {
+            (self as u64).packed_field(lsb, msb).into()
+        } else if lsb >= 64 {
+            ((self >> 64) as u64).packed_field(lsb - 64, msb - 64).into()
+        } else {
+            (self >> lsb) & low_mask!(u128, msb - lsb)
+        }
+    }
+
+    #[inline(always)]
+    fn with_packed_field(self, lsb: u8, msb: u8, field: Self) -> Self {
+        if msb <= 64 {
+            let low = (self as u64).with_packed_field(lsb, msb, field as u64);
+            self & !u128::from(u64::MAX) | u128::from(low)
+        } else if lsb >= 64 {
+            let high =
+                ((self >> 64) as u64).with_packed_field(lsb - 64, msb - 64, field as u64);
+            self & u128::from(u64::MAX) | u128::from(high) << 64
+        } else {
+            let mask = low_mask!(u128, msb - lsb);
+            self & !(mask << lsb) | (field & mask) << lsb
+        }
+    }
+
+    #[inline(always)]
+    fn into_u128(self) -> u128 {
+        self
+    }
+
+    #[inline(always)]
+    fn from_u128(value: u128) -> Self {
+        value
+    }
+}
+
+/// Add one to the bits `lsb..msb` of a bitfield, either wrapping around to zero or
+/// staying at the maximum value when all the bits are already set
+#[doc(hidden)]
+pub fn increment_field<B, T>(bitfield: B, lsb: u8, msb: u8, saturating: bool) -> B
+where
+    B: Bitfield<T>,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits + TruncateInto<T>,
+{
+    let value = raw_field(bitfield, lsb, msb);
+    let max = u128::MAX >> (128 - (msb - lsb));
+    let value = match value {
+        _ if value < max => value + 1,
+        _ if saturating => max,
+        _ => 0,
+    };
+    with_raw_field(bitfield, lsb, msb, value)
+}
+
+/// True if an odd number of the bits `lsb..msb` of a bitfield are set
+#[doc(hidden)]
+pub fn odd_parity<B, T>(bitfield: B, lsb: u8, msb: u8) -> bool
+where
+    B: Bitfield<T>,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits,
+{
+    raw_field(bitfield, lsb, msb).count_ones() % 2 == 1
+}
+
+/// The bits `lsb..msb` of a bitfield, shifted down to the least significant bit
+#[doc(hidden)]
+pub fn raw_field<B, T>(bitfield: B, lsb: u8, msb: u8) -> u128
+where
+    B: Bitfield<T>,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits,
+{
+    packed_value(bitfield).packed_field(lsb, msb).into_u128()
+}
+
+/// Replace the bits `lsb..msb` of a bitfield with the low bits of `value`
+#[doc(hidden)]
+pub fn with_raw_field<B, T>(bitfield: B, lsb: u8, msb: u8, value: u128) -> B
+where
+    B: Bitfield<T>,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits + TruncateInto<T>,
+{
+    let packed =
+        packed_value(bitfield).with_packed_field(lsb, msb, T::Storage::from_u128(value));
+    from_packed_value(packed)
+}
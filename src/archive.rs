@@ -0,0 +1,103 @@
+use crate::{Bitfield, FieldStorage, PackedBits, TruncateInto};
+use core::convert::Infallible;
+use core::fmt;
+use core::marker::PhantomData;
+use rkyv::bytecheck::CheckBytes;
+use rkyv::munge::munge;
+use rkyv::rancor::{Fallible, Strategy};
+use rkyv::{Archive, Archived, Deserialize, Place, Portable};
+
+/// The archived form of a bitfield with the `rkyv` feature. It has the same layout as the
+/// archived form of the underlying integer, and its fields can be read in place with
+/// [`get`](Self::get), without deserializing the archive.
+///
+/// ```
+/// # use rkyv::rancor::Error;
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Telemetry(u32) {
+///         [0..12]  pub channel: u16,
+///         [12..31] pub reading: u32,
+///         [31]     pub overflow,
+///     }
+/// }
+///
+/// #[derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+/// struct Record {
+///     time: u64,
+///     status: Telemetry,
+/// }
+///
+/// let record = Record { time: 100, status: Telemetry(0x0001_2003) };
+/// let bytes = rkyv::to_bytes::<Error>(&record).unwrap();
+/// let archived = rkyv::access::<ArchivedRecord, Error>(&bytes).unwrap();
+/// assert_eq!(archived.status.get().channel(), 3);
+/// assert_eq!(archived.status.get().reading(), 0x12);
+/// ```
+#[derive(Portable, CheckBytes)]
+#[bytecheck(crate = rkyv::bytecheck)]
+#[repr(transparent)]
+pub struct ArchivedBitfield<A, B> {
+    value: A,
+    bitfield: PhantomData<B>,
+}
+
+impl<A, B> ArchivedBitfield<A, B>
+where
+    B: Bitfield<B::Storage> + FieldStorage,
+    B::Storage: FieldStorage,
+    <B::Storage as FieldStorage>::Storage: TruncateInto<B::Storage>,
+    A: Deserialize<<B::Storage as FieldStorage>::Storage, Strategy<(), Infallible>>,
+{
+    /// Read the bitfield from the archive
+    #[inline(always)]
+    pub fn get(&self) -> B {
+        match self.value.deserialize(Strategy::wrap(&mut ())) {
+            Ok(packed) => crate::from_packed_value(packed),
+            Err(never) => match never {},
+        }
+    }
+}
+
+impl<A, B, D> Deserialize<B, D> for ArchivedBitfield<A, B>
+where
+    B: Bitfield<B::Storage> + FieldStorage,
+    B::Storage: FieldStorage,
+    <B::Storage as FieldStorage>::Storage: TruncateInto<B::Storage>,
+    A: Deserialize<<B::Storage as FieldStorage>::Storage, Strategy<(), Infallible>>,
+    D: Fallible + ?Sized,
+{
+    #[inline(always)]
+    fn deserialize(&self, _: &mut D) -> Result<B, D::Error> {
+        Ok(self.get())
+    }
+}
+
+impl<A, B> fmt::Debug for ArchivedBitfield<A, B>
+where
+    B: Bitfield<B::Storage> + FieldStorage,
+    B::Storage: FieldStorage,
+    <B::Storage as FieldStorage>::Storage: TruncateInto<B::Storage>,
+    A: Deserialize<<B::Storage as FieldStorage>::Storage, Strategy<(), Infallible>>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ArchivedBitfield").field(&self.get()).finish()
+    }
+}
+
+/// Write the archived form of a bitfield. Helper function for the rkyv implementations
+/// generated by [`bitfield`](crate::bitfield).
+#[doc(hidden)]
+#[inline(always)]
+pub fn resolve_bitfield<B, T>(
+    bitfield: B,
+    resolver: <T::Storage as Archive>::Resolver,
+    out: Place<ArchivedBitfield<Archived<T::Storage>, B>>,
+) where
+    B: Bitfield<T>,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits + Archive,
+{
+    munge!(let ArchivedBitfield { value, .. } = out);
+    crate::packed_value(bitfield).resolve(resolver, value);
+}
@@ -0,0 +1,110 @@
+// Pattern parsing for the `bitmatch` macro. Patterns are string literals, so they are
+// parsed by const functions that the macro calls in const contexts, which turns a
+// malformed pattern into a compile error and leaves only masks and shifts at runtime.
+
+use crate::{FieldStorage, TruncateInto};
+
+/// The mask and value of the fixed bits of a `bitmatch` pattern, including the bits above
+/// the end of the pattern, which must be zero.
+///
+/// # Panics
+///
+/// Panics if the pattern contains a character other than `0`, `1`, `?`, `_`, or a
+/// lowercase letter, or if it doesn't have between 1 and 128 bits.
+#[doc(hidden)]
+#[must_use]
+pub const fn bitmatch_fixed(pattern: &str) -> (u128, u128) {
+    let bytes = pattern.as_bytes();
+    let mut mask = 0;
+    let mut value = 0;
+    let mut bit = 0;
+    let mut i = bytes.len();
+    while i > 0 {
+        i -= 1;
+        let c = bytes[i];
+        if c == b'_' {
+            continue;
+        }
+        assert!(bit < 128, "bitmatch pattern is longer than 128 bits");
+        match c {
+            b'0' => mask |= 1 << bit,
+            b'1' => {
+                mask |= 1 << bit;
+                value |= 1 << bit;
+            }
+            b'?' | b'a'..=b'z' => {}
+            _ => panic!(
+                "bitmatch pattern may only contain 0, 1, ?, _, and lowercase letters"
+            ),
+        }
+        bit += 1;
+    }
+    assert!(bit > 0, "bitmatch pattern is empty");
+    if bit < 128 {
+        mask |= u128::MAX << bit;
+    }
+    (mask, value)
+}
+
+/// The mask of the bits of a `bitmatch` pattern that are marked with the single letter
+/// `name`
+///
+/// # Panics
+///
+/// Panics if `name` isn't a single lowercase letter that appears in the pattern.
+#[doc(hidden)]
+#[must_use]
+pub const fn bitmatch_mask(pattern: &str, name: &str) -> u128 {
+    let name = name.as_bytes();
+    assert!(
+        name.len() == 1 && name[0].is_ascii_lowercase(),
+        "bitmatch binding must be a single lowercase letter",
+    );
+    let bytes = pattern.as_bytes();
+    let mut mask = 0;
+    let mut bit = 0;
+    let mut i = bytes.len();
+    while i > 0 {
+        i -= 1;
+        if bytes[i] == b'_' {
+            continue;
+        }
+        if bytes[i] == name[0] && bit < 128 {
+            mask |= 1 << bit;
+        }
+        bit += 1;
+    }
+    assert!(mask != 0, "bitmatch binding does not appear in the pattern");
+    mask
+}
+
+/// Gather the bits of `word` in `MASK` into the low bits of the result, keeping their
+/// order
+#[doc(hidden)]
+#[inline(always)]
+pub fn bitmatch_field<const MASK: u128, V>(word: u128) -> V
+where
+    V: FieldStorage,
+    u128: TruncateInto<V>,
+{
+    const {
+        assert!(
+            MASK.count_ones() <= V::MAX_BITS as u32,
+            "bitmatch binding has more bits than its type",
+        );
+    }
+    let low = MASK >> MASK.trailing_zeros();
+    if low & low.wrapping_add(1) == 0 {
+        return ((word & MASK) >> MASK.trailing_zeros()).truncate_into();
+    }
+    let mut result = 0;
+    let mut remaining = MASK;
+    let mut out = 0;
+    while remaining != 0 {
+        let bit = remaining.trailing_zeros();
+        result |= (word >> bit & 1) << out;
+        remaining &= remaining - 1;
+        out += 1;
+    }
+    result.truncate_into()
+}
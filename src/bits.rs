@@ -0,0 +1,251 @@
+use crate::{Bitfield, FieldLayout, FieldStorage, PackedBits, TruncateInto};
+use core::fmt;
+use core::marker::PhantomData;
+
+/// An iterator over the bits of a bitfield value as booleans, starting from the least
+/// significant bit.
+///
+/// This is returned by the `iter_bits` method generated by the
+/// [`bitfield`](crate::bitfield) macro, and it can be created for any bitfield type with
+/// [`Bits::new`]. It yields one item for every bit of the underlying type, including
+/// bits that aren't part of any field. Bitfields can also be collected from an iterator
+/// of booleans in the same order, with the generated `from_bits` function or
+/// [`FromIterator`]:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Example(u8) {
+///         [0]    pub a,
+///         [1..4] pub b,
+///     }
+/// }
+///
+/// let bits: Vec<_> = Example(0b0000_1101).iter_bits().collect();
+/// assert_eq!(bits, [true, false, true, true, false, false, false, false]);
+///
+/// assert_eq!(Example::from_bits(bits), Example(0b0000_1101));
+/// assert_eq!([false, true].into_iter().collect::<Example>(), Example(0b10));
+/// ```
+///
+/// When collecting, missing bits are clear and extra bits are ignored.
+#[derive(Debug, Clone)]
+pub struct Bits {
+    packed: u128,
+    next: u8,
+    end: u8,
+}
+
+impl Bits {
+    /// Iterate over the bits of the given bitfield value
+    pub fn new<B, T>(bitfield: B) -> Self
+    where
+        B: Bitfield<T>,
+        T: FieldStorage + Into<T::Storage>,
+        T::Storage: PackedBits,
+    {
+        let packed = crate::packed_value(bitfield).into_u128();
+        Self { packed, next: 0, end: T::MAX_BITS }
+    }
+}
+
+impl Iterator for Bits {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.next == self.end {
+            return None;
+        }
+        let bit = self.packed >> self.next & 1 != 0;
+        self.next += 1;
+        Some(bit)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = usize::from(self.end - self.next);
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Bits {
+    fn next_back(&mut self) -> Option<bool> {
+        if self.next == self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(self.packed >> self.end & 1 != 0)
+    }
+}
+
+impl ExactSizeIterator for Bits {}
+
+/// An iterator over every combination of field values of a bitfield, with the bits that
+/// aren't part of any field clear, for exhaustive tests of small bitfields like decode
+/// tables.
+///
+/// This is returned by the `iter_all` function generated by the
+/// [`bitfield`](crate::bitfield) macro, and it can be created for any bitfield type with
+/// [`AllValues::new`]. Values are yielded in increasing order of their underlying value,
+/// starting from zero. There are 2<sup>n</sup> values for n bits covered by fields, so
+/// this is only practical when the fields cover a few dozen bits at most. The count can
+/// overflow `usize` for wider fields, so this isn't an `ExactSizeIterator`, but its
+/// `size_hint` is exact whenever the count fits.
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Opcode(u8) {
+///         [0..2] pub reg,
+///         [6]    pub wide,
+///     }
+/// }
+///
+/// let all: Vec<_> = Opcode::iter_all().map(|op| op.0).collect();
+/// assert_eq!(all, [0x00, 0x01, 0x02, 0x03, 0x40, 0x41, 0x42, 0x43]);
+/// assert_eq!(Opcode::iter_all().size_hint(), (8, Some(8)));
+/// ```
+pub struct AllValues<B, T> {
+    mask: u128,
+    next: Option<u128>,
+    yielded: u128,
+    bitfield: PhantomData<fn() -> (B, T)>,
+}
+
+impl<B, T> AllValues<B, T>
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage,
+    T::Storage: PackedBits + TruncateInto<T>,
+{
+    /// Iterate over every combination of field values of the bitfield `B`
+    pub fn new() -> Self {
+        let mask = crate::field_mask::<B, T>().into_u128();
+        Self { mask, next: Some(0), yielded: 0, bitfield: PhantomData }
+    }
+}
+
+impl<B, T> Default for AllValues<B, T>
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage,
+    T::Storage: PackedBits + TruncateInto<T>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B, T> Iterator for AllValues<B, T>
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage,
+    T::Storage: PackedBits + TruncateInto<T>,
+{
+    type Item = B;
+
+    fn next(&mut self) -> Option<B> {
+        let packed = self.next?;
+        // The next larger subset of the bits in the mask, which wraps around to zero
+        // after the mask itself
+        let next = packed.wrapping_sub(self.mask) & self.mask;
+        self.next = (next != 0).then_some(next);
+        self.yielded += 1;
+        Some(crate::from_packed_value(T::Storage::from_u128(packed)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.next.is_none() {
+            return (0, Some(0));
+        }
+        let remaining = 1_u128
+            .checked_shl(self.mask.count_ones())
+            .and_then(|total| usize::try_from(total - self.yielded).ok());
+        (remaining.unwrap_or(usize::MAX), remaining)
+    }
+}
+
+impl<B, T> Clone for AllValues<B, T> {
+    fn clone(&self) -> Self {
+        Self { bitfield: PhantomData, ..*self }
+    }
+}
+
+impl<B, T> fmt::Debug for AllValues<B, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AllValues")
+            .field("mask", &self.mask)
+            .field("next", &self.next)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Construct a bitfield from an iterator of booleans, starting from the least
+/// significant bit. Missing bits are clear and extra bits are ignored.
+pub fn bitfield_from_bits<B, T, I>(bits: I) -> B
+where
+    B: Bitfield<T>,
+    T: FieldStorage,
+    T::Storage: PackedBits + TruncateInto<T>,
+    I: IntoIterator<Item = bool>,
+{
+    let mut packed = 0_u128;
+    for (i, bit) in bits.into_iter().take(T::MAX_BITS.into()).enumerate() {
+        packed |= u128::from(bit) << i;
+    }
+    crate::from_packed_value(T::Storage::from_u128(packed))
+}
+
+/// Replace the bits of `bitfield` starting at `offset` with all the bits of `sub`.
+///
+/// # Panics
+///
+/// Panics if `sub` doesn't fit in `bitfield` at the given offset.
+pub fn insert_bitfield<B, T, S>(bitfield: B, offset: u8, sub: S) -> B
+where
+    B: Bitfield<T>,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits + TruncateInto<T>,
+    S: Bitfield<S::Storage> + FieldStorage,
+    S::Storage: FieldStorage + Into<<S::Storage as FieldStorage>::Storage>,
+    <S::Storage as FieldStorage>::Storage: PackedBits,
+{
+    let end = sub_bitfield_end::<T, S>(offset);
+    let packed = crate::packed_value(bitfield).into_u128();
+    let sub = crate::packed_value(sub).into_u128();
+    crate::from_packed_value(T::Storage::from_u128(
+        packed.with_packed_field(offset, end, sub),
+    ))
+}
+
+/// Extract a bitfield of type `S` from the bits of `bitfield` starting at `offset`.
+///
+/// # Panics
+///
+/// Panics if `S` doesn't fit in `bitfield` at the given offset.
+pub fn extract_bitfield<B, T, S>(bitfield: B, offset: u8) -> S
+where
+    B: Bitfield<T>,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits,
+    S: Bitfield<S::Storage> + FieldStorage,
+    S::Storage: FieldStorage,
+    <S::Storage as FieldStorage>::Storage: PackedBits + TruncateInto<S::Storage>,
+{
+    let end = sub_bitfield_end::<T, S>(offset);
+    let packed = crate::packed_value(bitfield).into_u128();
+    let sub = packed.packed_field(offset, end);
+    crate::from_packed_value(<S::Storage as FieldStorage>::Storage::from_u128(sub))
+}
+
+// The end of a sub-bitfield at the given offset, which can't truncate since it's no
+// more than `T::MAX_BITS`
+#[allow(clippy::cast_possible_truncation)]
+fn sub_bitfield_end<T, S>(offset: u8) -> u8
+where
+    T: FieldStorage,
+    S: FieldStorage,
+{
+    let end = u16::from(offset) + u16::from(S::MAX_BITS);
+    assert!(end <= T::MAX_BITS.into(), "sub-bitfield extends past the end of the value");
+    end as u8
+}
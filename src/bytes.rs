@@ -0,0 +1,273 @@
+use crate::Bitfield;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::size_of;
+
+/// The order of bytes when converting values to or from a byte slice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ByteOrder {
+    /// Least significant byte first
+    Little,
+    /// Most significant byte first
+    Big,
+    /// The native byte order of the target platform
+    Native,
+}
+
+/// Error returned when a byte slice is not the combined size of a slice of bitfields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LengthMismatchError {
+    /// The number of bytes in the representation of the bitfields
+    pub expected: usize,
+    /// The number of bytes in the byte slice
+    pub actual: usize,
+}
+
+impl fmt::Display for LengthMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected {} bytes for the bitfields, found {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl core::error::Error for LengthMismatchError {}
+
+/// Check that a byte slice of length `actual` holds `count` values of type `T`
+#[inline(always)]
+pub(crate) fn check_length<T>(
+    count: usize,
+    actual: usize,
+) -> Result<(), LengthMismatchError>
+where
+    T: ByteRepr,
+{
+    let expected = count * T::SIZE;
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(LengthMismatchError { expected, actual })
+    }
+}
+
+/// An integer type that can be read from and written to a byte slice.
+///
+/// This is implemented for all unsigned integer types, and is used by the bulk
+/// conversion helpers like [`bitfields_from_bytes`].
+pub trait ByteRepr
+where
+    Self: Sized + Copy,
+{
+    /// The number of bytes in the representation of the type
+    const SIZE: usize;
+
+    /// Read a value from a byte slice of length [`SIZE`](Self::SIZE)
+    fn read_bytes(bytes: &[u8], order: ByteOrder) -> Self;
+
+    /// Write the value to a byte slice of length [`SIZE`](Self::SIZE)
+    fn write_bytes(self, bytes: &mut [u8], order: ByteOrder);
+
+    /// Reverse the order of bytes in the value
+    #[must_use]
+    fn swap_bytes(self) -> Self;
+}
+
+macro_rules! byte_repr_impl {
+    ($type:ty) => {
+        impl ByteRepr for $type {
+            const SIZE: usize = size_of::<$type>();
+
+            #[inline(always)]
+            fn read_bytes(bytes: &[u8], order: ByteOrder) -> Self {
+                let mut array = [0; size_of::<$type>()];
+                array.copy_from_slice(bytes);
+                match order {
+                    ByteOrder::Little => <$type>::from_le_bytes(array),
+                    ByteOrder::Big => <$type>::from_be_bytes(array),
+                    ByteOrder::Native => <$type>::from_ne_bytes(array),
+                }
+            }
+
+            #[inline(always)]
+            fn write_bytes(self, bytes: &mut [u8], order: ByteOrder) {
+                let array = match order {
+                    ByteOrder::Little => self.to_le_bytes(),
+                    ByteOrder::Big => self.to_be_bytes(),
+                    ByteOrder::Native => self.to_ne_bytes(),
+                };
+                bytes.copy_from_slice(&array);
+            }
+
+            #[inline(always)]
+            fn swap_bytes(self) -> Self {
+                <$type>::swap_bytes(self)
+            }
+        }
+    };
+}
+
+byte_repr_impl!(u8);
+byte_repr_impl!(u16);
+byte_repr_impl!(u32);
+byte_repr_impl!(u64);
+byte_repr_impl!(u128);
+byte_repr_impl!(usize);
+
+/// Iterator returned by [`bitfields_from_bytes`].
+#[derive(Debug, Clone)]
+pub struct BitfieldsFromBytes<'a, B, T> {
+    chunks: core::slice::ChunksExact<'a, u8>,
+    order: ByteOrder,
+    bitfield: PhantomData<fn() -> (B, T)>,
+}
+
+impl<B, T> Iterator for BitfieldsFromBytes<'_, B, T>
+where
+    B: Bitfield<T>,
+    T: ByteRepr,
+{
+    type Item = B;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<B> {
+        self.chunks.next().map(|chunk| B::new(T::read_bytes(chunk, self.order)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<B, T> DoubleEndedIterator for BitfieldsFromBytes<'_, B, T>
+where
+    B: Bitfield<T>,
+    T: ByteRepr,
+{
+    #[inline(always)]
+    fn next_back(&mut self) -> Option<B> {
+        self.chunks.next_back().map(|chunk| B::new(T::read_bytes(chunk, self.order)))
+    }
+}
+
+impl<B, T> ExactSizeIterator for BitfieldsFromBytes<'_, B, T>
+where
+    B: Bitfield<T>,
+    T: ByteRepr,
+{
+}
+
+/// Interpret a byte slice as a sequence of bitfield values.
+///
+/// Returns `None` if the length of `bytes` is not a multiple of the size of the
+/// bitfield's underlying type.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, bitfields_from_bytes, ByteOrder};
+/// bitfield! {
+///     pub struct Descriptor(u16) {
+///         [0..15] pub length: u16,
+///         [15]    pub last,
+///     }
+/// }
+///
+/// let ring = [0x40, 0x00, 0x08, 0x80];
+/// let mut descriptors =
+///     bitfields_from_bytes::<Descriptor, _>(&ring, ByteOrder::Little).unwrap();
+/// assert_eq!(descriptors.next(), Some(Descriptor(0x0040)));
+/// assert_eq!(descriptors.next(), Some(Descriptor(0x8008)));
+/// assert_eq!(descriptors.next(), None);
+///
+/// assert!(bitfields_from_bytes::<Descriptor, _>(&ring[..3], ByteOrder::Big).is_none());
+/// ```
+pub fn bitfields_from_bytes<B, T>(
+    bytes: &[u8],
+    order: ByteOrder,
+) -> Option<BitfieldsFromBytes<'_, B, T>>
+where
+    B: Bitfield<T>,
+    T: ByteRepr,
+{
+    let chunks = bytes.chunks_exact(T::SIZE);
+    if !chunks.remainder().is_empty() {
+        return None;
+    }
+    Some(BitfieldsFromBytes { chunks, order, bitfield: PhantomData })
+}
+
+/// Fill a slice of bitfields from their byte representations.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, copy_bitfields_from_bytes, ByteOrder};
+/// # bitfield! {
+/// #     pub struct Descriptor(u16) {
+/// #         [0..15] pub length: u16,
+/// #         [15]    pub last,
+/// #     }
+/// # }
+/// let mut descriptors = [Descriptor::default(); 2];
+/// copy_bitfields_from_bytes(&mut descriptors, &[0x00, 0x40, 0x80, 0x08], ByteOrder::Big)
+///     .unwrap();
+/// assert_eq!(descriptors, [Descriptor(0x0040), Descriptor(0x8008)]);
+/// ```
+///
+/// # Errors
+///
+/// Returns an error without modifying `dest` if the length of `bytes` does not match
+/// the combined size of the bitfields in `dest`.
+pub fn copy_bitfields_from_bytes<B, T>(
+    dest: &mut [B],
+    bytes: &[u8],
+    order: ByteOrder,
+) -> Result<(), LengthMismatchError>
+where
+    B: Bitfield<T>,
+    T: ByteRepr,
+{
+    check_length::<T>(dest.len(), bytes.len())?;
+    for (bitfield, chunk) in dest.iter_mut().zip(bytes.chunks_exact(T::SIZE)) {
+        *bitfield = B::new(T::read_bytes(chunk, order));
+    }
+    Ok(())
+}
+
+/// Write a slice of bitfields to their byte representations.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, copy_bitfields_to_bytes, ByteOrder};
+/// # bitfield! {
+/// #     pub struct Descriptor(u16) {
+/// #         [0..15] pub length: u16,
+/// #         [15]    pub last,
+/// #     }
+/// # }
+/// let descriptors = [Descriptor(0x0040), Descriptor(0x8008)];
+/// let mut ring = [0; 4];
+/// copy_bitfields_to_bytes(&descriptors, &mut ring, ByteOrder::Little).unwrap();
+/// assert_eq!(ring, [0x40, 0x00, 0x08, 0x80]);
+///
+/// let err = copy_bitfields_to_bytes(&descriptors, &mut ring[..3], ByteOrder::Little);
+/// let message = err.unwrap_err().to_string();
+/// assert_eq!(message, "expected 4 bytes for the bitfields, found 3");
+/// ```
+///
+/// # Errors
+///
+/// Returns an error without modifying `dest` if the length of `dest` does not match the
+/// combined size of the bitfields in `src`.
+pub fn copy_bitfields_to_bytes<B, T>(
+    src: &[B],
+    dest: &mut [u8],
+    order: ByteOrder,
+) -> Result<(), LengthMismatchError>
+where
+    B: Bitfield<T>,
+    T: ByteRepr,
+{
+    check_length::<T>(src.len(), dest.len())?;
+    for (bitfield, chunk) in src.iter().zip(dest.chunks_exact_mut(T::SIZE)) {
+        bitfield.value().write_bytes(chunk, order);
+    }
+    Ok(())
+}
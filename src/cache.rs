@@ -0,0 +1,153 @@
+use crate::{Bitfield, FieldInfo, FieldLayout, FieldStorage, PackedBits};
+use core::fmt;
+use core::marker::PhantomData;
+
+/// A shadow copy of a register that tracks which bits have changed since it was last
+/// written to the device, so writes over a slow bus can be skipped when nothing changed.
+///
+/// The bits are compared with the value that was last loaded or flushed, so a field that
+/// is changed and then changed back isn't dirty. After the device may have changed the
+/// register on its own, [`invalidate`](Self::invalidate) makes the next flush write it
+/// regardless.
+///
+/// The write function passed to [`flush`](Self::flush) can be anything that writes the
+/// register, like the [`write`](crate::Register::write) method of a
+/// [`Register`](crate::Register) handle.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, Cached};
+/// bitfield! {
+///     pub struct Control(u16) {
+///         [0..4]  pub mode: u8,
+///         [4]     pub enable,
+///         [8..16] pub divider: u8,
+///     }
+/// }
+///
+/// let mut writes = Vec::new();
+/// let mut write = |value: Control| -> Result<(), ()> {
+///     writes.push(value);
+///     Ok(())
+/// };
+///
+/// let mut cr = Cached::new(Control(0x0401));
+/// cr.update(|c| c.set_mode(1));
+/// assert_eq!(cr.flush(&mut write), Ok(false));
+///
+/// cr.update(|c| c.set_enable(true));
+/// assert_eq!(cr.dirty_bits(), 0x0010);
+/// assert_eq!(cr.dirty_fields().map(|f| f.name).collect::<Vec<_>>(), ["enable"]);
+/// assert_eq!(cr.flush(&mut write), Ok(true));
+/// assert!(!cr.is_dirty());
+///
+/// assert_eq!(writes, [Control(0x0411)]);
+/// ```
+pub struct Cached<B, T> {
+    value: B,
+    clean: B,
+    invalid: bool,
+    underlying: PhantomData<T>,
+}
+
+impl<B, T> Cached<B, T>
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits,
+{
+    /// Cache a value that was just read from or written to the device
+    #[inline(always)]
+    pub fn new(value: B) -> Self {
+        Self { value, clean: value, invalid: false, underlying: PhantomData }
+    }
+
+    /// The cached value of the register
+    #[inline(always)]
+    pub fn get(&self) -> B {
+        self.value
+    }
+
+    /// Replace the cached value of the register
+    #[inline(always)]
+    pub fn set(&mut self, value: B) {
+        self.value = value;
+    }
+
+    /// Modify the cached value with the given function
+    pub fn update<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut B) -> R,
+    {
+        f(&mut self.value)
+    }
+
+    /// A mask of the bits that differ from the value on the device
+    pub fn dirty_bits(&self) -> u128 {
+        if self.invalid {
+            return u128::MAX >> (128 - T::MAX_BITS);
+        }
+        let value = crate::packed_value(self.value).into_u128();
+        let clean = crate::packed_value(self.clean).into_u128();
+        value ^ clean
+    }
+
+    /// Whether any bits differ from the value on the device
+    pub fn is_dirty(&self) -> bool {
+        self.dirty_bits() != 0
+    }
+
+    /// The fields with any bits that differ from the value on the device, in the order
+    /// they were declared
+    pub fn dirty_fields(&self) -> impl Iterator<Item = &'static FieldInfo> {
+        let dirty = self.dirty_bits();
+        B::LAYOUT.iter().filter(move |field| field.mask() & dirty != 0)
+    }
+
+    /// Write the cached value with the given function if any bits are dirty, and mark it
+    /// clean if that succeeds. Returns whether the value was written.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from the write function, in which case the value stays dirty.
+    pub fn flush<F, E>(&mut self, write: F) -> Result<bool, E>
+    where
+        F: FnOnce(B) -> Result<(), E>,
+    {
+        if !self.is_dirty() {
+            return Ok(false);
+        }
+        write(self.value)?;
+        self.clean = self.value;
+        self.invalid = false;
+        Ok(true)
+    }
+
+    /// Replace both the cached value and the value it is compared to, after reading the
+    /// register from the device
+    #[inline(always)]
+    pub fn reload(&mut self, value: B) {
+        *self = Self::new(value);
+    }
+
+    /// Mark every bit dirty, so the next flush writes the value even if it hasn't
+    /// changed
+    #[inline(always)]
+    pub fn invalidate(&mut self) {
+        self.invalid = true;
+    }
+
+    /// Stop caching and return the cached value
+    #[inline(always)]
+    pub fn into_inner(self) -> B {
+        self.value
+    }
+}
+
+impl<B, T> fmt::Debug for Cached<B, T>
+where
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Cached").field(&self.value).finish()
+    }
+}
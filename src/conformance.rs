@@ -0,0 +1,105 @@
+use crate::{
+    from_packed_value, packed_value, set_bit, set_bits, Bitfield, FieldStorage,
+    OverflowingShl, TruncateInto,
+};
+use core::fmt::Debug;
+use core::ops;
+
+/// Check that the accessors for a single field only touch the bits in `lsb..msb`, and
+/// that every bit pattern in that range survives a round trip through the getter and
+/// setter. `copy_field` must return its first argument with the field set to its value
+/// in the second argument.
+///
+/// # Panics
+///
+/// Panics with a message naming the field if any check fails.
+#[doc(hidden)]
+pub fn assert_field_layout<B, T, S>(
+    name: &str,
+    lsb: u8,
+    msb: u8,
+    copy_field: impl Fn(B, B) -> B,
+) where
+    B: Bitfield<T>,
+    T: FieldStorage<Storage = S> + Into<S>,
+    S: TruncateInto<T>
+        + Default
+        + Copy
+        + PartialEq
+        + Debug
+        + From<bool>
+        + OverflowingShl
+        + ops::Shl<u8, Output = S>
+        + ops::Not<Output = S>
+        + ops::BitAnd<S, Output = S>
+        + ops::BitOr<S, Output = S>,
+{
+    assert!(lsb < msb, "field {name} has an empty bit range {lsb}..{msb}");
+    assert!(
+        msb <= T::MAX_BITS,
+        "field {name} bit range {lsb}..{msb} extends past the end of the underlying type",
+    );
+
+    let zero = S::default();
+    let ones = !zero;
+
+    // All ones, plus walking ones and walking zeros through the field
+    let patterns = core::iter::once(ones).chain(
+        (lsb..msb).flat_map(|bit| [set_bit(zero, bit, true), set_bit(ones, bit, false)]),
+    );
+    for pattern in patterns {
+        for background in [zero, ones] {
+            assert_field_copy(
+                name,
+                lsb,
+                msb,
+                &copy_field,
+                from_packed_value(background),
+                from_packed_value(pattern),
+            );
+        }
+    }
+}
+
+/// Check that copying a single field from `pattern` onto `background` changes exactly
+/// the bits in `lsb..msb`, and that they match `pattern`. `copy_field` must return its
+/// first argument with the field set to its value in the second argument.
+///
+/// # Panics
+///
+/// Panics with a message naming the field if the check fails.
+#[doc(hidden)]
+pub fn assert_field_copy<B, T, S>(
+    name: &str,
+    lsb: u8,
+    msb: u8,
+    copy_field: impl Fn(B, B) -> B,
+    background: B,
+    pattern: B,
+) where
+    B: Bitfield<T>,
+    T: FieldStorage<Storage = S> + Into<S>,
+    S: TruncateInto<T>
+        + Default
+        + Copy
+        + PartialEq
+        + Debug
+        + OverflowingShl
+        + ops::Shl<u8, Output = S>
+        + ops::Not<Output = S>
+        + ops::BitAnd<S, Output = S>
+        + ops::BitOr<S, Output = S>,
+{
+    let zero = S::default();
+    let mask = set_bits(zero, lsb, msb, !zero);
+    let (background, pattern) = (packed_value(background), packed_value(pattern));
+    let expected = pattern & mask | background & !mask;
+    let actual = packed_value(copy_field(
+        from_packed_value(background),
+        from_packed_value(pattern),
+    ));
+    assert_eq!(
+        actual, expected,
+        "field {name} did not round-trip {pattern:?} onto {background:?}",
+    );
+}
@@ -0,0 +1,68 @@
+use core::marker::PhantomData;
+
+// Accessors convert between the underlying and interface types of a field with `Into`,
+// but core doesn't implement `From<u32>` for `f32`, and the orphan rule prevents us from
+// doing it here. Like nested field parsing, these types use autoref-based specialization
+// to convert some types directly, while everything else falls back to `Into`. Direct
+// conversions reinterpret the bits of floating-point fields, mask the bits of
+// exact-width integer fields instead of the range check that `UInt::new` needs, and read
+// single-bit `bool` fields from their `u8` storage.
+
+#[doc(hidden)]
+pub struct FieldConversion<T, U>(PhantomData<fn(T) -> U>);
+
+#[allow(clippy::new_without_default)]
+impl<T, U> FieldConversion<T, U> {
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait FieldConversionDirect<T, U> {
+    fn convert_field(&self, value: T) -> U;
+}
+
+macro_rules! float_conversion_impl {
+    ($bits:ty, $float:ty) => {
+        impl FieldConversionDirect<$bits, $float> for FieldConversion<$bits, $float> {
+            #[inline(always)]
+            fn convert_field(&self, value: $bits) -> $float {
+                <$float>::from_bits(value)
+            }
+        }
+
+        impl FieldConversionDirect<$float, $bits> for FieldConversion<$float, $bits> {
+            #[inline(always)]
+            fn convert_field(&self, value: $float) -> $bits {
+                value.to_bits()
+            }
+        }
+    };
+}
+
+float_conversion_impl!(u32, f32);
+float_conversion_impl!(u64, f64);
+
+impl FieldConversionDirect<u8, bool> for FieldConversion<u8, bool> {
+    #[inline(always)]
+    fn convert_field(&self, value: u8) -> bool {
+        value != 0
+    }
+}
+
+#[doc(hidden)]
+pub trait FieldConversionFallback<T, U> {
+    fn convert_field(&self, value: T) -> U;
+}
+
+impl<T, U> FieldConversionFallback<T, U> for &FieldConversion<T, U>
+where
+    T: Into<U>,
+{
+    #[inline(always)]
+    fn convert_field(&self, value: T) -> U {
+        value.into()
+    }
+}
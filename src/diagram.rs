@@ -0,0 +1,198 @@
+use crate::format::Counter;
+use crate::{Bitfield, FieldInfo, FieldLayout, FieldStorage, PackedBits};
+use core::fmt::{self, Write};
+use core::marker::PhantomData;
+
+/// A packet diagram of a bitfield value, for pasting register states into bug reports
+/// and reviews.
+///
+/// This is returned by the `layout_diagram` method generated by the
+/// [`bitfield`](crate::bitfield) macro. Its [`Display`](fmt::Display) implementation
+/// draws one box per field, from the most significant bit on the left, with the bit
+/// numbers above and the value below the name of the field. Single-bit fields are
+/// shown as `0` or `1` and other fields in hexadecimal, like
+/// [`WriteFields`](crate::WriteFields). Bits that aren't part of any field are shown
+/// in boxes named `-`. Where fields overlap, only the one that ends at the higher bit
+/// is shown.
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Status(u16) {
+///         [0..4]  pub mode: u8,
+///         [4]     pub ready,
+///         [8..16] pub count: u8,
+///     }
+/// }
+///
+/// let expected = [
+///     " 15    8 7   5    4    3    0",
+///     "+-------+-----+-------+------+",
+///     "| count |  -  | ready | mode |",
+///     "| 0x2a  | 0x0 |   1   | 0xb  |",
+///     "+-------+-----+-------+------+",
+/// ];
+/// assert_eq!(Status(0x2a1b).layout_diagram().to_string(), expected.join("\n"));
+/// ```
+pub struct LayoutDiagram<B, T> {
+    value: B,
+    underlying: PhantomData<T>,
+}
+
+impl<B, T> LayoutDiagram<B, T>
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits,
+{
+    /// Draw a diagram of the given bitfield value
+    #[inline(always)]
+    pub fn new(value: B) -> Self {
+        Self { value, underlying: PhantomData }
+    }
+
+    fn segments() -> Segments {
+        Segments { layout: B::LAYOUT, cursor: T::MAX_BITS }
+    }
+
+    fn field_value(&self, segment: &FieldInfo) -> u128 {
+        crate::packed_value(self.value).packed_field(segment.lsb, segment.msb).into_u128()
+    }
+
+    /// The number of characters inside the box of a segment
+    fn width(&self, segment: &FieldInfo) -> usize {
+        let ruler = if segment.width() == 1 {
+            digits(segment.lsb)
+        } else {
+            digits(segment.msb - 1) + 1 + digits(segment.lsb)
+        };
+        let mut value = Counter(0);
+        let _ = write_value(&mut value, segment, self.field_value(segment), 0);
+        segment.name.chars().count().max(value.0).max(ruler) + 2
+    }
+
+    fn border(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_char('+')?;
+        for segment in Self::segments() {
+            for _ in 0..self.width(&segment) {
+                f.write_char('-')?;
+            }
+            f.write_char('+')?;
+        }
+        Ok(())
+    }
+}
+
+impl<B, T> fmt::Display for LayoutDiagram<B, T>
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Padding is held back until the next number, so that lines don't end in spaces
+        let mut pending = 0;
+        for segment in Self::segments() {
+            let width = self.width(&segment);
+            if segment.width() == 1 {
+                let left = (width - digits(segment.lsb)) / 2;
+                write!(f, "{:pad$}{}", "", segment.lsb, pad = pending + 1 + left)?;
+                pending = width - digits(segment.lsb) - left;
+            } else {
+                let msb = segment.msb - 1;
+                let gap = width - digits(msb) - digits(segment.lsb);
+                write!(
+                    f,
+                    "{:pad$}{msb}{:gap$}{}",
+                    "",
+                    "",
+                    segment.lsb,
+                    pad = pending + 1
+                )?;
+                pending = 0;
+            }
+        }
+        f.write_char('\n')?;
+        self.border(f)?;
+
+        f.write_str("\n|")?;
+        for segment in Self::segments() {
+            write!(f, "{:^width$}|", segment.name, width = self.width(&segment))?;
+        }
+
+        f.write_str("\n|")?;
+        for segment in Self::segments() {
+            let width = self.width(&segment);
+            write_value(f, &segment, self.field_value(&segment), width)?;
+            f.write_char('|')?;
+        }
+
+        f.write_char('\n')?;
+        self.border(f)
+    }
+}
+
+impl<B, T> fmt::Debug for LayoutDiagram<B, T>
+where
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("LayoutDiagram").field(&self.value).finish()
+    }
+}
+
+/// The fields of a layout from the most significant bit down, with the gaps between
+/// them as fields named `-`
+struct Segments {
+    layout: &'static [FieldInfo],
+    cursor: u8,
+}
+
+impl Iterator for Segments {
+    type Item = FieldInfo;
+
+    fn next(&mut self) -> Option<FieldInfo> {
+        if self.cursor == 0 {
+            return None;
+        }
+        // The field that ends closest to the cursor, preferring the narrowest
+        let next = self
+            .layout
+            .iter()
+            .filter(|field| field.msb <= self.cursor && field.msb > field.lsb)
+            .min_by_key(|field| (self.cursor - field.msb, field.width()));
+        let segment = match next {
+            Some(field) if field.msb == self.cursor => *field,
+            Some(field) => {
+                FieldInfo { name: "-", lsb: field.msb, msb: self.cursor, flag: false }
+            }
+            None => FieldInfo { name: "-", lsb: 0, msb: self.cursor, flag: false },
+        };
+        self.cursor = segment.lsb;
+        Some(segment)
+    }
+}
+
+fn write_value<W>(
+    w: &mut W,
+    segment: &FieldInfo,
+    value: u128,
+    width: usize,
+) -> fmt::Result
+where
+    W: Write + ?Sized,
+{
+    if segment.flag {
+        write!(w, "{value:^width$}")
+    } else {
+        write!(w, "{value:^#width$x}")
+    }
+}
+
+fn digits(n: u8) -> usize {
+    match n {
+        0..=9 => 1,
+        10..=99 => 2,
+        _ => 3,
+    }
+}
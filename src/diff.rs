@@ -0,0 +1,162 @@
+use crate::format::{write_bits, Counter};
+use crate::{Bitfield, FieldInfo, FieldLayout, FieldStorage, PackedBits};
+use core::fmt::{self, Write};
+use core::marker::PhantomData;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+
+/// Two values of a bitfield side by side, with the fields that changed highlighted, for
+/// spotting the difference between register dumps in a terminal.
+///
+/// The [`Display`](fmt::Display) implementation writes a table like
+/// [`write_field_table`](crate::write_field_table), with the old and new value of
+/// each field in hexadecimal. Rows for fields that changed start with `*`, and unless
+/// colors are turned off, the name of the field is bold, the old value is red, and the
+/// new value is green, using ANSI escape codes. Colors are on by default unless the
+/// `NO_COLOR` environment variable is set.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, FieldDiff};
+/// bitfield! {
+///     pub struct Status(u16) {
+///         [0..4]  pub mode: u8,
+///         [4]     pub ready,
+///         [8..16] pub count: u8,
+///     }
+/// }
+///
+/// let diff = FieldDiff::new(Status(0x2a1b), Status(0x2a03)).with_color(false);
+/// assert_eq!(
+///     diff.to_string(),
+///     "  field | bits  |  old |  new
+/// --------+-------+------+-----
+/// * mode  | 0..4  |  0xb |  0x3
+/// * ready | 4     |  0x1 |  0x0
+///   count | 8..16 | 0x2a | 0x2a
+/// ",
+/// );
+/// ```
+pub struct FieldDiff<B, T> {
+    old: B,
+    new: B,
+    color: bool,
+    underlying: PhantomData<T>,
+}
+
+impl<B, T> FieldDiff<B, T>
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits,
+{
+    /// Compare an old and a new value of a bitfield
+    pub fn new(old: B, new: B) -> Self {
+        let color = std::env::var_os("NO_COLOR").is_none();
+        Self { old, new, color, underlying: PhantomData }
+    }
+
+    /// Turn the ANSI colors on or off
+    #[must_use]
+    pub fn with_color(self, color: bool) -> Self {
+        Self { color, ..self }
+    }
+
+    /// The names of the fields that changed, in the order they were declared
+    pub fn changed(&self) -> impl Iterator<Item = &'static str> + '_ {
+        B::LAYOUT.iter().filter(|field| self.is_changed(field)).map(|field| field.name)
+    }
+
+    fn is_changed(&self, field: &FieldInfo) -> bool {
+        crate::raw_field(self.old, field.lsb, field.msb)
+            != crate::raw_field(self.new, field.lsb, field.msb)
+    }
+
+    fn paint(&self, f: &mut fmt::Formatter, style: &str, changed: bool) -> fmt::Result {
+        if self.color && changed {
+            f.write_str(style)?;
+        }
+        Ok(())
+    }
+}
+
+impl<B, T> fmt::Display for FieldDiff<B, T>
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut widths = ["field".len(), "bits".len(), "old".len(), "new".len()];
+        for field in B::LAYOUT {
+            let mut bits = Counter(0);
+            write_bits(&mut bits, field)?;
+            let mut old = Counter(0);
+            write!(old, "{:#x}", crate::raw_field(self.old, field.lsb, field.msb))?;
+            let mut new = Counter(0);
+            write!(new, "{:#x}", crate::raw_field(self.new, field.lsb, field.msb))?;
+            let row = [field.name.chars().count(), bits.0, old.0, new.0];
+            for (width, len) in widths.iter_mut().zip(row) {
+                *width = (*width).max(len);
+            }
+        }
+        let [name_width, bits_width, old_width, new_width] = widths;
+
+        writeln!(
+            f,
+            "  {:name_width$} | {:bits_width$} | {:>old_width$} | {:>new_width$}",
+            "field", "bits", "old", "new",
+        )?;
+        writeln!(
+            f,
+            "--{:-<name_width$}-+-{:-<bits_width$}-+-{:-<old_width$}-+-{:-<new_width$}",
+            "", "", "", "",
+        )?;
+        for field in B::LAYOUT {
+            let changed = self.is_changed(field);
+            f.write_str(if changed { "* " } else { "  " })?;
+            self.paint(f, BOLD, changed)?;
+            write!(f, "{:name_width$}", field.name)?;
+            self.paint(f, RESET, changed)?;
+
+            f.write_str(" | ")?;
+            let mut bits = Counter(0);
+            write_bits(&mut bits, field)?;
+            write_bits(f, field)?;
+            write!(f, "{:pad$} | ", "", pad = bits_width - bits.0)?;
+
+            self.paint(f, RED, changed)?;
+            write!(
+                f,
+                "{:>#old_width$x}",
+                crate::raw_field(self.old, field.lsb, field.msb)
+            )?;
+            self.paint(f, RESET, changed)?;
+            f.write_str(" | ")?;
+            self.paint(f, GREEN, changed)?;
+            write!(
+                f,
+                "{:>#new_width$x}",
+                crate::raw_field(self.new, field.lsb, field.msb)
+            )?;
+            self.paint(f, RESET, changed)?;
+            f.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+impl<B, T> fmt::Debug for FieldDiff<B, T>
+where
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FieldDiff")
+            .field("old", &self.old)
+            .field("new", &self.new)
+            .field("color", &self.color)
+            .finish()
+    }
+}
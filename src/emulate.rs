@@ -0,0 +1,166 @@
+use crate::{FieldInfo, FieldLayout};
+use core::fmt;
+
+/// Side effects of accessing the fields of an [`Emulated`] register.
+///
+/// Each method is called once for every field of the register, in the order they were
+/// declared, with the name and position of the field. All of them do nothing by
+/// default, so an implementation only needs to match the fields that have side effects.
+pub trait EmulatorHooks<B> {
+    /// Called before the register is read, to update the value that the reader will
+    /// see, like loading the next byte of a FIFO into a data field.
+    #[inline(always)]
+    fn before_read(&mut self, field: &FieldInfo, value: &mut B) {
+        let _ = (field, value);
+    }
+
+    /// Called after the register is read, to update the value that later reads will
+    /// see, like clearing a clear-on-read flag. Changes don't affect the value that was
+    /// just read.
+    #[inline(always)]
+    fn after_read(&mut self, field: &FieldInfo, value: &mut B) {
+        let _ = (field, value);
+    }
+
+    /// Called when the register is written, to compute the value that is stored from
+    /// the `old` value and the `value` being written, like clearing the flags that are
+    /// written with a one. This is called for every field, even if it is written with the
+    /// value it already had.
+    #[inline(always)]
+    fn on_write(&mut self, field: &FieldInfo, old: B, value: &mut B) {
+        let _ = (field, old, value);
+    }
+}
+
+/// A bitfield used as the model of a device register, with side effects when it is read
+/// or written. See [`EmulatorHooks`].
+///
+/// The [`read`](Self::read) and [`write`](Self::write) methods are for accesses by the
+/// emulated software, and they call the hooks. The device model itself uses
+/// [`get`](Self::get), [`set`](Self::set), and [`update`](Self::update), which don't.
+///
+/// ```
+/// # use std::collections::VecDeque;
+/// # use tartan_bitfield::{bitfield, Emulated, EmulatorHooks, FieldInfo};
+/// bitfield! {
+///     pub struct Uart(u16) {
+///         [0..8] pub data: u8,
+///         [8]    pub rx_ready,
+///         [9]    pub overrun,
+///     }
+/// }
+///
+/// struct Device {
+///     fifo: VecDeque<u8>,
+/// }
+///
+/// impl EmulatorHooks<Uart> for Device {
+///     fn before_read(&mut self, field: &FieldInfo, value: &mut Uart) {
+///         if field.name == "data" {
+///             value.set_data(self.fifo.pop_front().unwrap_or(0));
+///             value.set_rx_ready(!self.fifo.is_empty());
+///         }
+///     }
+///
+///     fn after_read(&mut self, field: &FieldInfo, value: &mut Uart) {
+///         if field.name == "overrun" {
+///             value.set_overrun(false);
+///         }
+///     }
+/// }
+///
+/// let device = Device { fifo: VecDeque::from([b'h', b'i']) };
+/// let mut uart = Emulated::new(Uart::default().with_overrun(true), device);
+///
+/// let first = uart.read();
+/// assert_eq!((first.data(), first.rx_ready(), first.overrun()), (b'h', true, true));
+/// let second = uart.read();
+/// assert_eq!(second.data(), b'i');
+/// assert!(!second.rx_ready() && !second.overrun());
+/// assert!(uart.hooks().fifo.is_empty());
+/// ```
+pub struct Emulated<B, H> {
+    value: B,
+    hooks: H,
+}
+
+impl<B, H> Emulated<B, H>
+where
+    B: FieldLayout + Copy,
+    H: EmulatorHooks<B>,
+{
+    /// Model a register with the given initial value and side effects
+    #[inline(always)]
+    pub fn new(value: B, hooks: H) -> Self {
+        Self { value, hooks }
+    }
+
+    /// Read the register, with side effects
+    pub fn read(&mut self) -> B {
+        for field in B::LAYOUT {
+            self.hooks.before_read(field, &mut self.value);
+        }
+        let result = self.value;
+        for field in B::LAYOUT {
+            self.hooks.after_read(field, &mut self.value);
+        }
+        result
+    }
+
+    /// Write the register, with side effects
+    pub fn write(&mut self, value: B) {
+        let old = self.value;
+        let mut new = value;
+        for field in B::LAYOUT {
+            self.hooks.on_write(field, old, &mut new);
+        }
+        self.value = new;
+    }
+
+    /// The current value of the register, without side effects
+    #[inline(always)]
+    pub fn get(&self) -> B {
+        self.value
+    }
+
+    /// Replace the value of the register, without side effects
+    #[inline(always)]
+    pub fn set(&mut self, value: B) {
+        self.value = value;
+    }
+
+    /// Modify the value of the register with the given function, without side effects
+    pub fn update<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut B) -> R,
+    {
+        f(&mut self.value)
+    }
+
+    /// The side effects of the register, which usually hold the rest of the device state
+    #[inline(always)]
+    pub fn hooks(&self) -> &H {
+        &self.hooks
+    }
+
+    /// The side effects of the register, mutably
+    #[inline(always)]
+    pub fn hooks_mut(&mut self) -> &mut H {
+        &mut self.hooks
+    }
+
+    /// Stop emulating the register and return its current value and side effects
+    #[inline(always)]
+    pub fn into_inner(self) -> (B, H) {
+        (self.value, self.hooks)
+    }
+}
+
+impl<B, H> fmt::Debug for Emulated<B, H>
+where
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Emulated").field(&self.value).finish()
+    }
+}
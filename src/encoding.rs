@@ -0,0 +1,453 @@
+// Interface types for fields with special encodings. These are meant to be used with the
+// `A as B` syntax of the `bitfield!` macro.
+
+use crate::{get_bit, ByteRepr};
+use core::{fmt, ops};
+
+/// A binary-coded decimal value, with one decimal digit in each 4-bit nibble of the
+/// underlying integer `T`.
+///
+/// The raw bits are not validated when a bitfield is read, since hardware can produce
+/// anything. Use [`try_to_binary`](Self::try_to_binary) to detect invalid digits.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, Bcd};
+/// bitfield! {
+///     pub struct RtcTime(u32) {
+///         [ 0.. 7] pub seconds: u8 as Bcd<u8>,
+///         [ 8..15] pub minutes: u8 as Bcd<u8>,
+///         [16..22] pub hours: u8 as Bcd<u8>,
+///     }
+/// }
+///
+/// let time = RtcTime(0x0023_5907);
+/// assert_eq!(time.hours().to_binary(), 23);
+/// assert_eq!(time.minutes().to_binary(), 59);
+/// assert_eq!(time.seconds().to_binary(), 7);
+///
+/// let later = time.with_seconds(Bcd::from_binary(42));
+/// assert_eq!(later, RtcTime(0x0023_5942));
+///
+/// let garbage = RtcTime(0x0000_003c);
+/// assert_eq!(garbage.seconds().try_to_binary(), None);
+/// ```
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bcd<T> {
+    raw: T,
+}
+
+impl<T> Bcd<T>
+where
+    T: BcdRepr,
+{
+    /// The largest value that can be represented
+    pub const MAX: T = T::BCD_MAX;
+
+    /// Create a value from its raw BCD representation
+    pub const fn from_raw(raw: T) -> Self {
+        Self { raw }
+    }
+
+    /// The raw BCD representation of the value
+    pub fn raw(self) -> T {
+        self.raw
+    }
+
+    /// Encode a binary value as BCD. If the value is greater than [`MAX`](Self::MAX),
+    /// the most significant decimal digits are discarded.
+    pub fn from_binary(value: T) -> Self {
+        Self { raw: value.encode_bcd() }
+    }
+
+    /// Encode a binary value as BCD, or return `None` if it is greater than
+    /// [`MAX`](Self::MAX).
+    pub fn try_from_binary(value: T) -> Option<Self> {
+        if value > T::BCD_MAX {
+            None
+        } else {
+            Some(Self::from_binary(value))
+        }
+    }
+
+    /// Decode the BCD value to binary. Nibbles with invalid digit values (`a` through
+    /// `f`) are not checked, and will produce meaningless results.
+    pub fn to_binary(self) -> T {
+        self.raw.decode_bcd()
+    }
+
+    /// Decode the BCD value to binary, or return `None` if any nibble is not a valid
+    /// decimal digit.
+    pub fn try_to_binary(self) -> Option<T> {
+        if self.raw.is_valid_bcd() {
+            Some(self.to_binary())
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> fmt::Debug for Bcd<T>
+where
+    T: fmt::LowerHex,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Bcd({:#x})", self.raw)
+    }
+}
+
+/// An integer type that can hold a [`Bcd`] value.
+pub trait BcdRepr
+where
+    Self: Sized + Copy + PartialOrd,
+{
+    /// The largest binary value that can be encoded in this type
+    const BCD_MAX: Self;
+
+    /// Encode a binary value as BCD, discarding digits that don't fit
+    #[must_use]
+    fn encode_bcd(self) -> Self;
+
+    /// Decode a BCD value to binary, without checking that the digits are valid
+    #[must_use]
+    fn decode_bcd(self) -> Self;
+
+    /// True if every nibble of the value is a valid decimal digit
+    fn is_valid_bcd(self) -> bool;
+}
+
+macro_rules! bcd_repr_impl {
+    ($type:ty) => {
+        impl BcdRepr for $type {
+            const BCD_MAX: Self = {
+                let mut max: $type = 0;
+                let mut i = 0;
+                while i < <$type>::BITS / 4 {
+                    max = max * 10 + 9;
+                    i += 1;
+                }
+                max
+            };
+
+            fn encode_bcd(mut self) -> Self {
+                let mut raw = 0;
+                for i in 0..<$type>::BITS / 4 {
+                    raw |= (self % 10) << (4 * i);
+                    self /= 10;
+                }
+                raw
+            }
+
+            fn decode_bcd(self) -> Self {
+                (0..<$type>::BITS / 4).rev().fold(0, |value: $type, i| {
+                    let digit = (self >> (4 * i)) & 0xf;
+                    value.wrapping_mul(10).wrapping_add(digit)
+                })
+            }
+
+            fn is_valid_bcd(self) -> bool {
+                (0..<$type>::BITS / 4).all(|i| (self >> (4 * i)) & 0xf <= 9)
+            }
+        }
+
+        impl From<$type> for Bcd<$type> {
+            #[inline(always)]
+            fn from(raw: $type) -> Self {
+                Self::from_raw(raw)
+            }
+        }
+
+        impl From<Bcd<$type>> for $type {
+            #[inline(always)]
+            fn from(value: Bcd<$type>) -> Self {
+                value.raw
+            }
+        }
+    };
+}
+
+bcd_repr_impl!(u8);
+bcd_repr_impl!(u16);
+bcd_repr_impl!(u32);
+bcd_repr_impl!(u64);
+bcd_repr_impl!(u128);
+
+/// A value encoded as a reflected binary Gray code, where consecutive values differ by
+/// only one bit. This is common for rotary encoder positions and some ADC outputs.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, Gray};
+/// bitfield! {
+///     pub struct Encoder(u16) {
+///         [0..10] pub position: u16 as Gray<u16>,
+///         [15]    pub index,
+///     }
+/// }
+///
+/// let x = Encoder(0x8183);
+/// assert_eq!(x.position().to_binary(), 0x102);
+///
+/// let y = x.with_position(Gray::from_binary(0x103));
+/// assert_eq!(y, Encoder(0x8182));
+/// ```
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Gray<T> {
+    raw: T,
+}
+
+impl<T> Gray<T>
+where
+    T: Default
+        + Copy
+        + PartialEq
+        + ops::BitXor<Output = T>
+        + ops::BitXorAssign
+        + ops::Shr<u8, Output = T>,
+{
+    /// Create a value from its raw Gray code representation
+    pub const fn from_raw(raw: T) -> Self {
+        Self { raw }
+    }
+
+    /// The raw Gray code representation of the value
+    pub fn raw(self) -> T {
+        self.raw
+    }
+
+    /// Encode a binary value as Gray code
+    pub fn from_binary(value: T) -> Self {
+        Self { raw: value ^ (value >> 1) }
+    }
+
+    /// Decode the Gray code value to binary
+    pub fn to_binary(self) -> T {
+        let mut value = self.raw;
+        let mut shifted = self.raw >> 1;
+        while shifted != T::default() {
+            value ^= shifted;
+            shifted = shifted >> 1;
+        }
+        value
+    }
+}
+
+impl<T> From<T> for Gray<T> {
+    #[inline(always)]
+    fn from(raw: T) -> Self {
+        Self { raw }
+    }
+}
+
+macro_rules! gray_conversion_impl {
+    ($type:ty) => {
+        impl From<Gray<$type>> for $type {
+            #[inline(always)]
+            fn from(value: Gray<$type>) -> Self {
+                value.raw
+            }
+        }
+    };
+}
+
+gray_conversion_impl!(u8);
+gray_conversion_impl!(u16);
+gray_conversion_impl!(u32);
+gray_conversion_impl!(u64);
+gray_conversion_impl!(u128);
+
+impl<T> fmt::Debug for Gray<T>
+where
+    T: fmt::LowerHex,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Gray({:#x})", self.raw)
+    }
+}
+
+/// A one-hot value, where a single bit is set to indicate one of several choices. This
+/// is common for arbitration, priority, and select registers.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, OneHot};
+/// bitfield! {
+///     pub struct Arbiter(u16) {
+///         [0..8]  pub grant: u8 as OneHot<u8>,
+///         [8..16] pub request: u8,
+///     }
+/// }
+///
+/// let x = Arbiter(0x0b08);
+/// assert_eq!(x.grant().index(), Some(3));
+///
+/// let y = x.with_grant(OneHot::from_index(0));
+/// assert_eq!(y, Arbiter(0x0b01));
+///
+/// // Zero or multiple bits set
+/// assert_eq!(Arbiter(0x0000).grant().index(), None);
+/// assert_eq!(Arbiter(0x0041).grant().index(), None);
+/// ```
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OneHot<T> {
+    raw: T,
+}
+
+impl<T> OneHot<T>
+where
+    T: Default
+        + Copy
+        + PartialEq
+        + From<bool>
+        + ops::BitAnd<Output = T>
+        + ops::Shl<u8, Output = T>
+        + ops::Shr<u8, Output = T>,
+{
+    /// Create a value from its raw representation, which may not be valid
+    pub const fn from_raw(raw: T) -> Self {
+        Self { raw }
+    }
+
+    /// The raw representation of the value
+    pub fn raw(self) -> T {
+        self.raw
+    }
+
+    /// Create a value with only the given bit set.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if the index is greater than or equal to the number of
+    /// bits in `T`, just like a normal shift.
+    pub fn from_index(index: u8) -> Self {
+        Self { raw: T::from(true) << index }
+    }
+
+    /// The index of the set bit, or `None` if zero or multiple bits are set
+    pub fn index(self) -> Option<u8> {
+        if self.raw == T::default() {
+            return None;
+        }
+        let mut remaining = self.raw;
+        let mut index = 0;
+        while !get_bit(remaining, 0) {
+            remaining = remaining >> 1;
+            index += 1;
+        }
+        if remaining >> 1 == T::default() {
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> From<T> for OneHot<T> {
+    #[inline(always)]
+    fn from(raw: T) -> Self {
+        Self { raw }
+    }
+}
+
+macro_rules! one_hot_conversion_impl {
+    ($type:ty) => {
+        impl From<OneHot<$type>> for $type {
+            #[inline(always)]
+            fn from(value: OneHot<$type>) -> Self {
+                value.raw
+            }
+        }
+    };
+}
+
+one_hot_conversion_impl!(u8);
+one_hot_conversion_impl!(u16);
+one_hot_conversion_impl!(u32);
+one_hot_conversion_impl!(u64);
+one_hot_conversion_impl!(u128);
+
+impl<T> fmt::Debug for OneHot<T>
+where
+    T: fmt::Binary,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "OneHot({:#b})", self.raw)
+    }
+}
+
+/// A value stored in the opposite byte order from the rest of the register, so the
+/// bytes are swapped whenever it is read or written.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, Swapped};
+/// bitfield! {
+///     pub struct Header(u32) {
+///         [0..16]  pub length: u16 as Swapped<u16>,
+///         [16..24] pub kind: u8,
+///     }
+/// }
+///
+/// let x = Header(0x0003_3412);
+/// assert_eq!(x.length().get(), 0x1234);
+///
+/// let y = x.with_length(Swapped::new(0xabcd));
+/// assert_eq!(y, Header(0x0003_cdab));
+/// ```
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Swapped<T> {
+    raw: T,
+}
+
+impl<T> Swapped<T>
+where
+    T: ByteRepr,
+{
+    /// Create a value from its byte-swapped representation
+    pub const fn from_raw(raw: T) -> Self {
+        Self { raw }
+    }
+
+    /// The byte-swapped representation of the value
+    pub fn raw(self) -> T {
+        self.raw
+    }
+
+    /// Create a value that will be stored with its bytes swapped
+    pub fn new(value: T) -> Self {
+        Self { raw: value.swap_bytes() }
+    }
+
+    /// The value with its bytes swapped back to the native order
+    pub fn get(self) -> T {
+        self.raw.swap_bytes()
+    }
+}
+
+impl<T> From<T> for Swapped<T> {
+    #[inline(always)]
+    fn from(raw: T) -> Self {
+        Self { raw }
+    }
+}
+
+macro_rules! swapped_conversion_impl {
+    ($type:ty) => {
+        impl From<Swapped<$type>> for $type {
+            #[inline(always)]
+            fn from(value: Swapped<$type>) -> Self {
+                value.raw
+            }
+        }
+    };
+}
+
+swapped_conversion_impl!(u16);
+swapped_conversion_impl!(u32);
+swapped_conversion_impl!(u64);
+swapped_conversion_impl!(u128);
+
+impl<T> fmt::Debug for Swapped<T>
+where
+    T: ByteRepr + fmt::LowerHex,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Swapped({:#x})", self.get())
+    }
+}
@@ -0,0 +1,379 @@
+//! Descriptions of bitfield layouts for tools outside of Rust, like data files for
+//! scripts, Wireshark dissectors, and Kaitai Struct parsers.
+//!
+//! The layout of every type defined with the [`bitfield`](crate::bitfield) macro is
+//! available from [`FieldLayout`]. The functions in this module render it in formats
+//! that scripts and other tools can read, so that they can share the exact layout used
+//! by the Rust code instead of keeping a copy in sync by hand:
+//!
+//! ```
+//! # use tartan_bitfield::bitfield;
+//! use tartan_bitfield::export;
+//!
+//! bitfield! {
+//!     pub struct Control(u16) {
+//!         [0..4]  pub mode,
+//!         [7]     pub enable,
+//!     }
+//! }
+//!
+//! let mut json = String::new();
+//! export::write_json::<Control, _, _>(&mut json).unwrap();
+//! assert_eq!(
+//!     json,
+//!     concat!(
+//!         r#"{"name":"Control","bits":16,"fields":["#,
+//!         r#"{"name":"mode","lsb":0,"msb":4,"width":4,"flag":false},"#,
+//!         r#"{"name":"enable","lsb":7,"msb":8,"width":1,"flag":true}"#,
+//!         r#"]}"#,
+//!     ),
+//! );
+//! ```
+//!
+//! Like [`FieldInfo`], the `msb` of each field is exclusive. The output is written to
+//! any [`fmt::Write`], so it doesn't require an allocator. With the `std` feature,
+//! `to_json` and `to_toml` return a `String` instead.
+
+use crate::{Bitfield, ByteOrder, FieldInfo, FieldLayout, FieldStorage};
+use core::fmt::{self, Write};
+
+/// The name of the bitfield type, without its module path
+pub(crate) fn type_name<B>() -> &'static str {
+    let full = core::any::type_name::<B>();
+    // Generic arguments can contain paths too, so only look before them
+    let end = full.find('<').unwrap_or(full.len());
+    let start = full[..end].rfind("::").map_or(0, |i| i + 2);
+    &full[start..]
+}
+
+/// Write the layout of the bitfield `B` as a JSON object.
+///
+/// The object has the `name` of the type, the number of `bits` in its underlying type,
+/// and an array of `fields` with the `name`, `lsb`, `msb`, `width`, and `flag` of each
+/// field, in the order they were declared. See the [module](self) documentation for an
+/// example.
+///
+/// # Errors
+///
+/// Returns an error if the writer fails.
+pub fn write_json<B, T, W>(w: &mut W) -> fmt::Result
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage,
+    W: fmt::Write + ?Sized,
+{
+    write!(w, r#"{{"name":"{}","bits":{},"fields":["#, type_name::<B>(), T::MAX_BITS)?;
+    for (i, field) in B::LAYOUT.iter().enumerate() {
+        if i > 0 {
+            w.write_char(',')?;
+        }
+        let FieldInfo { name, lsb, msb, flag } = field;
+        write!(
+            w,
+            r#"{{"name":"{name}","lsb":{lsb},"msb":{msb},"width":{},"flag":{flag}}}"#,
+            field.width(),
+        )?;
+    }
+    w.write_str("]}")
+}
+
+/// Write the layout of the bitfield `B` as a TOML document.
+///
+/// The document has the same keys as [`write_json`], with each field in a `[[fields]]`
+/// table:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// # use tartan_bitfield::export;
+/// bitfield! {
+///     pub struct Status(u8) {
+///         [0] pub ready,
+///     }
+/// }
+///
+/// let mut toml = String::new();
+/// export::write_toml::<Status, _, _>(&mut toml).unwrap();
+/// assert_eq!(
+///     toml,
+///     "name = \"Status\"\nbits = 8\n\n\
+///      [[fields]]\nname = \"ready\"\nlsb = 0\nmsb = 1\nwidth = 1\nflag = true\n",
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the writer fails.
+pub fn write_toml<B, T, W>(w: &mut W) -> fmt::Result
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage,
+    W: fmt::Write + ?Sized,
+{
+    writeln!(w, "name = \"{}\"", type_name::<B>())?;
+    writeln!(w, "bits = {}", T::MAX_BITS)?;
+    for field in B::LAYOUT {
+        let FieldInfo { name, lsb, msb, flag } = field;
+        writeln!(w, "\n[[fields]]")?;
+        writeln!(w, "name = \"{name}\"")?;
+        writeln!(w, "lsb = {lsb}")?;
+        writeln!(w, "msb = {msb}")?;
+        writeln!(w, "width = {}", field.width())?;
+        writeln!(w, "flag = {flag}")?;
+    }
+    Ok(())
+}
+
+/// Write a Lua snippet for a Wireshark dissector that declares the fields of the
+/// bitfield `B` and adds them to a protocol tree.
+///
+/// The snippet defines a table of `ProtoField`s named after the type, with abbreviations
+/// formed from `protocol` and the name of each field. Single-bit flags are declared as
+/// booleans and other fields as hexadecimal integers, all with masks selecting their
+/// bits of the underlying value. It also defines a function that adds every field to a
+/// tree, given the range of the buffer holding the value in the given byte order. The
+/// fields still have to be added to the protocol's `fields`:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// use tartan_bitfield::{export, ByteOrder};
+///
+/// bitfield! {
+///     pub struct Header(u16) {
+///         [0..4]  pub kind,
+///         [15]    pub last,
+///     }
+/// }
+///
+/// let mut lua = String::new();
+/// export::write_wireshark::<Header, _, _>(&mut lua, "myproto", ByteOrder::Big).unwrap();
+/// assert_eq!(
+///     lua,
+///     r#"local Header_fields = {
+///     kind = ProtoField.uint16("myproto.kind", "kind", base.HEX, nil, 0x000f),
+///     last = ProtoField.bool("myproto.last", "last", 16, nil, 0x8000),
+/// }
+///
+/// local function add_Header_fields(tree, range)
+///     tree:add(Header_fields.kind, range)
+///     tree:add(Header_fields.last, range)
+/// end
+/// "#,
+/// );
+/// ```
+///
+/// The generated Lua would be used like this:
+///
+/// ```lua
+/// local proto = Proto("myproto", "My Protocol")
+/// for _, field in pairs(Header_fields) do
+///     table.insert(proto.fields, field)
+/// end
+///
+/// function proto.dissector(buffer, pinfo, tree)
+///     local subtree = tree:add(proto, buffer())
+///     add_Header_fields(subtree, buffer(0, 2))
+/// end
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the writer fails.
+///
+/// # Panics
+///
+/// Panics if the underlying type has more than 64 bits, since Wireshark has no wider
+/// integer fields.
+pub fn write_wireshark<B, T, W>(
+    w: &mut W,
+    protocol: &str,
+    order: ByteOrder,
+) -> fmt::Result
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage,
+    W: fmt::Write + ?Sized,
+{
+    let bits: u8 = match T::MAX_BITS {
+        0..=8 => 8,
+        9..=16 => 16,
+        17..=24 => 24,
+        25..=32 => 32,
+        33..=64 => 64,
+        _ => panic!("Wireshark fields can have at most 64 bits"),
+    };
+    let digits = usize::from(bits / 4);
+    let name = LuaName(type_name::<B>());
+    let add = if is_big_endian(order) { "add" } else { "add_le" };
+
+    writeln!(w, "local {name}_fields = {{")?;
+    for field in B::LAYOUT {
+        let FieldInfo { name: field_name, flag, .. } = field;
+        let mask = field.mask();
+        write!(w, "    {field_name} = ")?;
+        if *flag {
+            write!(
+                w,
+                r#"ProtoField.bool("{protocol}.{field_name}", "{field_name}", {bits}"#
+            )?;
+        } else {
+            write!(w, "ProtoField.uint{bits}(")?;
+            write!(w, r#""{protocol}.{field_name}", "{field_name}", base.HEX"#)?;
+        }
+        writeln!(w, ", nil, {mask:#0width$x}),", width = digits + 2)?;
+    }
+    writeln!(w, "}}")?;
+    writeln!(w)?;
+    writeln!(w, "local function add_{name}_fields(tree, range)")?;
+    for field in B::LAYOUT {
+        writeln!(w, "    tree:{add}({name}_fields.{}, range)", field.name)?;
+    }
+    writeln!(w, "end")
+}
+
+/// Write a [Kaitai Struct](https://kaitai.io) specification that reads the bitfield
+/// `B` in the given byte order.
+///
+/// The whole value is read as one integer named `raw_value`, and each field is an
+/// instance that extracts its bits, so fields can overlap like they can in Rust.
+/// Single-bit flags are booleans. The `id` of the specification is the name of the type
+/// in snake case:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// use tartan_bitfield::{export, ByteOrder};
+///
+/// bitfield! {
+///     pub struct PacketHeader(u16) {
+///         [0..4]  pub kind,
+///         [15]    pub last,
+///     }
+/// }
+///
+/// let mut ksy = String::new();
+/// export::write_kaitai::<PacketHeader, _, _>(&mut ksy, ByteOrder::Little).unwrap();
+/// assert_eq!(
+///     ksy,
+///     "meta:
+///   id: packet_header
+///   bit-endian: le
+/// seq:
+///   - id: raw_value
+///     type: b16
+/// instances:
+///   kind:
+///     value: (raw_value >> 0) & 0xf
+///   last:
+///     value: ((raw_value >> 15) & 0x1) != 0
+/// ",
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the writer fails.
+///
+/// # Panics
+///
+/// Panics if the underlying type has more than 64 bits, since Kaitai Struct has no
+/// wider integers.
+pub fn write_kaitai<B, T, W>(w: &mut W, order: ByteOrder) -> fmt::Result
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage,
+    W: fmt::Write + ?Sized,
+{
+    assert!(T::MAX_BITS <= 64, "Kaitai Struct integers can have at most 64 bits");
+    let bit_endian = if is_big_endian(order) { "be" } else { "le" };
+
+    writeln!(w, "meta:")?;
+    writeln!(w, "  id: {}", SnakeCase(type_name::<B>()))?;
+    writeln!(w, "  bit-endian: {bit_endian}")?;
+    writeln!(w, "seq:")?;
+    writeln!(w, "  - id: raw_value")?;
+    writeln!(w, "    type: b{}", T::MAX_BITS)?;
+    if B::LAYOUT.is_empty() {
+        return Ok(());
+    }
+    writeln!(w, "instances:")?;
+    for field in B::LAYOUT {
+        let FieldInfo { name, lsb, flag, .. } = field;
+        let width_mask = field.mask() >> lsb;
+        writeln!(w, "  {name}:")?;
+        if *flag {
+            writeln!(w, "    value: ((raw_value >> {lsb}) & {width_mask:#x}) != 0")?;
+        } else {
+            writeln!(w, "    value: (raw_value >> {lsb}) & {width_mask:#x}")?;
+        }
+    }
+    Ok(())
+}
+
+fn is_big_endian(order: ByteOrder) -> bool {
+    match order {
+        ByteOrder::Big => true,
+        ByteOrder::Little => false,
+        ByteOrder::Native => cfg!(target_endian = "big"),
+    }
+}
+
+/// A type name in snake case, with every character that isn't valid in an identifier
+/// replaced
+struct SnakeCase(&'static str);
+
+impl fmt::Display for SnakeCase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut previous_lower = false;
+        for c in self.0.chars() {
+            if c.is_ascii_uppercase() {
+                if previous_lower {
+                    f.write_char('_')?;
+                }
+                f.write_char(c.to_ascii_lowercase())?;
+            } else {
+                f.write_char(if c.is_ascii_alphanumeric() { c } else { '_' })?;
+            }
+            previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        }
+        Ok(())
+    }
+}
+
+/// A type name with every character that isn't valid in a Lua name replaced
+struct LuaName(&'static str);
+
+impl fmt::Display for LuaName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            f.write_char(if c.is_ascii_alphanumeric() { c } else { '_' })?;
+        }
+        Ok(())
+    }
+}
+
+/// The layout of the bitfield `B` as a JSON object. See [`write_json`].
+#[cfg(feature = "std")]
+#[must_use]
+pub fn to_json<B, T>() -> std::string::String
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage,
+{
+    let mut out = std::string::String::new();
+    // Writing to a `String` can't fail
+    let _ = write_json::<B, T, _>(&mut out);
+    out
+}
+
+/// The layout of the bitfield `B` as a TOML document. See [`write_toml`].
+#[cfg(feature = "std")]
+#[must_use]
+pub fn to_toml<B, T>() -> std::string::String
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage,
+{
+    let mut out = std::string::String::new();
+    // Writing to a `String` can't fail
+    let _ = write_toml::<B, T, _>(&mut out);
+    out
+}
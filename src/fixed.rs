@@ -0,0 +1,179 @@
+use core::fmt;
+
+/// A signed fixed-point number in Q format, with `INT` integer bits (including the sign
+/// bit) and `FRAC` fractional bits.
+///
+/// This is intended to be used as the interface type of a bitfield with a width of
+/// `INT + FRAC` bits, which may be at most 64. The bits are interpreted as a two's
+/// complement integer, which is then divided by 2<sup>`FRAC`</sup>.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, Q, UQ};
+/// bitfield! {
+///     pub struct Sensor(u32) {
+///         // Unsigned, with 4 integer bits and 8 fractional bits
+///         [0..12]  pub voltage: u16 as UQ<4, 8>,
+///         // Signed, with 8 integer bits (including sign) and 4 fractional bits
+///         [16..28] pub temperature: u16 as Q<8, 4>,
+///     }
+/// }
+///
+/// let x = Sensor(0x0e70_0340);
+/// assert_eq!(x.voltage().to_f32(), 3.25);
+/// assert_eq!(x.temperature().to_f32(), -25.0);
+///
+/// let y = Sensor::default()
+///     .with_voltage(UQ::from_f32(15.5))
+///     .with_temperature(Q::from_f32(37.0625));
+/// assert_eq!(y, Sensor(0x0251_0f80));
+/// ```
+#[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Q<const INT: u8, const FRAC: u8> {
+    raw: i64,
+}
+
+/// An unsigned fixed-point number in Q format, with `INT` integer bits and `FRAC`
+/// fractional bits.
+///
+/// This is intended to be used as the interface type of a bitfield with a width of
+/// `INT + FRAC` bits, which may be at most 64. The bits are interpreted as an unsigned
+/// integer, which is then divided by 2<sup>`FRAC`</sup>. See [`Q`] for an example.
+#[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UQ<const INT: u8, const FRAC: u8> {
+    raw: u64,
+}
+
+macro_rules! fixed_impl {
+    ($type:ident, $raw:ty, $min_int:literal) => {
+        impl<const INT: u8, const FRAC: u8> $type<INT, FRAC> {
+            #[allow(clippy::cast_lossless)]
+            const WIDTH: u32 = INT as u32 + FRAC as u32;
+
+            #[allow(unused_comparisons)]
+            const VALID_FORMAT: () = assert!(
+                INT >= $min_int && Self::WIDTH >= 1 && Self::WIDTH <= 64,
+                "Fixed-point format must have from 1 to 64 bits"
+            );
+
+            // Split into two shifts so FRAC = 64 won't overflow
+            #[allow(clippy::cast_precision_loss)]
+            const SCALE: f64 =
+                (1_u64 << (FRAC / 2)) as f64 * (1_u64 << (FRAC - FRAC / 2)) as f64;
+
+            /// Create a value from its raw representation, which is the fixed-point
+            /// value multiplied by 2<sup>`FRAC`</sup>. Bits beyond the width of the
+            /// format are discarded.
+            pub const fn from_raw(raw: $raw) -> Self {
+                #[allow(clippy::let_unit_value)]
+                let () = Self::VALID_FORMAT;
+                let unused_bits = 64 - Self::WIDTH;
+                Self { raw: raw.wrapping_shl(unused_bits).wrapping_shr(unused_bits) }
+            }
+
+            /// The raw representation of the value, which is the fixed-point value
+            /// multiplied by 2<sup>`FRAC`</sup>.
+            pub const fn raw(self) -> $raw {
+                self.raw
+            }
+
+            /// Convert a floating-point value to the nearest fixed-point value. Values
+            /// out of range wrap around, like conversions between integer types. NaN
+            /// and infinities convert to zero.
+            #[allow(clippy::cast_possible_wrap)]
+            pub fn from_f64(value: f64) -> Self {
+                let scaled = value * Self::SCALE;
+                let rounded = if scaled < 0.0 { scaled - 0.5 } else { scaled + 0.5 };
+                Self::from_raw(wrapping_u64(rounded) as $raw)
+            }
+
+            /// Convert a floating-point value to the nearest fixed-point value. Values
+            /// out of range wrap around, like conversions between integer types. NaN
+            /// and infinities convert to zero.
+            pub fn from_f32(value: f32) -> Self {
+                Self::from_f64(value.into())
+            }
+
+            /// Convert the value to floating-point
+            #[allow(clippy::cast_precision_loss)]
+            pub fn to_f64(self) -> f64 {
+                self.raw as f64 / Self::SCALE
+            }
+
+            /// Convert the value to floating-point, possibly losing precision
+            #[allow(clippy::cast_possible_truncation)]
+            pub fn to_f32(self) -> f32 {
+                self.to_f64() as f32
+            }
+        }
+
+        impl<const INT: u8, const FRAC: u8> fmt::Debug for $type<INT, FRAC> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::Debug::fmt(&self.to_f64(), f)
+            }
+        }
+
+        impl<const INT: u8, const FRAC: u8> fmt::Display for $type<INT, FRAC> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::Display::fmt(&self.to_f64(), f)
+            }
+        }
+
+        fixed_conversion_impl!($type, $raw, u8);
+        fixed_conversion_impl!($type, $raw, u16);
+        fixed_conversion_impl!($type, $raw, u32);
+        fixed_conversion_impl!($type, $raw, u64);
+    };
+}
+
+/// Convert a floating-point value to an integer, discarding the fractional part and
+/// wrapping modulo 2<sup>64</sup>. NaN and infinities convert to zero.
+///
+/// A plain `as` cast saturates instead of wrapping, so values outside the range of `i64`
+/// are wrapped by shifting the mantissa. Such values are always integers.
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+fn wrapping_u64(value: f64) -> u64 {
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0; // 2^63
+    if !value.is_finite() {
+        return 0;
+    }
+    if (-LIMIT..LIMIT).contains(&value) {
+        return value as i64 as u64;
+    }
+    let bits = value.to_bits();
+    // The value is the mantissa, with its implicit leading 1, times 2^exponent
+    let exponent = ((bits >> 52) & 0x7ff) as u32 - 1075;
+    let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+    let magnitude = mantissa.checked_shl(exponent).unwrap_or(0);
+    if value < 0.0 {
+        magnitude.wrapping_neg()
+    } else {
+        magnitude
+    }
+}
+
+// Conversions to and from the raw bits, for use as the interface type of a bitfield
+macro_rules! fixed_conversion_impl {
+    ($type:ident, $raw:ty, $bits:ty) => {
+        impl<const INT: u8, const FRAC: u8> From<$bits> for $type<INT, FRAC> {
+            #[inline(always)]
+            #[allow(clippy::cast_possible_wrap)]
+            fn from(bits: $bits) -> Self {
+                Self::from_raw(u64::from(bits) as $raw)
+            }
+        }
+
+        impl<const INT: u8, const FRAC: u8> From<$type<INT, FRAC>> for $bits {
+            /// Get the raw bits of the fixed-point value, truncated to fit the type.
+            #[inline(always)]
+            #[allow(clippy::cast_possible_truncation)]
+            #[allow(clippy::cast_sign_loss)]
+            fn from(value: $type<INT, FRAC>) -> Self {
+                value.raw as $bits
+            }
+        }
+    };
+}
+
+fixed_impl!(Q, i64, 1);
+fixed_impl!(UQ, u64, 0);
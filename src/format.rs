@@ -0,0 +1,199 @@
+use crate::{Bitfield, FieldInfo, FieldLayout, FieldStorage, PackedBits};
+use core::fmt::{self, Write};
+
+/// Bitfield types that can write their field values to a [`fmt::Write`] target.
+///
+/// This is implemented by all types defined with the [`bitfield`](crate::bitfield)
+/// macro. The output is a comma-separated list of `name=value` pairs, with single-bit
+/// fields written as `0` or `1` and other fields written as their underlying integer
+/// values in hexadecimal. Unlike [`Debug`](fmt::Debug), the format is compact and
+/// predictable, and it can be read back with
+/// [`ParseFields::parse_fields`](crate::ParseFields::parse_fields).
+///
+/// No allocation is required, so this can be used to stream register dumps on `no_std`
+/// targets.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, ParseFields, WriteFields};
+/// bitfield! {
+///     pub struct Status(u16) {
+///         [0..4]  pub mode: u8,
+///         [4]     pub ready,
+///         [8..16] pub count: u8,
+///     }
+/// }
+///
+/// let mut out = String::new();
+/// Status(0x2a1b).write_fields(&mut out).unwrap();
+/// assert_eq!(out, "mode=0xb, ready=1, count=0x2a");
+///
+/// assert_eq!(Status::parse_fields(&out), Ok(Status(0x2a1b)));
+/// ```
+pub trait WriteFields {
+    /// Write the values of all fields as `name=value` pairs separated by `, `.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the writer fails.
+    fn write_fields<W>(&self, w: &mut W) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized;
+}
+
+/// Bitfield types that can print a [`Debug`](fmt::Debug) summary of only the fields that
+/// differ from their default value.
+///
+/// This is implemented by all types defined with the [`bitfield`](crate::bitfield)
+/// macro. Registers with dozens of fields that are usually zero are hard to read in the
+/// full `Debug` output, and the summary makes log lines easier to scan. Fields are
+/// compared by their bits, so field types don't need to implement [`PartialEq`].
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, DebugSummary};
+/// bitfield! {
+///     pub struct Control(u32) {
+///         [0]      pub enable,
+///         [1]      pub reset,
+///         [4..8]   pub mode,
+///         [8..16]  pub divider: u8,
+///         [16..32] pub timeout: u16,
+///     }
+/// }
+///
+/// let cr = Control::default().with_divider(4).with_enable(true);
+/// assert_eq!(format!("{:?}", cr.summary()), "Control { enable: true, divider: 4 }");
+/// assert_eq!(format!("{:?}", Control::default().summary()), "Control");
+/// ```
+pub trait DebugSummary {
+    /// Write the name of the type and the fields that differ from the default value,
+    /// in the same format as [`Debug`](fmt::Debug).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the formatter fails.
+    fn fmt_summary(&self, f: &mut fmt::Formatter) -> fmt::Result;
+
+    /// A value whose [`Debug`](fmt::Debug) implementation prints the summary
+    fn summary(&self) -> Summary<'_, Self> {
+        Summary(self)
+    }
+}
+
+/// Prints the summary of a bitfield. Returned by [`DebugSummary::summary`].
+pub struct Summary<'a, B: ?Sized>(&'a B);
+
+impl<B> fmt::Debug for Summary<'_, B>
+where
+    B: DebugSummary + ?Sized,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt_summary(f)
+    }
+}
+
+/// Write a table of the fields of a bitfield, with one row for each field in the order
+/// they were declared, and columns for the name, the bit range, and the value in
+/// hexadecimal and decimal. The columns are aligned, and every line ends with a newline.
+///
+/// This is also available as the `format_table` method generated by the
+/// [`bitfield`](crate::bitfield) macro. No allocation is required, so it can be used for
+/// register dumps on `no_std` targets.
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Status(u16) {
+///         [0..4]  pub mode: u8,
+///         [4]     pub ready,
+///         [8..16] pub count: u8,
+///     }
+/// }
+///
+/// let mut out = String::new();
+/// Status(0x2a1b).format_table(&mut out).unwrap();
+/// assert_eq!(
+///     out,
+///     "\
+/// field | bits  |  hex | decimal
+/// ------+-------+------+--------
+/// mode  | 0..4  |  0xb |      11
+/// ready | 4     |  0x1 |       1
+/// count | 8..16 | 0x2a |      42
+/// ",
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the writer fails.
+pub fn write_field_table<B, T, W>(bitfield: B, w: &mut W) -> fmt::Result
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits,
+    W: Write + ?Sized,
+{
+    let value = |field: &FieldInfo| crate::raw_field(bitfield, field.lsb, field.msb);
+
+    let mut widths = ["field".len(), "bits".len(), "hex".len(), "decimal".len()];
+    for field in B::LAYOUT {
+        let mut bits = Counter(0);
+        write_bits(&mut bits, field)?;
+        let mut hex = Counter(0);
+        write!(hex, "{:#x}", value(field))?;
+        let mut decimal = Counter(0);
+        write!(decimal, "{}", value(field))?;
+        let row = [field.name.chars().count(), bits.0, hex.0, decimal.0];
+        for (width, len) in widths.iter_mut().zip(row) {
+            *width = (*width).max(len);
+        }
+    }
+    let [name_width, bits_width, hex_width, decimal_width] = widths;
+
+    writeln!(
+        w,
+        "{:name_width$} | {:bits_width$} | {:>hex_width$} | {:>decimal_width$}",
+        "field", "bits", "hex", "decimal",
+    )?;
+    writeln!(
+        w,
+        "{:-<name_width$}-+-{:-<bits_width$}-+-{:-<hex_width$}-+-{:-<decimal_width$}",
+        "", "", "", "",
+    )?;
+    for field in B::LAYOUT {
+        write!(w, "{:name_width$} | ", field.name)?;
+        let mut bits = Counter(0);
+        write_bits(&mut bits, field)?;
+        write_bits(w, field)?;
+        let value = value(field);
+        writeln!(
+            w,
+            "{:pad$} | {value:>#hex_width$x} | {value:>decimal_width$}",
+            "",
+            pad = bits_width - bits.0,
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the bit range of a field like a Rust range, or a single bit number
+pub(crate) fn write_bits<W>(w: &mut W, field: &FieldInfo) -> fmt::Result
+where
+    W: Write + ?Sized,
+{
+    if field.width() == 1 {
+        write!(w, "{}", field.lsb)
+    } else {
+        write!(w, "{}..{}", field.lsb, field.msb)
+    }
+}
+
+/// Counts the characters written to it, to measure values without allocating
+pub(crate) struct Counter(pub(crate) usize);
+
+impl Write for Counter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.chars().count();
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{Bitfield, ByteOrder, ByteRepr, PackedBits};
+use core::fmt;
+use core::ops::Range;
+
+/// Error returned when a message framed with [`bitfield_frame`](crate::bitfield_frame)
+/// can't be parsed or emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameError {
+    /// The input ended before the end of the header or the payload
+    Truncated,
+    /// The payload is too long for the length field of the header
+    PayloadTooLong,
+    /// The output buffer is too small for the header and the payload
+    BufferTooSmall,
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "message is shorter than its header declares"),
+            Self::PayloadTooLong => write!(f, "payload is too long for the length field"),
+            Self::BufferTooSmall => write!(f, "buffer is too small for the message"),
+        }
+    }
+}
+
+impl core::error::Error for FrameError {}
+
+/// Split a message into its header and payload, where the payload length is given by
+/// the bits of the header in `length`. Helper function for
+/// [`bitfield_frame`](crate::bitfield_frame).
+///
+/// # Errors
+///
+/// Returns an error if `bytes` is shorter than the header and the payload. Any bytes
+/// after the payload are ignored.
+#[doc(hidden)]
+pub fn parse_frame<B, T>(
+    bytes: &[u8],
+    order: ByteOrder,
+    length: Range<u8>,
+) -> Result<(B, &[u8]), FrameError>
+where
+    B: Bitfield<T>,
+    T: ByteRepr + PackedBits,
+{
+    let (header, rest) = bytes.split_at_checked(T::SIZE).ok_or(FrameError::Truncated)?;
+    let header = T::read_bytes(header, order);
+    let payload_len = header.packed_field(length.start, length.end).into_u128();
+    let payload = usize::try_from(payload_len)
+        .ok()
+        .and_then(|len| rest.get(..len))
+        .ok_or(FrameError::Truncated)?;
+    Ok((B::new(header), payload))
+}
+
+/// Write a header followed by a payload, with the length of the payload stored in the
+/// bits of the header in `length`. Returns the number of bytes written. Helper function
+/// for [`bitfield_frame`](crate::bitfield_frame).
+///
+/// # Errors
+///
+/// Returns an error without modifying `dest` if the length of the payload doesn't fit
+/// in the length field, or if `dest` is too small for the header and the payload.
+#[doc(hidden)]
+pub fn emit_frame<B, T>(
+    header: B,
+    payload: &[u8],
+    dest: &mut [u8],
+    order: ByteOrder,
+    length: Range<u8>,
+) -> Result<usize, FrameError>
+where
+    B: Bitfield<T>,
+    T: ByteRepr + PackedBits,
+{
+    let width = length.end - length.start;
+    let payload_len = payload.len() as u128;
+    if width < 128 && payload_len >> width != 0 {
+        return Err(FrameError::PayloadTooLong);
+    }
+    let total = T::SIZE + payload.len();
+    let dest = dest.get_mut(..total).ok_or(FrameError::BufferTooSmall)?;
+
+    let header = header.value().with_packed_field(
+        length.start,
+        length.end,
+        T::from_u128(payload_len),
+    );
+    let (header_bytes, payload_bytes) = dest.split_at_mut(T::SIZE);
+    header.write_bytes(header_bytes, order);
+    payload_bytes.copy_from_slice(payload);
+    Ok(total)
+}
@@ -0,0 +1,85 @@
+use crate::{ByteOrder, ByteRepr, RegisterInterface};
+use embedded_hal::i2c::{I2c, Operation};
+
+// Large enough for the register address and the bytes of the widest underlying type
+const MAX_FRAME: usize = 1 + 16;
+
+/// A [`RegisterInterface`] for a device on an embedded-hal [`I2c`] bus.
+///
+/// Most I2C sensors and peripherals expose their registers the same way: the driver
+/// writes the number of a register, then reads or writes its bytes in the same
+/// transaction. This implements that protocol for registers of any width, so generic
+/// drivers can take the bus and access each register as a bitfield.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, ByteOrder, I2cInterface, RegisterInterface};
+/// # use embedded_hal::i2c::I2c;
+/// bitfield! {
+///     pub struct Config(u16) {
+///         [0..3]  pub mode: u8,
+///         [3..6]  pub average_count: u8,
+///         [15]    pub reset,
+///     }
+/// }
+///
+/// const SENSOR: u8 = 0x40;
+/// const CONFIG: u8 = 0x01;
+///
+/// fn start_continuous<I: I2c>(i2c: I) -> Result<(), I::Error> {
+///     let mut sensor = I2cInterface::new(i2c, SENSOR, ByteOrder::Big);
+///     sensor.register::<Config>(CONFIG).modify(|c| c.set_mode(7))
+/// }
+/// ```
+#[derive(Debug)]
+pub struct I2cInterface<I> {
+    i2c: I,
+    device: u8,
+    order: ByteOrder,
+}
+
+impl<I> I2cInterface<I>
+where
+    I: I2c,
+{
+    /// An interface to the device at the 7-bit address `device`, whose registers are
+    /// sent in the given byte order
+    pub fn new(i2c: I, device: u8, order: ByteOrder) -> Self {
+        Self { i2c, device, order }
+    }
+
+    /// The address of the device on the bus
+    pub fn device(&self) -> u8 {
+        self.device
+    }
+
+    /// Release the bus
+    pub fn into_inner(self) -> I {
+        self.i2c
+    }
+}
+
+impl<I, T> RegisterInterface<T> for I2cInterface<I>
+where
+    I: I2c,
+    T: ByteRepr,
+{
+    type Address = u8;
+    type Error = I::Error;
+
+    fn read_register(&mut self, address: u8) -> Result<T, I::Error> {
+        let mut frame = [0; MAX_FRAME];
+        let bytes = &mut frame[..T::SIZE];
+        self.i2c.transaction(
+            self.device,
+            &mut [Operation::Write(&[address]), Operation::Read(bytes)],
+        )?;
+        Ok(T::read_bytes(bytes, self.order))
+    }
+
+    fn write_register(&mut self, address: u8, value: T) -> Result<(), I::Error> {
+        let mut frame = [0; MAX_FRAME];
+        frame[0] = address;
+        value.write_bytes(&mut frame[1..=T::SIZE], self.order);
+        self.i2c.write(self.device, &frame[..=T::SIZE])
+    }
+}
@@ -0,0 +1,437 @@
+//! Generation of [`bitfield`](crate::bitfield) definitions from C headers.
+//!
+//! Drivers written in C usually describe registers with `#define`s for the shift and
+//! mask of each field, or with a struct of C bit-fields. When migrating a driver,
+//! [`bitfield_from_c_defines`] and [`bitfield_from_c_struct`] can turn those into a
+//! `bitfield!` invocation at build time, so that the layout doesn't have to be
+//! transcribed by hand. With a `build.rs` like this:
+//!
+//! ```no_run
+//! use std::{env, fs, path::Path};
+//!
+//! let header = fs::read_to_string("vendor/uart.h").unwrap();
+//! let code = tartan_bitfield::import::bitfield_from_c_defines(
+//!     &header, "UART_CR_", "pub struct UartControl(u32)",
+//! ).unwrap();
+//! let out = Path::new(&env::var("OUT_DIR").unwrap()).join("uart_cr.rs");
+//! fs::write(out, code).unwrap();
+//! ```
+//!
+//! The generated definition can be included in the crate with
+//! `include!(concat!(env!("OUT_DIR"), "/uart_cr.rs"));`.
+//!
+//! This requires the `std` feature.
+
+use core::fmt::{self, Write};
+use std::collections::BTreeMap;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+/// Error returned when a C header can't be converted to a bitfield definition.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ImportError {
+    /// No field definitions were found with the given prefix
+    NoFields,
+    /// The named field has a mask without a single contiguous range of bits
+    NonContiguousMask(String),
+    /// The named field has a shift that doesn't match its mask
+    MismatchedShift(String),
+    /// The value of the named definition isn't an integer expression that can be
+    /// evaluated
+    InvalidValue(String),
+    /// No struct with the given name was found
+    StructNotFound,
+    /// The named struct member isn't a bit-field with a constant width
+    UnsupportedMember(String),
+    /// The fields need more bits than the underlying type has
+    Overflow,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoFields => write!(f, "no field definitions found"),
+            Self::NonContiguousMask(name) => {
+                write!(f, "mask of field `{name}` is not a contiguous range of bits")
+            }
+            Self::MismatchedShift(name) => {
+                write!(f, "shift of field `{name}` does not match its mask")
+            }
+            Self::InvalidValue(name) => write!(f, "value of `{name}` is not supported"),
+            Self::StructNotFound => write!(f, "struct definition not found"),
+            Self::UnsupportedMember(name) => {
+                write!(f, "member `{name}` is not a bit-field with a constant width")
+            }
+            Self::Overflow => write!(f, "bit-fields do not fit in the underlying type"),
+        }
+    }
+}
+
+impl core::error::Error for ImportError {}
+
+/// Generate a [`bitfield`](crate::bitfield) invocation from the `#define`s in a C header
+/// that start with `prefix`.
+///
+/// A field is declared by a definition ending in `_MASK` or `_MSK`, optionally with a
+/// matching definition ending in `_SHIFT` or `_POS`. The mask may be either shifted
+/// into position or not, as long as it selects a contiguous range of bits. The name of
+/// the field is the part between the prefix and the suffix, in lower case. Values can be
+/// integer literals with the usual suffixes, earlier definitions, and combinations of
+/// them with `<<` and `|`. Other definitions are ignored.
+///
+/// `header` is the declaration of the struct, like `pub struct Control(u32)`. It is
+/// checked that the fields fit within its underlying type. Every field is public, and
+/// fields are listed from the least significant bit.
+///
+/// ```
+/// # use tartan_bitfield::import::bitfield_from_c_defines;
+/// let header = "
+///     #define CTRL_EN_MASK      0x1u
+///     #define CTRL_EN_SHIFT     0
+///     #define CTRL_MODE_SHIFT   4
+///     #define CTRL_MODE_MASK    (0x7u << CTRL_MODE_SHIFT)
+///     #define CTRL_DIV_MSK      0xff00
+///     #define CTRL_RESET_VALUE  0x0010
+/// ";
+///
+/// let code = bitfield_from_c_defines(header, "CTRL_", "pub struct Control(u16)");
+/// assert_eq!(
+///     code.unwrap(),
+///     "bitfield! {
+///     pub struct Control(u16) {
+///         [0] pub en,
+///         [4..7] pub mode,
+///         [8..16] pub div,
+///     }
+/// }
+/// ",
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if there are no fields, a mask isn't contiguous, a shift doesn't
+/// match its mask, a value of a definition with the prefix can't be evaluated, or the
+/// fields don't fit within the underlying type of `declaration`.
+pub fn bitfield_from_c_defines(
+    header: &str,
+    prefix: &str,
+    declaration: &str,
+) -> Result<String, ImportError> {
+    let mut values = BTreeMap::new();
+    let mut masks = Vec::new();
+    let mut shifts = BTreeMap::new();
+
+    for line in header.lines() {
+        let Some(rest) = line.trim().strip_prefix("#define") else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        let name_end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let (name, value) = rest.split_at(name_end);
+        // Skip function-like macros and definitions without a value
+        if value.starts_with('(') || value.trim().is_empty() {
+            continue;
+        }
+        let value = strip_comment(value).trim();
+
+        let Some(field) = name.strip_prefix(prefix) else {
+            // Definitions without the prefix may still be referenced by the others
+            if let Some(value) = evaluate(value, &values) {
+                values.insert(name.to_string(), value);
+            }
+            continue;
+        };
+        let value = evaluate(value, &values)
+            .ok_or_else(|| ImportError::InvalidValue(name.into()))?;
+        values.insert(name.to_string(), value);
+
+        if let Some(field) = field.strip_suffix("_MASK").or(field.strip_suffix("_MSK")) {
+            masks.push((field.to_ascii_lowercase(), value));
+        } else if let Some(field) =
+            field.strip_suffix("_SHIFT").or(field.strip_suffix("_POS"))
+        {
+            shifts.insert(field.to_ascii_lowercase(), value);
+        }
+    }
+
+    let mut fields = Vec::new();
+    for (name, mask) in masks {
+        if mask == 0 {
+            return Err(ImportError::NonContiguousMask(name));
+        }
+        let shift = shifts.get(&name).copied();
+        let mask = match shift {
+            // The mask is already in position if its lowest bit is at the shift, or if
+            // there is no shift
+            Some(shift) if u128::from(mask.trailing_zeros()) == shift => mask,
+            None => mask,
+            // Shifting an unshifted mask must not push any of its bits out
+            Some(shift) if mask & 1 == 1 && shift <= u128::from(mask.leading_zeros()) => {
+                mask << shift
+            }
+            Some(_) => return Err(ImportError::MismatchedShift(name)),
+        };
+        let lsb = mask.trailing_zeros();
+        let msb = 128 - mask.leading_zeros();
+        if mask >> lsb != u128::MAX >> (128 - (msb - lsb)) {
+            return Err(ImportError::NonContiguousMask(name));
+        }
+        fields.push((lsb, msb, name));
+    }
+    if fields.is_empty() {
+        return Err(ImportError::NoFields);
+    }
+    if let Some(bits) = declared_bits(declaration) {
+        if fields.iter().any(|&(_, msb, _)| msb > bits) {
+            return Err(ImportError::Overflow);
+        }
+    }
+    fields.sort();
+    Ok(render(declaration, &fields))
+}
+
+/// Generate a [`bitfield`](crate::bitfield) invocation from the C bit-fields in the
+/// struct named `name` in a C header.
+///
+/// Members are allocated from the least significant bit in the order they are declared,
+/// which is what GCC, Clang, and MSVC do on little-endian targets. A bit-field without a
+/// name, like `unsigned : 3;`, leaves a gap. A width of zero, which aligns the next
+/// member to a new unit in C, isn't supported, and neither are members that aren't
+/// bit-fields. Widths can use the same expressions and `#define`s as
+/// [`bitfield_from_c_defines`].
+///
+/// `header` is the declaration of the struct, like `pub struct Status(u32)`. It is
+/// checked that the members fit within its underlying type. Every field is public.
+///
+/// ```
+/// # use tartan_bitfield::import::bitfield_from_c_struct;
+/// let header = "
+///     #define CHANNEL_BITS 3
+///
+///     struct dma_status {
+///         unsigned int busy    : 1;
+///         unsigned int channel : CHANNEL_BITS; /* active channel */
+///         unsigned int         : 4;
+///         uint32_t     count   : 8;
+///     };
+/// ";
+///
+/// let code = bitfield_from_c_struct(header, "dma_status", "pub struct DmaStatus(u16)");
+/// assert_eq!(
+///     code.unwrap(),
+///     "bitfield! {
+///     pub struct DmaStatus(u16) {
+///         [0] pub busy,
+///         [1..4] pub channel,
+///         [8..16] pub count,
+///     }
+/// }
+/// ",
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the struct isn't found or has no fields, if a member isn't a
+/// bit-field with a width that can be evaluated, or if the members don't fit within the
+/// underlying type of `declaration`.
+pub fn bitfield_from_c_struct(
+    header: &str,
+    name: &str,
+    declaration: &str,
+) -> Result<String, ImportError> {
+    let mut values = BTreeMap::new();
+    for line in header.lines() {
+        let Some(rest) = line.trim().strip_prefix("#define") else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        let name_end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let (define, value) = rest.split_at(name_end);
+        if value.starts_with('(') {
+            continue;
+        }
+        if let Some(value) = evaluate(strip_comment(value), &values) {
+            values.insert(define.to_string(), value);
+        }
+    }
+
+    let header = strip_all_comments(header);
+    let body = struct_body(&header, name).ok_or(ImportError::StructNotFound)?;
+
+    let mut fields = Vec::new();
+    let mut lsb = 0;
+    for member in body.split(';').map(str::trim).filter(|m| !m.is_empty()) {
+        let Some((decl, width)) = member.split_once(':') else {
+            return Err(ImportError::UnsupportedMember(member.into()));
+        };
+        let decl = decl.trim();
+        // The name is the last word, unless the only words are part of the type
+        let field = decl.rsplit(char::is_whitespace).next().unwrap_or_default();
+        let field = if decl.contains(char::is_whitespace) && !is_c_type_word(field) {
+            Some(field)
+        } else {
+            None
+        };
+        let width =
+            evaluate(width, &values).filter(|&w| w > 0 && w <= 128).ok_or_else(|| {
+                ImportError::UnsupportedMember(field.unwrap_or(decl).into())
+            })?;
+        let msb = lsb + u32::try_from(width).unwrap_or(u32::MAX);
+        if let Some(field) = field {
+            fields.push((lsb, msb, field.to_string()));
+        }
+        lsb = msb;
+    }
+    if let Some(bits) = declared_bits(declaration) {
+        if lsb > bits {
+            return Err(ImportError::Overflow);
+        }
+    }
+    if fields.is_empty() {
+        return Err(ImportError::NoFields);
+    }
+    Ok(render(declaration, &fields))
+}
+
+fn render(declaration: &str, fields: &[(u32, u32, String)]) -> String {
+    // Writing to a `String` can't fail
+    let mut out = String::new();
+    out.push_str("bitfield! {\n");
+    let _ = writeln!(out, "    {declaration} {{");
+    for (lsb, msb, name) in fields {
+        if msb - lsb == 1 {
+            let _ = writeln!(out, "        [{lsb}] pub {name},");
+        } else {
+            let _ = writeln!(out, "        [{lsb}..{msb}] pub {name},");
+        }
+    }
+    out.push_str("    }\n}\n");
+    out
+}
+
+/// The text between the braces of `struct name { ... }`
+fn struct_body<'a>(header: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = header;
+    while let Some(start) = rest.find("struct") {
+        let after = &rest[start + "struct".len()..];
+        rest = after;
+        let Some(after) = after.trim_start().strip_prefix(name) else {
+            continue;
+        };
+        let Some(body) = after.trim_start().strip_prefix('{') else {
+            continue;
+        };
+        return body.find('}').map(|end| &body[..end]);
+    }
+    None
+}
+
+fn strip_all_comments(header: &str) -> String {
+    let mut out = String::new();
+    for line in header.lines() {
+        // Skip preprocessor directives so that their values aren't taken as members
+        if !line.trim_start().starts_with('#') {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    while let Some(start) = out.find("/*") {
+        let end = out[start..].find("*/").map_or(out.len(), |e| start + e + 2);
+        out.replace_range(start..end, " ");
+    }
+    out.lines()
+        .map(|line| &line[..line.find("//").unwrap_or(line.len())])
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// True if the word can only be part of the type of a struct member, so that a member
+/// declared like `unsigned int : 4` has no name
+fn is_c_type_word(word: &str) -> bool {
+    matches!(
+        word,
+        "int" | "unsigned" | "signed" | "char" | "short" | "long" | "_Bool" | "bool"
+    ) || (word.starts_with("uint") || word.starts_with("int")) && word.ends_with("_t")
+}
+
+/// The number of bits in the underlying type of a declaration like `struct A(u32)`, if
+/// it is a primitive or exact-width integer
+fn declared_bits(declaration: &str) -> Option<u32> {
+    let start = declaration.rfind('(')?;
+    let end = declaration.rfind(')')?;
+    let ty = declaration.get(start + 1..end)?.trim();
+    let ty = ty.rsplit("::").next()?;
+    ty.strip_prefix('u')?.parse().ok()
+}
+
+fn strip_comment(value: &str) -> &str {
+    let end = [value.find("//"), value.find("/*")].into_iter().flatten().min();
+    &value[..end.unwrap_or(value.len())]
+}
+
+/// Evaluate a C integer expression with literals, names of earlier definitions,
+/// parentheses, `<<`, and `|`
+fn evaluate(expr: &str, values: &BTreeMap<String, u128>) -> Option<u128> {
+    let expr = expr.trim();
+    if let Some(inner) = expr.strip_prefix('(').and_then(|e| e.strip_suffix(')')) {
+        if balanced(inner) {
+            return evaluate(inner, values);
+        }
+    }
+    if let Some((left, right)) = split_top_level(expr, "|") {
+        return Some(evaluate(left, values)? | evaluate(right, values)?);
+    }
+    if let Some((left, right)) = split_top_level(expr, "<<") {
+        let shift = u32::try_from(evaluate(right, values)?).ok()?;
+        return evaluate(left, values)?.checked_shl(shift);
+    }
+    if expr.starts_with(|c: char| c.is_ascii_digit()) {
+        let digits = expr.trim_end_matches(['u', 'U', 'l', 'L']);
+        return crate::parse_prefixed(digits).ok();
+    }
+    values.get(expr).copied()
+}
+
+/// True if every parenthesis in the expression is closed within it
+fn balanced(expr: &str) -> bool {
+    let mut depth = 0_usize;
+    for c in expr.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => match depth.checked_sub(1) {
+                Some(d) => depth = d,
+                None => return false,
+            },
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+/// Split the expression at the last occurrence of the operator outside of parentheses
+fn split_top_level<'a>(expr: &'a str, op: &str) -> Option<(&'a str, &'a str)> {
+    let mut depth = 0_usize;
+    let mut split = None;
+    for (i, c) in expr.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            // `|` shouldn't match either character of `||`
+            _ if depth == 0
+                && expr[i..].starts_with(op)
+                && (op != "|"
+                    || !(expr[i..].starts_with("||") || expr[..i].ends_with('|'))) =>
+            {
+                split = Some(i);
+            }
+            _ => {}
+        }
+    }
+    split.map(|i| (&expr[..i], &expr[i + op.len()..]))
+}
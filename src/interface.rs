@@ -0,0 +1,181 @@
+use crate::Bitfield;
+use core::fmt;
+use core::marker::PhantomData;
+
+/// A bus or device that stores registers of type `T` at addresses, like an I2C or SPI
+/// sensor.
+///
+/// Implement this once for a bus, and access each register as a bitfield through a
+/// [`Register`] handle. A device with registers of several widths can implement it for
+/// each of them. Interfaces that can't fail, like memory-mapped registers or a model of
+/// a device, can use [`Infallible`](core::convert::Infallible) as the error type.
+pub trait RegisterInterface<T> {
+    /// The type used to select a register, like the register number for an I2C device
+    type Address: Copy;
+
+    /// The error returned when a transfer fails
+    type Error;
+
+    /// Read the value of the register at `address`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transfer fails.
+    fn read_register(&mut self, address: Self::Address) -> Result<T, Self::Error>;
+
+    /// Write `value` to the register at `address`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transfer fails.
+    fn write_register(
+        &mut self,
+        address: Self::Address,
+        value: T,
+    ) -> Result<(), Self::Error>;
+
+    /// A handle to read and write the register at `address` as the bitfield `B`
+    #[inline(always)]
+    fn register<B>(&mut self, address: Self::Address) -> Register<'_, Self, B, T>
+    where
+        B: Bitfield<T>,
+    {
+        Register::new(self, address)
+    }
+}
+
+impl<I, T> RegisterInterface<T> for &mut I
+where
+    I: RegisterInterface<T> + ?Sized,
+{
+    type Address = I::Address;
+    type Error = I::Error;
+
+    #[inline(always)]
+    fn read_register(&mut self, address: Self::Address) -> Result<T, Self::Error> {
+        (**self).read_register(address)
+    }
+
+    #[inline(always)]
+    fn write_register(
+        &mut self,
+        address: Self::Address,
+        value: T,
+    ) -> Result<(), Self::Error> {
+        (**self).write_register(address, value)
+    }
+}
+
+/// A register at a fixed address of a [`RegisterInterface`], read and written as the
+/// bitfield `B`.
+///
+/// ```
+/// # use core::convert::Infallible;
+/// # use tartan_bitfield::{bitfield, RegisterInterface};
+/// bitfield! {
+///     pub struct Config(u16) {
+///         [0..3]  pub mode: u8,
+///         [3..6]  pub average_count: u8,
+///         [15]    pub reset,
+///     }
+/// }
+///
+/// struct Sensor {
+///     registers: [u16; 4],
+/// }
+///
+/// impl RegisterInterface<u16> for Sensor {
+///     type Address = u8;
+///     type Error = Infallible;
+///
+///     fn read_register(&mut self, address: u8) -> Result<u16, Infallible> {
+///         Ok(self.registers[usize::from(address)])
+///     }
+///
+///     fn write_register(&mut self, address: u8, value: u16) -> Result<(), Infallible> {
+///         self.registers[usize::from(address)] = value;
+///         Ok(())
+///     }
+/// }
+///
+/// const CONFIG: u8 = 1;
+///
+/// let mut sensor = Sensor { registers: [0, 0x0009, 0, 0] };
+/// let mut config = sensor.register::<Config>(CONFIG);
+/// assert_eq!(config.read().unwrap().average_count(), 1);
+/// config.modify(|c| c.set_mode(4)).unwrap();
+/// assert_eq!(sensor.registers[1], 0x000c);
+/// ```
+pub struct Register<'a, I, B, T>
+where
+    I: RegisterInterface<T> + ?Sized,
+{
+    interface: &'a mut I,
+    address: I::Address,
+    bitfield: PhantomData<fn(B) -> B>,
+}
+
+impl<'a, I, B, T> Register<'a, I, B, T>
+where
+    I: RegisterInterface<T> + ?Sized,
+    B: Bitfield<T>,
+{
+    /// A handle to the register at `address` of the given interface
+    #[inline(always)]
+    pub fn new(interface: &'a mut I, address: I::Address) -> Self {
+        Self { interface, address, bitfield: PhantomData }
+    }
+
+    /// The address of the register
+    #[inline(always)]
+    pub fn address(&self) -> I::Address {
+        self.address
+    }
+
+    /// Read the register
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the interface fails to read the register.
+    #[inline(always)]
+    pub fn read(&mut self) -> Result<B, I::Error> {
+        self.interface.read_register(self.address).map(B::new)
+    }
+
+    /// Write the register
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the interface fails to write the register.
+    #[inline(always)]
+    pub fn write(&mut self, value: B) -> Result<(), I::Error> {
+        self.interface.write_register(self.address, value.value())
+    }
+
+    /// Read the register, modify it with the given function, and write it back.
+    /// Returns the result of the function.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the interface fails to read or write the register. If the
+    /// read fails, the function isn't called and nothing is written.
+    pub fn modify<F, R>(&mut self, f: F) -> Result<R, I::Error>
+    where
+        F: FnOnce(&mut B) -> R,
+    {
+        let mut value = self.read()?;
+        let result = f(&mut value);
+        self.write(value)?;
+        Ok(result)
+    }
+}
+
+impl<I, B, T> fmt::Debug for Register<'_, I, B, T>
+where
+    I: RegisterInterface<T> + ?Sized,
+    I::Address: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Register").field(&self.address).finish()
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{Bitfield, FieldInfo, FieldLayout, FieldStorage, PackedBits, TruncateInto};
+use core::fmt;
+use core::marker::PhantomData;
+use std::time::SystemTime;
+use std::vec::Vec;
+
+/// A change to one field of a [`Journaled`] bitfield
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JournalEntry {
+    /// The field that changed
+    pub field: &'static FieldInfo,
+    /// The value of the field before the change
+    pub old: u128,
+    /// The value of the field after the change
+    pub new: u128,
+    /// When the change was made. All fields changed by the same call share a time.
+    pub time: SystemTime,
+}
+
+struct Mutation<B> {
+    old: B,
+    new: B,
+    time: SystemTime,
+}
+
+/// A bitfield value that keeps a journal of every change, so that changes can be undone
+/// and redone, or replayed on another value. Useful for interactive tools that poke at
+/// registers.
+///
+/// Each call to [`set`](Self::set) or [`update`](Self::update) that changes the value is
+/// one step for [`undo`](Self::undo) and [`redo`](Self::redo), even if it changes
+/// several fields. Making a new change discards any steps that were undone.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, Journaled};
+/// bitfield! {
+///     pub struct Control(u16) {
+///         [0..4]  pub mode: u8,
+///         [4]     pub enable,
+///     }
+/// }
+///
+/// let mut cr = Journaled::new(Control(0x0001));
+/// cr.update(|c| c.set_mode(3));
+/// cr.update(|c| {
+///     c.set_mode(5);
+///     c.set_enable(true);
+/// });
+/// let changes: Vec<_> = cr.journal().map(|e| (e.field.name, e.old, e.new)).collect();
+/// assert_eq!(changes, [("mode", 1, 3), ("mode", 3, 5), ("enable", 0, 1)]);
+///
+/// assert!(cr.undo());
+/// assert_eq!(cr.get(), Control(0x0003));
+/// assert!(cr.redo());
+/// assert_eq!(cr.get(), Control(0x0015));
+///
+/// // Replay the same changes on another value
+/// assert_eq!(cr.replay(Control(0xff00)), Control(0xff15));
+/// ```
+pub struct Journaled<B, T> {
+    value: B,
+    done: Vec<Mutation<B>>,
+    undone: Vec<Mutation<B>>,
+    underlying: PhantomData<T>,
+}
+
+impl<B, T> Journaled<B, T>
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits + TruncateInto<T>,
+{
+    /// Start a journal of changes to the given bitfield value
+    #[inline(always)]
+    pub fn new(value: B) -> Self {
+        Self { value, done: Vec::new(), undone: Vec::new(), underlying: PhantomData }
+    }
+
+    /// The current value of the bitfield
+    #[inline(always)]
+    pub fn get(&self) -> B {
+        self.value
+    }
+
+    /// Replace the bitfield with the given value, recording the fields that change
+    pub fn set(&mut self, value: B) {
+        let old = self.value;
+        let changed = crate::packed_value(old).into_u128()
+            != crate::packed_value(value).into_u128();
+        if !changed {
+            return;
+        }
+        self.value = value;
+        self.done.push(Mutation { old, new: value, time: SystemTime::now() });
+        self.undone.clear();
+    }
+
+    /// Modify the bitfield with the given function, typically by calling its `set_`
+    /// methods. All of the fields it changes are recorded as one step.
+    pub fn update<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut B) -> R,
+    {
+        let mut bitfield = self.value;
+        let result = f(&mut bitfield);
+        self.set(bitfield);
+        result
+    }
+
+    /// Revert the last step. Returns `false` if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(mutation) = self.done.pop() else {
+            return false;
+        };
+        self.value = mutation.old;
+        self.undone.push(mutation);
+        true
+    }
+
+    /// Apply the last step that was undone again. Returns `false` if there is nothing to
+    /// redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(mutation) = self.undone.pop() else {
+            return false;
+        };
+        self.value = mutation.new;
+        self.done.push(mutation);
+        true
+    }
+
+    /// The changes to each field, oldest first, not including steps that were undone
+    pub fn journal(&self) -> impl Iterator<Item = JournalEntry> + '_ {
+        self.done.iter().flat_map(|mutation| {
+            B::LAYOUT.iter().filter_map(move |field| {
+                let old = crate::raw_field(mutation.old, field.lsb, field.msb);
+                let new = crate::raw_field(mutation.new, field.lsb, field.msb);
+                (old != new).then_some(JournalEntry {
+                    field,
+                    old,
+                    new,
+                    time: mutation.time,
+                })
+            })
+        })
+    }
+
+    /// Apply the changes in the journal to another value, writing only the fields that
+    /// changed
+    pub fn replay(&self, value: B) -> B {
+        self.journal().fold(value, |value, entry| {
+            crate::with_raw_field(value, entry.field.lsb, entry.field.msb, entry.new)
+        })
+    }
+
+    /// Discard the journal, keeping the current value
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+
+    /// Stop journaling and return the current value of the bitfield
+    #[inline(always)]
+    pub fn into_inner(self) -> B {
+        self.value
+    }
+}
+
+impl<B, T> fmt::Debug for Journaled<B, T>
+where
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Journaled").field(&self.value).finish()
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{Bitfield, FieldStorage, PackedBits};
+use core::slice;
+
+/// The name and position of one field of a bitfield type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldInfo {
+    /// The name of the field, as declared
+    pub name: &'static str,
+    /// The least significant bit of the field
+    pub lsb: u8,
+    /// One more than the most significant bit of the field, like the end of a `Range`
+    pub msb: u8,
+    /// True if the field is a single-bit `bool` flag rather than an integer
+    pub flag: bool,
+}
+
+impl FieldInfo {
+    /// The number of bits in the field
+    #[must_use]
+    pub const fn width(&self) -> u8 {
+        self.msb - self.lsb
+    }
+
+    /// A mask of the bits in the field
+    #[must_use]
+    pub const fn mask(&self) -> u128 {
+        (u128::MAX >> (128 - self.width())) << self.lsb
+    }
+}
+
+/// Bitfield types that describe the position of each of their fields.
+///
+/// This is implemented by all types defined with the [`bitfield`](crate::bitfield)
+/// macro, so generic code can inspect fields without knowing the concrete type.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, FieldInfo, FieldLayout};
+/// bitfield! {
+///     pub struct Example(u16) {
+///         [0..4]  pub mode,
+///         [7]     pub enable,
+///         [8..16] pub count: u8,
+///     }
+/// }
+///
+/// assert_eq!(Example::LAYOUT.len(), 3);
+/// assert_eq!(
+///     Example::LAYOUT[1],
+///     FieldInfo { name: "enable", lsb: 7, msb: 8, flag: true },
+/// );
+/// assert_eq!(Example::LAYOUT[2].width(), 8);
+/// ```
+pub trait FieldLayout {
+    /// Every field of the type, in the order they were declared
+    const LAYOUT: &'static [FieldInfo];
+}
+
+/// A mask of the bits covered by any field of the bitfield `B`
+#[doc(hidden)]
+pub fn field_mask<B, T>() -> T::Storage
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage,
+    T::Storage: PackedBits,
+{
+    T::Storage::from_u128(B::LAYOUT.iter().fold(0, |mask, field| mask | field.mask()))
+}
+
+/// The value of one field of a bitfield, as reported by [`FieldValues`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldValue {
+    /// The value of a single-bit flag
+    Bool(bool),
+    /// The raw bits of a multi-bit field, regardless of its declared type
+    Uint(u128),
+}
+
+/// An iterator over the name and value of each field of a bitfield, in the order they
+/// were declared.
+///
+/// This is returned by the `fields` method generated by the [`bitfield`](crate::bitfield)
+/// macro, and it can be created for any bitfield type with [`FieldValues::new`]. It is
+/// useful for diagnostics and register viewers that don't know the concrete type.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, FieldValue};
+/// bitfield! {
+///     pub struct Status(u16) {
+///         [0..4]  pub mode,
+///         [4]     pub ready,
+///         [8..16] pub count: u8,
+///     }
+/// }
+///
+/// let fields: Vec<_> = Status(0x2a1b).fields().collect();
+/// assert_eq!(
+///     fields,
+///     [
+///         ("mode", FieldValue::Uint(0xb)),
+///         ("ready", FieldValue::Bool(true)),
+///         ("count", FieldValue::Uint(0x2a)),
+///     ],
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct FieldValues {
+    packed: u128,
+    fields: slice::Iter<'static, FieldInfo>,
+}
+
+impl FieldValues {
+    /// Iterate over the fields of the given bitfield value
+    pub fn new<B, T>(bitfield: B) -> Self
+    where
+        B: Bitfield<T> + FieldLayout,
+        T: FieldStorage + Into<T::Storage>,
+        T::Storage: PackedBits,
+    {
+        let packed = crate::packed_value(bitfield).into_u128();
+        Self { packed, fields: B::LAYOUT.iter() }
+    }
+}
+
+impl Iterator for FieldValues {
+    type Item = (&'static str, FieldValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let field = self.fields.next()?;
+        let bits = self.packed.packed_field(field.lsb, field.msb);
+        let value =
+            if field.flag { FieldValue::Bool(bits != 0) } else { FieldValue::Uint(bits) };
+        Some((field.name, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.fields.size_hint()
+    }
+}
+
+impl ExactSizeIterator for FieldValues {}
+
+/// An iterator over the names of the single-bit flags of a bitfield that are set, in
+/// the order they were declared.
+///
+/// This is returned by the `iter_set_flags` method generated by the
+/// [`bitfield`](crate::bitfield) macro, and it can be created for any bitfield type with
+/// [`SetFlags::new`].
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Interrupts(u8) {
+///         [0] pub rx_done,
+///         [1] pub tx_done,
+///         [2] pub crc_err,
+///         [4..8] pub count,
+///     }
+/// }
+///
+/// let irq = Interrupts(0xf5);
+/// let flags: Vec<_> = irq.iter_set_flags().collect();
+/// assert_eq!(format!("IRQ: {flags:?}"), r#"IRQ: ["rx_done", "crc_err"]"#);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SetFlags(FieldValues);
+
+impl SetFlags {
+    /// Iterate over the flags of the given bitfield value
+    pub fn new<B, T>(bitfield: B) -> Self
+    where
+        B: Bitfield<T> + FieldLayout,
+        T: FieldStorage + Into<T::Storage>,
+        T::Storage: PackedBits,
+    {
+        Self(FieldValues::new(bitfield))
+    }
+}
+
+impl Iterator for SetFlags {
+    type Item = &'static str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.find_map(|(name, value)| (value == FieldValue::Bool(true)).then_some(name))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.0.size_hint().1)
+    }
+}
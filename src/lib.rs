@@ -4,7 +4,10 @@
 //! # Features
 //!
 //!   * **Performance**: Generated code is nearly identical to hand-rolled bit twiddling.
-//!   * **Safety**: Absolutely no unsafe code in the implementation or usage.
+//!     With the `rayon` feature, bulk helpers like [`get_bits_batch`] also have parallel
+//!     versions for processing large buffers.
+//!   * **Safety**: No unsafe code in the implementation or usage, except for the pointer
+//!     cast that borrows a bitfield in place from its underlying integer.
 //!   * **Portability**:
 //!     * `#![no_std]`-compatible out of the box.
 //!     * Unlike bit fields in C, the layout is predictable. See the section about
@@ -18,12 +21,54 @@
 //!     * Single-bit flags and multi-bit fields can be defined in the same structure.
 //!     * Bit ranges can be accessed as non-primitive, non-integer types (including other
 //!       bitfield structs) using appropriate [`Into`] and [`From`] implementations.
+//!     * Exact-width integer types from the [`uint`] module, like `u3`, make it
+//!       impossible to pass an out-of-range value to a setter. Types like `u24` can also
+//!       hold a whole bitfield, for protocols with odd-sized words.
 //!     * The structs implement all the traits you would expect. See the documentation
 //!       for [`bitfield`]. A [`bitfield_without_debug`] macro is also available if you
-//!       want to provide your own debugging output.
+//!       want to provide your own debugging output, and derived traits other than
+//!       `Clone` and `Copy` can be skipped to implement them by hand.
+//!     * Errors from fallible operations like parsing and validation implement
+//!       `core::error::Error`, which is the same trait as `std::error::Error`, so they
+//!       work with error handling crates with or without `std`.
 //!     * Accessors can be defined in a trait, which is useful for registers where where
 //!       some fields are common, but others are only defined in certain states. See
-//!       [`bitfield_accessors`].
+//!       [`bitfield_accessors`]. Types from other crates, like register values from a
+//!       peripheral access crate, can get accessors from an extension trait. See
+//!       [`bitfield_ext`].
+//!     * Groups of fields that appear in several bitfields can be declared once and
+//!       included in each of them, and a bitfield can be extended with more fields. See
+//!       [`bitfield_fields`].
+//!     * Fields can be grouped under a common prefix, like `tx_enable` and `rx_enable`
+//!       for registers with symmetric halves.
+//!     * Values stored across a pair of registers, like 64-bit timers on a 32-bit bus,
+//!       can be read and written as one bitfield. See [`RegisterPair`].
+//!     * Fields can also be described at runtime with a [`FieldSpec`], for generic driver
+//!       code that programs registers from a table of field positions and values.
+//!     * Instruction words can be decoded by matching them against bit patterns with
+//!       wildcards and named fields. See [`bitmatch`].
+//!     * Packets or descriptors with several layouts can be decoded into an enum of
+//!       bitfields by their discriminating bits. See [`bitfield_dispatch`].
+//!     * Registers can be emulated with side effects on reads and writes, like
+//!       clear-on-read flags, so a bitfield can serve as a device model. See
+//!       [`Emulated`].
+//!     * Changes to fields can be observed with a callback, for register viewers and
+//!       hardware-in-the-loop tests. See [`Observed`] and [`Traced`].
+//!     * A shadow copy of a register can track which bits changed, to skip redundant
+//!       writes over a slow bus. See [`Cached`].
+//!     * With the `std` feature, two values can be compared field by field, with the
+//!       changed fields highlighted in the terminal. See [`FieldDiff`].
+//!     * With the `rand` feature, random values can be generated for randomized testing,
+//!       either with every bit random or with reserved bits clear. See [`RandomFields`].
+//!       Fuzzers can mutate one field at a time with [`mutate_field`] and
+//!       [`flip_field_bit`].
+//!     * With the `std` feature, changes can be journaled with timestamps, and undone,
+//!       redone, or replayed on another value. See [`Journaled`].
+//!     * Layouts can be exported for scripts and tools that need to share them with the
+//!       Rust code, as JSON, TOML, Kaitai Struct, or fields for a Wireshark dissector.
+//!       See [`export`].
+//!     * Definitions can be generated at build time from the `#define`s or bit-field
+//!       structs in a C header, which helps when migrating a driver. See [`import`].
 //!
 //! # Example
 //!
@@ -51,7 +96,11 @@
 //!         [6..=17] b: u16,
 //!
 //!         // Note that this bit range overlaps with `b`. This is allowed.
-//!         [16..20] pub c: u8,
+//!         //
+//!         // When the type is omitted, the accessors use the smallest unsigned integer
+//!         // type that fits the range, which is a `u8` here. See [`SmallestUint`]. A
+//!         // range of one bit, like `[16..17]`, uses a `bool` instead.
+//!         [16..20] pub c,
 //!
 //!         // Accessors for field `d` will take/return a boolean and refer to a single
 //!         // bit. Note that the `bool` is implied and not specified after the name.
@@ -60,7 +109,11 @@
 //!         // This will cover the 6 most significant bits of the wrapped value, but
 //!         // the getters will take/return a `SubFields` struct instead of `u8`. This is
 //!         // useful for nested bitfields, but the `A as B` syntax works for any `B`
-//!         // which implements `Into<A>` and `From<A>`.
+//!         // which implements `Into<A>` and `From<A>`. As a special case, `u32 as f32`
+//!         // and `u64 as f64` reinterpret the bits as an IEEE 754 floating-point value.
+//!         //
+//!         // Nested bitfields can also be declared without `as`, as in `e: SubFields`,
+//!         // in which case the underlying type comes from `SubFields` itself.
 //!         [26..32] pub e: u8 as SubFields,
 //!     }
 //! }
@@ -140,6 +193,61 @@
 //!     [`u32::from_be_bytes`] and [`u64::to_le_bytes`], or a crate like
 //!     [byteorder](https://docs.rs/byteorder/latest/byteorder/).
 //!
+//! # Serialization
+//!
+//! Attributes before the struct name in the [`bitfield`] macro are applied to the
+//! generated struct, including `derive` attributes. Since every bitfield is a
+//! `#[repr(transparent)]` wrapper around a single integer, derive macros from
+//! serialization crates generally work as-is and encode the struct as its underlying
+//! integer.
+//!
+//! With the `rkyv` feature, every bitfield also implements the `Archive`, `Serialize`,
+//! and `Deserialize` traits of [rkyv](https://rkyv.org/), and is archived as its
+//! underlying integer. Records full of bitfields can then be memory-mapped and read in
+//! place without a deserialization step, by calling `get` on the archived bitfield. See
+//! [`ArchivedBitfield`].
+//!
+//! # Register Drivers
+//!
+//! Bitfields don't depend on any particular bus or driver framework. Drivers that share
+//! code between buses can implement [`RegisterInterface`] once for each bus, and access
+//! each register through a [`Register`] handle, which reads, writes, and modifies it as
+//! a bitfield.
+//!
+//! With the `embedded-hal` feature, [`I2cInterface`] implements [`RegisterInterface`]
+//! for a device on an [embedded-hal](https://docs.rs/embedded-hal) I2C bus, using the
+//! common protocol of writing a register number and then reading or writing its bytes.
+//!
+//! For other buses, and for frameworks that model registers as integers, every bitfield
+//! converts [`From`] and [`Into`] its underlying integer. For blocks of consecutive
+//! registers, see [`bitfields_from_bytes`] and [`copy_bitfields_to_bytes`].
+//!
+//! The traits of the [device-driver](https://docs.rs/device-driver) crate are not
+//! implemented, since it generates its own register types from a device description.
+//! Its register interfaces transfer registers as bytes, so an implementation for a bus
+//! can serve bitfields from this crate as well by converting them with [`ByteRepr`].
+//!
+//! # Derive Macro
+//!
+//! With the `derive` feature, the same fields can be declared with attributes on a
+//! struct that derives `Bitfield`. This gives IDEs a real struct definition to work with,
+//! and reports malformed fields with errors that point at the offending attribute.
+//!
+//! ```ignore
+//! use tartan_bitfield::Bitfield;
+//!
+//! #[derive(Default, Clone, Copy, PartialEq, Eq, Bitfield)]
+//! #[repr(transparent)]
+//! #[field(0..4, pub a: u8)]
+//! #[field(6..=17, b: u16 where 1..)]
+//! #[field(25, pub d)]
+//! #[invariant(Example::check)]
+//! pub struct Example(u32);
+//! ```
+//!
+//! The derive generates everything that [`bitfield`] does except the struct itself, so
+//! `Default`, `Clone`, `Copy`, `PartialEq`, and `Eq` must be derived explicitly.
+//!
 //! # Alternatives
 //!
 //! I have been using this in my personal OS project for a while, and it meets my needs
@@ -171,18 +279,194 @@
 #![allow(clippy::similar_names)]
 #![allow(clippy::upper_case_acronyms)]
 
+#[cfg(feature = "std")]
+extern crate std;
+
 use core::convert::From;
 use core::default::Default;
+use core::marker::PhantomData;
+use core::num::ParseIntError;
 use core::ops;
 
 // Must be re-exported so that crates that use these macros will be able to resolve it
 #[doc(hidden)]
 pub use paste::paste;
 
+mod access;
+#[doc(hidden)]
+pub use access::{
+    from_packed_value, increment_field, odd_parity, packed_value, raw_field,
+    widen_to_packed, with_raw_field, PackedBits,
+};
+
+#[cfg(feature = "derive")]
+pub use tartan_bitfield_derive::Bitfield;
+
+mod bitmatch;
+#[doc(hidden)]
+pub use bitmatch::{bitmatch_field, bitmatch_fixed, bitmatch_mask};
+
+mod bits;
+pub use bits::{bitfield_from_bits, extract_bitfield, insert_bitfield, AllValues, Bits};
+
+mod bytes;
+pub use bytes::{
+    bitfields_from_bytes, copy_bitfields_from_bytes, copy_bitfields_to_bytes,
+    BitfieldsFromBytes, ByteOrder, ByteRepr, LengthMismatchError,
+};
+
+mod cache;
+pub use cache::Cached;
+
+mod conformance;
+#[doc(hidden)]
+pub use conformance::{assert_field_copy, assert_field_layout};
+
+mod convert;
+#[doc(hidden)]
+pub use convert::{FieldConversion, FieldConversionDirect, FieldConversionFallback};
+
+mod diagram;
+pub use diagram::LayoutDiagram;
+
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "std")]
+pub use diff::FieldDiff;
+
+mod emulate;
+pub use emulate::{Emulated, EmulatorHooks};
+
+mod encoding;
+pub use encoding::{Bcd, BcdRepr, Gray, OneHot, Swapped};
+
+pub mod export;
+
+mod fixed;
+pub use fixed::{Q, UQ};
+
+mod format;
+pub use format::{write_field_table, DebugSummary, Summary, WriteFields};
+
+mod frame;
+pub use frame::FrameError;
+#[doc(hidden)]
+pub use frame::{emit_frame, parse_frame};
+
+#[cfg(feature = "embedded-hal")]
+mod hal;
+#[cfg(feature = "embedded-hal")]
+pub use hal::I2cInterface;
+
+#[cfg(feature = "std")]
+pub mod import;
+
+mod interface;
+pub use interface::{Register, RegisterInterface};
+
+#[cfg(feature = "std")]
+mod journal;
+#[cfg(feature = "std")]
+pub use journal::{JournalEntry, Journaled};
+
+mod layout;
+#[doc(hidden)]
+pub use layout::field_mask;
+pub use layout::{FieldInfo, FieldLayout, FieldValue, FieldValues, SetFlags};
+
+mod observe;
+pub use observe::Observed;
+
+mod packed;
+pub use packed::{packed_array_bytes, PackedArray, PackedSlice};
+
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "rayon")]
+pub use parallel::{
+    par_copy_bitfields_from_bytes, par_copy_bitfields_to_bytes, par_get_bits_batch,
+};
+
+#[cfg(feature = "rand")]
+mod random;
+#[cfg(feature = "rand")]
+#[doc(hidden)]
+pub use random::random_value;
+#[cfg(feature = "rand")]
+pub use random::{flip_field_bit, mutate_field, RandomFields};
+// Must be re-exported so that the `Distribution` implementations generated by
+// `bitfield` can name it
+#[cfg(feature = "rand")]
+#[doc(hidden)]
+pub use rand;
+
+#[cfg(feature = "rkyv")]
+mod archive;
+#[cfg(feature = "rkyv")]
+#[doc(hidden)]
+pub use archive::resolve_bitfield;
+#[cfg(feature = "rkyv")]
+pub use archive::ArchivedBitfield;
+// Must be re-exported so that the rkyv implementations generated by `bitfield` can name
+// it
+#[cfg(feature = "rkyv")]
+#[doc(hidden)]
+pub use rkyv;
+
+mod parse;
+#[doc(hidden)]
+pub use parse::{parse_flag, NestedField, NestedFieldFallback, NestedFieldParse};
+pub use parse::{FieldParseError, ParseFields};
+
+pub mod uint;
+pub use uint::UInt;
+
+pub mod svd;
+
+mod spec;
+pub use spec::FieldSpec;
+
+mod split;
+pub use split::{RegisterPair, SplitHalves};
+
+mod trace;
+pub use trace::Traced;
+
+mod validate;
+pub use validate::{FieldCheck, Validate, ValidationError};
+
+mod width;
+#[doc(hidden)]
+pub use width::{DefaultField, InterfaceWidth, InterfaceWidthFallback, Width, WidthType};
+pub use width::{FieldStorage, SmallestUint};
+
 /// Marker trait implemented by types defined with the [`bitfield`] macro.
 ///
 /// This mainly exists to allow type inference in the [`bitfield_accessors`] macro, but it
 /// also aids documentation and may be useful in user code.
+///
+/// It is also implemented for the unsigned integer types from `u8` to `u128`, as
+/// bitfields of themselves, so generic code can accept raw integers too:
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, Bitfield, FieldSpec};
+/// fn low_nibble<B: Bitfield<u16>>(value: B) -> u128 {
+///     value.field(FieldSpec::new(0, 4))
+/// }
+///
+/// bitfield! {
+///     pub struct Status(u16) {
+///         [0..4] pub mode,
+///     }
+/// }
+///
+/// assert_eq!(low_nibble(Status(0x1234)), 4);
+/// assert_eq!(low_nibble(0x1234_u16), 4);
+/// ```
+///
+/// Because of this, `from_halves` and `into_halves` on an integer are ambiguous when this
+/// trait and [`SplitHalves`] are both in scope. Call them as
+/// `SplitHalves::into_halves(x)` to pick the integer methods.
 pub trait Bitfield<T>
 where
     Self: core::fmt::Debug + Default + Copy + Eq + From<T> + Into<T>,
@@ -198,6 +482,312 @@ where
     fn value(self) -> T {
         self.into()
     }
+
+    /// Overwrite the whole underlying representation of the bitfield in place
+    ///
+    /// ```
+    /// # use tartan_bitfield::{bitfield, Bitfield};
+    /// fn reset<B: Bitfield<u8>>(reg: &mut B) {
+    ///     reg.set_value(0x80);
+    /// }
+    ///
+    /// bitfield! {
+    ///     pub struct Control(u8) {
+    ///         [7] pub enable,
+    ///     }
+    /// }
+    ///
+    /// let mut control = Control(0x12);
+    /// reset(&mut control);
+    /// assert_eq!(control, Control(0x80));
+    /// ```
+    #[inline(always)]
+    fn set_value(&mut self, value: T) {
+        *self = Self::new(value);
+    }
+
+    /// Convert to another bitfield type with the same underlying representation, keeping
+    /// all the bits. This is useful for moving between a generic view of a register and
+    /// one that is specific to a state or mode:
+    ///
+    /// ```
+    /// # use tartan_bitfield::{bitfield, Bitfield};
+    /// bitfield! {
+    ///     pub struct Status(u8) {
+    ///         [0..2] pub mode,
+    ///     }
+    /// }
+    ///
+    /// bitfield! {
+    ///     pub struct TransmitStatus(u8) {
+    ///         [0..2] pub mode,
+    ///         [2]    pub underrun,
+    ///     }
+    /// }
+    ///
+    /// let status = Status(0b101);
+    /// let transmit: TransmitStatus = status.reinterpret();
+    /// assert!(transmit.underrun());
+    /// assert_eq!(transmit.reinterpret::<Status>(), status);
+    /// ```
+    #[inline(always)]
+    fn reinterpret<B>(self) -> B
+    where
+        B: Bitfield<T>,
+    {
+        B::new(self.value())
+    }
+
+    /// Apply a function to the underlying representation, like masking or swapping
+    /// bytes, and wrap the result in the same bitfield type:
+    ///
+    /// ```
+    /// # use tartan_bitfield::{bitfield, Bitfield};
+    /// bitfield! {
+    ///     pub struct Header(u16) {
+    ///         [0..8]  pub length: u8,
+    ///         [15]    pub last,
+    ///     }
+    /// }
+    ///
+    /// let wire = Header(0x0580);
+    /// let header = wire.map(u16::swap_bytes);
+    /// assert_eq!((header.length(), header.last()), (5, true));
+    /// assert_eq!(header.map(|v| v & 0x00ff), Header(0x0005));
+    /// ```
+    #[inline(always)]
+    #[must_use]
+    fn map<F>(self, f: F) -> Self
+    where
+        F: FnOnce(T) -> T,
+    {
+        Self::new(f(self.value()))
+    }
+
+    /// Construct a bitfield from the most significant and least significant halves of
+    /// its underlying value, as when it is stored in two registers. See
+    /// [`RegisterPair`].
+    ///
+    /// ```
+    /// # use tartan_bitfield::{bitfield, Bitfield};
+    /// bitfield! {
+    ///     pub struct Counter(u64) {
+    ///         [0..40] pub ticks: u64,
+    ///     }
+    /// }
+    ///
+    /// let counter = Counter::from_halves(0x12, 0x3456_789a);
+    /// assert_eq!(counter.ticks(), 0x12_3456_789a);
+    /// assert_eq!(counter.into_halves(), (0x12, 0x3456_789a));
+    /// ```
+    #[inline(always)]
+    fn from_halves(high: T::Half, low: T::Half) -> Self
+    where
+        T: SplitHalves,
+    {
+        Self::new(T::from_halves(high, low))
+    }
+
+    /// Split the underlying value of the bitfield into its most significant and least
+    /// significant halves. See [`from_halves`](Self::from_halves).
+    #[inline(always)]
+    fn into_halves(self) -> (T::Half, T::Half)
+    where
+        T: SplitHalves,
+    {
+        self.value().into_halves()
+    }
+
+    /// The value of the field described by `spec`. See [`FieldSpec`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field extends past the end of the underlying type.
+    #[inline(always)]
+    fn field(self, spec: FieldSpec) -> u128
+    where
+        T: FieldStorage + Into<T::Storage>,
+        T::Storage: PackedBits,
+    {
+        assert!(spec.msb() <= T::MAX_BITS, "field extends past the end of the value");
+        raw_field(self, spec.lsb, spec.msb())
+    }
+
+    /// Replace the field described by `spec` with the low bits of `value`. See
+    /// [`FieldSpec`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field extends past the end of the underlying type.
+    #[inline(always)]
+    #[must_use]
+    fn with_field(self, spec: FieldSpec, value: u128) -> Self
+    where
+        T: FieldStorage + Into<T::Storage>,
+        T::Storage: PackedBits + TruncateInto<T>,
+    {
+        assert!(spec.msb() <= T::MAX_BITS, "field extends past the end of the value");
+        with_raw_field(self, spec.lsb, spec.msb(), value)
+    }
+
+    /// Replace the field described by `spec` with the low bits of `value` in place. See
+    /// [`FieldSpec`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field extends past the end of the underlying type.
+    #[inline(always)]
+    fn set_field(&mut self, spec: FieldSpec, value: u128)
+    where
+        T: FieldStorage + Into<T::Storage>,
+        T::Storage: PackedBits + TruncateInto<T>,
+    {
+        *self = self.with_field(spec, value);
+    }
+}
+
+// Plain integers are bitfields of themselves, so that generic code and test helpers can
+// take raw values as well as the types defined with `bitfield`
+impl Bitfield<u8> for u8 {}
+impl Bitfield<u16> for u16 {}
+impl Bitfield<u32> for u32 {}
+impl Bitfield<u64> for u64 {}
+impl Bitfield<u128> for u128 {}
+
+/// A bitfield that can be borrowed in place from a mutable reference to its underlying
+/// value, so that changes go straight to the referenced storage.
+///
+/// Every struct defined with [`bitfield`] implements this, since it is
+/// `#[repr(transparent)]`, along with the plain integers. See [`BitfieldMut`].
+pub trait FromMut<T> {
+    /// Borrow the referenced value as this bitfield
+    fn from_mut(value: &mut T) -> &mut Self;
+}
+
+macro_rules! from_mut_impl {
+    ($type:ty) => {
+        impl FromMut<$type> for $type {
+            #[inline(always)]
+            fn from_mut(value: &mut $type) -> &mut Self {
+                value
+            }
+        }
+    };
+}
+
+from_mut_impl!(u8);
+from_mut_impl!(u16);
+from_mut_impl!(u32);
+from_mut_impl!(u64);
+from_mut_impl!(u128);
+
+/// A mutable view of a bitfield whose underlying value lives in borrowed storage.
+///
+/// This is useful for bitfields embedded in larger structures, like an array of DMA
+/// descriptors, where you want to modify fields without manually reading the value out,
+/// updating it, and writing it back. The view dereferences to the bitfield, so its
+/// generated accessors can be called on it directly.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, BitfieldMut};
+/// bitfield! {
+///     pub struct Descriptor(u32) {
+///         [0..16] pub length: u16,
+///         [31]    pub owned,
+///     }
+/// }
+///
+/// let mut ring = [0_u32; 4];
+/// let mut view = BitfieldMut::<Descriptor, _>::new(&mut ring[2]);
+/// view.set_length(0x200);
+/// view.set_owned(true);
+/// assert_eq!(view.length(), 0x200);
+/// assert_eq!(ring, [0, 0, 0x8000_0200, 0]);
+/// ```
+///
+/// The view borrows the storage as the bitfield through [`FromMut`], so every change is
+/// made directly in the storage, and nothing is written back when the view is released.
+pub struct BitfieldMut<'a, B, T>
+where
+    B: Bitfield<T> + FromMut<T>,
+    T: Copy,
+{
+    value: &'a mut B,
+    storage: PhantomData<&'a mut T>,
+}
+
+impl<'a, B, T> BitfieldMut<'a, B, T>
+where
+    B: Bitfield<T> + FromMut<T>,
+    T: Copy,
+{
+    /// Create a view of the bitfield stored in the referenced value
+    #[inline(always)]
+    pub fn new(storage: &'a mut T) -> Self {
+        Self { value: B::from_mut(storage), storage: PhantomData }
+    }
+
+    /// The current value of the bitfield
+    #[inline(always)]
+    pub fn get(&self) -> B {
+        *self.value
+    }
+
+    /// Replace the bitfield with the given value
+    #[inline(always)]
+    pub fn set(&mut self, value: B) {
+        *self.value = value;
+    }
+
+    /// Modify the bitfield with the given function, typically by calling its `set_`
+    /// methods
+    #[inline(always)]
+    pub fn update<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut B) -> R,
+    {
+        f(self.value)
+    }
+
+    /// Release the view and return the storage, borrowed as the bitfield
+    #[inline(always)]
+    pub fn into_inner(self) -> &'a mut B {
+        self.value
+    }
+}
+
+impl<B, T> core::ops::Deref for BitfieldMut<'_, B, T>
+where
+    B: Bitfield<T> + FromMut<T>,
+    T: Copy,
+{
+    type Target = B;
+
+    #[inline(always)]
+    fn deref(&self) -> &B {
+        self.value
+    }
+}
+
+impl<B, T> core::ops::DerefMut for BitfieldMut<'_, B, T>
+where
+    B: Bitfield<T> + FromMut<T>,
+    T: Copy,
+{
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut B {
+        self.value
+    }
+}
+
+impl<B, T> core::fmt::Debug for BitfieldMut<'_, B, T>
+where
+    B: Bitfield<T> + FromMut<T>,
+    T: Copy,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_tuple("BitfieldMut").field(&self.value).finish()
+    }
 }
 
 /// Define a structure that wraps a number with accessors for certain bit ranges.
@@ -210,309 +800,4408 @@ where
 ///   * [`Debug`]
 ///   * [`Default`]
 ///   * [`Copy`]
+///   * [`DebugSummary`]
 ///   * [`Eq`]
 ///   * [`Into<T>`](Into)
 ///   * [`From<T>`](From)
-#[macro_export]
-macro_rules! bitfield {
-    [
-        $( #[$meta:meta] )*
-        $vis:vis struct $struct:ident($underlying_type:ty) {
-            $($body:tt)*
-        }
-    ] => {
-        $crate::bitfield_without_debug! {
-            $(#[$meta])*
-            $vis struct $struct($underlying_type) {
-                $($body)*
-            }
-        }
-
-        impl ::core::fmt::Debug for $struct {
-            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
-                let mut struct_out = f.debug_struct(stringify!($struct));
-                struct_out.field("<value>", &self.0);
-                self.fmt_fields(&mut struct_out);
-                struct_out.finish()
-            }
-        }
-    }
-}
-
-/// Same as the [`bitfield`] macro without a [`Debug`] implementation provided.
+///   * [`FromIterator<bool>`](FromIterator), from bits starting with the least
+///     significant
+///   * [`FromStr`](core::str::FromStr), accepting the same formats as [`parse_prefixed`]
+///   * [`ParseFields`]
+///   * [`svd::FieldReader`]
+///   * [`Validate`]
+///   * [`WriteFields`]
 ///
-/// Since Debug is required by the [`Bitfield`] trait, the caller must provide their own
-/// implementation.
-#[macro_export]
-macro_rules! bitfield_without_debug {
-    [
-        $( #[$meta:meta] )*
-        $vis:vis struct $struct:ident($underlying_type:ty) {
-            $($body:tt)*
-        }
-    ] => {
-        $( #[$meta] )*
-        #[repr(transparent)]
-        #[derive(Default, Clone, Copy, PartialEq, Eq)]
-        $vis struct $struct($underlying_type);
-
-        impl $struct {
-            $crate::bitfield_accessors! { $($body)* }
-        }
-
-        impl $crate::Bitfield<$underlying_type> for $struct {}
-
-        impl ::core::convert::From<$underlying_type> for $struct {
-            #[inline(always)]
-            fn from(val: $underlying_type) -> Self { Self(val) }
-        }
-
-        impl ::core::convert::From<$struct> for $underlying_type {
-            #[inline(always)]
-            fn from(val: $struct) -> Self { val.0 }
-        }
-    };
-}
-
-/// Define getters and setters for certain bit ranges. The containing type must
-/// implement the [`Bitfield`] trait.
+/// It will also have a `from_str_radix` function that parses the underlying value in an
+/// arbitrary base, like [`u32::from_str_radix`], a `fields` method that iterates over the
+/// values of its fields, and an `iter_set_flags` method that iterates over the names of
+/// single-bit fields that are set. See [`FieldValues`] and [`SetFlags`]. Its bits can be
+/// iterated with `iter_bits` and collected with `from_bits`. See [`Bits`]. A packet
+/// diagram of the value can be drawn with `layout_diagram`. See [`LayoutDiagram`]. A
+/// table of the fields can be written with `format_table`. See [`write_field_table`]. The
+/// bit range of each field is available as a constant. See [`field_range`]. The names of
+/// all the fields are listed in declaration order by the `FIELD_NAMES` constant.
 ///
-/// This is most commonly invoked by the [`bitfield`] macro, but it can be used on its own
-/// to define common accessors as part of a trait, as in the example below:
+/// The documentation generated for the struct ends with a table of the bit range of
+/// each field, and the documentation of each accessor ends with its bit range, so that
+/// readers don't have to find the macro invocation. Bit numbers are shown as declared.
+///
+/// The `from_value_truncated` function constructs a value from a wider integer, keeping
+/// only the bits that fit in the underlying type. This is convenient for registers read
+/// over a wider bus:
 ///
 /// ```
-/// # use tartan_bitfield::{Bitfield, bitfield, bitfield_accessors};
-/// #
-/// trait CommonFields: Bitfield<u32> {
-///     bitfield_accessors! {
-///         // Since these are part of a trait, the `pub` keyword must be omitted.
-///         [ 0.. 6] a: u8,
-///         [14]     b,
-///         [18..32] c: u16,
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Status(u8) {
+///         [0..4] pub code: u8,
+///         [7]    pub busy,
 ///     }
 /// }
 ///
+/// let bus_read: u32 = 0xdead_be8f;
+/// assert_eq!(Status::from_value_truncated(bus_read), Status(0x8f));
+/// assert_eq!(Status::from_value_truncated(0x1_2345_u64).code(), 5);
+/// ```
+///
+/// A `merge` method copies the bits of another value selected by a mask, for partial
+/// updates of registers:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
 /// bitfield! {
-///     struct SomeFields(u32) {
-///         [ 7] pub x,
-///         [16] pub y,
+///     pub struct Example(u16) {
+///         [0..4]  pub a,
+///         [4..12] pub b,
 ///     }
 /// }
 ///
+/// let shadow = Example(0x1234);
+/// assert_eq!(shadow.merge(Example(0xabcd), 0x0ff0), Example(0x1bc4));
+/// ```
+///
+/// Similarly, a `matches_masked` method compares only the bits selected by a mask. See
+/// [`bitfield_pattern`].
+///
+/// The `replace` and `take` methods swap in a new or default value and return the old
+/// one, like [`core::mem::replace`] and [`core::mem::take`]:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
 /// bitfield! {
-///     struct OtherFields(u32) {
-///         [10] pub z,
-///         [12] pub q,
+///     pub struct Pending(u8) {
+///         [0] pub rx,
+///         [1] pub tx,
 ///     }
 /// }
 ///
-/// impl CommonFields for SomeFields {}
+/// let mut pending = Pending(0b11);
+/// let handled = pending.take();
+/// assert!(handled.rx() && handled.tx());
+/// assert_eq!(pending, Pending(0));
+/// assert_eq!(pending.replace(Pending(0b10)), Pending(0));
+/// ```
+///
+/// When every field is a single-bit flag, the struct can also be used as a set of flags
+/// with the `contains`, `insert`, `remove`, `union`, and `intersection` methods:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct InterruptEnable(u8) {
+///         [0] pub rx,
+///         [1] pub tx,
+///         [4] pub error,
+///     }
+/// }
+///
+/// let rx = InterruptEnable::default().with_rx(true);
+/// let tx = InterruptEnable::default().with_tx(true);
+/// let mut enabled = rx.union(tx);
+/// assert!(enabled.contains(tx));
+/// enabled.remove(tx);
+/// assert_eq!(enabled, rx);
+/// ```
+///
+/// Every bit that is part of a field can be set, cleared, or inverted at once with the
+/// `fill_all_fields`, `clear_all_fields`, and `invert_fields` methods. Bits that aren't
+/// part of any field, like reserved bits, are left unchanged. Similarly, the
+/// `any_field_set` and `all_fields_set` methods only check bits that are part of a field,
+/// while `is_zero` checks the whole value:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Status(u8) {
+///         [0] pub ready,
+///         [1] pub error,
+///     }
+/// }
+///
+/// let mut status = Status(0x80);
+/// assert!(!status.any_field_set() && !status.is_zero());
+/// status.set_ready(true);
+/// assert!(status.any_field_set() && !status.all_fields_set());
+/// ```
+///
+/// Other bitfields can be inserted at runtime offsets with `insert_at` and extracted
+/// with `extract_at`, to compose words from reusable parts:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Command(u32) {
+///         [24..32] pub opcode: u8,
+///     }
+/// }
+///
+/// bitfield! {
+///     pub struct Channel(u8) {
+///         [0..4] pub id,
+///         [7]    pub enable,
+///     }
+/// }
+///
+/// let channel = Channel::default().with_id(5);
+/// let command = Command::default().with_opcode(0x42).insert_at(8, channel);
+/// assert_eq!(command, Command(0x4200_0500));
+/// assert_eq!(command.extract_at::<Channel>(8), channel);
+/// ```
+///
+/// A field declared with the range `[..]` covers the whole underlying value. This
+/// gives the raw value a name, so that it appears alongside the other fields in
+/// [`Debug`] output and [`FieldLayout`]:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Status(u16) {
+///         [..]   pub raw: u16,
+///         [0..4] pub mode,
+///     }
+/// }
+///
+/// let status = Status(0x1234);
+/// assert_eq!(status.raw(), 0x1234);
+/// assert_eq!(
+///     format!("{status:?}"),
+///     "Status { <value>: 4660, raw: 4660, mode: 4 }",
+/// );
+/// ```
+///
+/// A field whose type is another bitfield is printed with that type's [`Debug`]
+/// implementation, so the alternate format (`{:#?}`) expands nested bitfields with one
+/// field per line at each level:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Clock(u8) {
+///         [0..4] pub divider,
+///         [7]    pub enable,
+///     }
+/// }
+///
+/// bitfield! {
+///     pub struct Control(u16) {
+///         [0..8] pub clock: Clock,
+///         [8]    pub reset,
+///     }
+/// }
+///
+/// let control = Control(0x0183);
+/// assert_eq!(
+///     format!("{control:#?}"),
+///     "\
+/// Control {
+///     <value>: 387,
+///     clock: Clock {
+///         <value>: 131,
+///         divider: 3,
+///         enable: true,
+///     },
+///     reset: true,
+/// }",
+/// );
+/// ```
+///
+/// Like Rust ranges, either end of a field's range can be left out. `[4..]` covers bit 4
+/// through the most significant bit of the underlying value, and `[..8]` covers bits 0
+/// through 7. This avoids hard-coding the width of the value for fields that take up
+/// all of the remaining bits:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Header(u32) {
+///         [..4] pub version,
+///         [4..] pub length: u32,
+///     }
+/// }
+///
+/// let header = Header(0x0000_1236);
+/// assert_eq!(header.version(), 6);
+/// assert_eq!(header.length(), 0x123);
+/// assert_eq!(Header::LENGTH_RANGE, 4..32);
+/// ```
+///
+/// When a struct models only part of a wider register, a `#[bitfield(bit_offset = N)]`
+/// attribute allows fields to be declared with the register's bit numbers, as they
+/// appear in a datasheet. Bit `N` of the register is bit 0 of the struct, and every
+/// other generated item, like [`FieldLayout`] and the range constants, uses the
+/// struct's bit numbers:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     #[bitfield(bit_offset = 32)]
+///     /// The upper half of a 64-bit control register
+///     pub struct ControlHigh(u32) {
+///         [32..40] pub divider: u8,
+///         [63]     pub enable,
+///     }
+/// }
+///
+/// let x = ControlHigh(0x8000_0004);
+/// assert_eq!(x.divider(), 4);
+/// assert!(x.enable());
+/// assert_eq!(ControlHigh::DIVIDER_RANGE, 0..8);
+/// ```
+///
+/// The struct derives `Default`, `Clone`, `Copy`, `PartialEq`, and `Eq`. A
+/// `#[bitfield(no_derive(...))]` attribute skips any of `Debug`, `Default`, `PartialEq`,
+/// and `Eq`, for registers that need their own, like a `Default` with the reset value or
+/// a `PartialEq` that ignores reserved bits. The [`Bitfield`] trait still requires them,
+/// so they must be implemented by hand. Options can be combined in one attribute, like
+/// `#[bitfield(bit_offset = 8, no_derive(Default))]`.
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     #[bitfield(no_derive(Default, PartialEq))]
+///     pub struct Status(u8) {
+///         [0]    pub ready,
+///         [4..8] pub code,
+///     }
+/// }
+///
+/// impl Default for Status {
+///     fn default() -> Self {
+///         Self(0x01)
+///     }
+/// }
+///
+/// // Bits 1 to 3 are reserved
+/// impl PartialEq for Status {
+///     fn eq(&self, other: &Self) -> bool {
+///         self.0 & 0xf1 == other.0 & 0xf1
+///     }
+/// }
+///
+/// assert!(Status::default().ready());
+/// assert_eq!(Status(0x31), Status(0x3f));
+/// assert!(!Status::default().is_zero());
+/// ```
+///
+/// Other derives can be added with a `#[bitfield(derive(...))]` attribute. Unlike a
+/// plain `#[derive(...)]`, it accepts the built-in derives without conflicting with them,
+/// which is convenient for definitions generated by other tools, and it brings back any
+/// that were skipped with `no_derive`:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// # use std::collections::HashSet;
+/// bitfield! {
+///     #[bitfield(derive(Clone, Copy, PartialEq, Hash, PartialOrd))]
+///     pub struct Priority(u8) {
+///         [0..3] pub level,
+///     }
+/// }
+///
+/// assert!(Priority(1) < Priority(2));
+/// assert_eq!(HashSet::from([Priority(1), Priority(1)]).len(), 1);
+/// ```
+///
+/// The underlying value is a private field of a tuple struct by default. It can be made
+/// public like any other tuple struct, as in `pub struct Status(pub u32)`, so that other
+/// crates can construct values directly. A `#[bitfield(storage = name)]` attribute makes
+/// it a named field instead:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     #[bitfield(storage = bits)]
+///     pub struct Status(pub u32) {
+///         [0]    pub ready,
+///         [4..8] pub code,
+///     }
+/// }
+///
+/// let status = Status { bits: 0x31 };
+/// assert!(status.ready());
+/// assert_eq!(status.code(), 3);
+/// assert_eq!(status.with_code(5).bits, 0x51);
+/// ```
+///
+/// Every struct has a `ZERO` constant with all bits clear. Unlike `Default::default()`,
+/// it can be used in statics and match patterns. A `#[bitfield(reset(...))]` attribute
+/// declares the value of some fields after a reset, as constant expressions that are
+/// converted with `as`. They are combined in a `RESET` constant, which `Default` returns
+/// instead of zero. This requires a primitive underlying type.
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     #[bitfield(reset(enable = true, divider = 4))]
+///     pub struct Clock(u16) {
+///         [0]     pub enable,
+///         [4..8]  pub divider: u8,
+///         [8..16] pub trim: u8,
+///     }
+/// }
+///
+/// static INIT: [(usize, Clock); 2] = [(0x10, Clock::RESET), (0x14, Clock::ZERO)];
+///
+/// assert_eq!(Clock::RESET, Clock(0x0041));
+/// assert_eq!(Clock::default(), Clock::RESET);
+/// assert_eq!(INIT[1].1, Clock(0));
+/// assert!(matches!(Clock(0), Clock::ZERO));
+/// ```
+///
+/// Registers with symmetric halves can declare their fields in `group` entries, which
+/// prefix the name of each field with the name of the group. Like the `include` entries
+/// described in [`bitfield_fields`], groups come before the other fields:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Link(u16) {
+///         group tx {
+///             [0]    pub enable,
+///             [1..8] pub len,
+///         },
+///         group rx {
+///             [8]     pub enable,
+///             [9..16] pub len,
+///         },
+///     }
+/// }
+///
+/// let link = Link::default().with_tx_len(3).with_rx_enable(true);
+/// assert_eq!(link, Link(0x0106));
+/// assert_eq!((link.tx_enable(), link.tx_len()), (false, 3));
+/// ```
+///
+/// Constraints on individual fields can be declared with a `where` clause after the
+/// field type, and constraints that span several fields with a `where` clause after the
+/// underlying type. The `try_new` constructor checks all of them, so that values from
+/// untrusted sources like EEPROM or the network can only be accepted if they are valid.
+/// See [`Validate`].
+///
+/// A single-bit field is a `bool` unless it has a type. With a type, like `[3] pub sel:
+/// u8`, it has integer accessors like any other range, which is convenient for fields
+/// that are combined arithmetically with other values:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Mux(u8) {
+///         [0..3] pub input: u8,
+///         [3]    pub bank: u8,
+///     }
+/// }
+///
+/// let mux = Mux::default().with_input(5).with_bank(1);
+/// assert_eq!(mux.bank() * 8 + mux.input(), 13);
+/// assert_eq!(Mux::BANK_RANGE, 3..4);
+/// ```
+///
+/// In the other direction, a range of one bit without a type, like `[0..1] pub flag`,
+/// is a `bool` just like `[0] pub flag`. So is any field of one bit with the type
+/// `bool`. This lets definitions generated from register descriptions use ranges for
+/// every field.
+///
+/// With the `A as B` syntax, the field must be wide enough for every value of `B` if
+/// that is known, as it is when `B` is another bitfield or a [`UInt`]. Otherwise
+/// converting a value to `A` could produce bits that don't fit, which would be dropped
+/// silently. This is checked at compile time:
+///
+/// ```compile_fail
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Inner(u8) {
+///         [0..3] pub mode,
+///         [5]    pub enable,
+///     }
+/// }
+///
+/// bitfield! {
+///     pub struct Outer(u16) {
+///         [0..4] pub inner: u8 as Inner,  // ERROR: field `inner` is too narrow
+///     }
+/// }
+/// ```
+///
+/// A malformed field is reported with an error naming the field and the expected
+/// syntax. For example, bit ranges are written like Rust ranges:
+///
+/// ```compile_fail
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Example(u32) {
+///         [0:4] pub a: u8,  // ERROR: field `a` has an unsupported bit range
+///     }
+/// }
+/// ```
+///
+/// Each field is separated from the next by exactly one comma:
+///
+/// ```compile_fail
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Example(u32) {
+///         [0..4] pub a: u8   // ERROR: expected `,` after the type of field `a`
+///         [4..8] pub b: u8,
+///     }
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Example(u32) {
+///         [0..4] pub a: u8,,  // ERROR: unexpected `,`
+///         [4..8] pub b: u8,
+///     }
+/// }
+/// ```
+///
+/// A type, if any, follows a colon after the name of the field:
+///
+/// ```compile_fail
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Example(u32) {
+///         [0..4] pub a u8,  // ERROR: expected `,` or `:` after field `a`, found `u8`
+///     }
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Example(u32) {
+///         [0..4] pub a:,  // ERROR: expected a type after `a:`
+///     }
+/// }
+/// ```
+///
+/// Every field starts with its bit range:
+///
+/// ```compile_fail
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Example(u32) {
+///         pub a: u8,  // ERROR: expected a field like `[0..4] pub name: u8`
+///     }
+/// }
+/// ```
+///
+/// Options in the `#[bitfield(...)]` attribute are checked the same way:
+///
+/// ```compile_fail
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     #[bitfield(packed)]  // ERROR: unsupported bitfield option `packed`
+///     pub struct Example(u32) {
+///         [0..4] pub a: u8,
+///     }
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     #[bitfield(no_derive(Hash))]  // ERROR: `Hash` is not a built-in derive
+///     pub struct Example(u32) {
+///         [0..4] pub a: u8,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! bitfield {
+    // Everything but the struct definition, for the derive macro
+    [
+        @impls $vis:vis struct $struct:ident($underlying_type:ty)
+        $( where $invariant:path )? {
+            $($body:tt)*
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @impls $vis struct $struct($underlying_type) $( where $invariant )? {
+                $($body)*
+            }
+        }
+        $crate::bitfield! { @debug $struct }
+    };
+
+    [
+        @debug $struct:ident
+    ] => {
+        $crate::bitfield! { @debug $struct [0] }
+    };
+
+    [
+        @debug $struct:ident [ $inner:tt ]
+    ] => {
+        impl ::core::fmt::Debug for $struct {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                let mut struct_out = f.debug_struct(stringify!($struct));
+                struct_out.field("<value>", &self.$inner);
+                self.fmt_fields(&mut struct_out);
+                struct_out.finish()
+            }
+        }
+    };
+
+    // The attributes are kept as plain tokens so that `#[bitfield(...)]` can be matched
+    // by `bitfield_without_debug`, which generates the `Debug` implementation when asked
+    [
+        $( #[ $($meta:tt)* ] )*
+        $vis:vis struct $struct:ident($inner_vis:vis $underlying_type:ty)
+        $( where $invariant:path )? {
+            $($body:tt)*
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            #[bitfield(debug)]
+            $( #[ $($meta)* ] )*
+            $vis struct $struct($inner_vis $underlying_type) $( where $invariant )? {
+                $($body)*
+            }
+        }
+    };
+}
+
+/// Same as the [`bitfield`] macro without a [`Debug`] implementation provided.
+///
+/// Since Debug is required by the [`Bitfield`] trait, the caller must provide their own
+/// implementation. This is the same as `#[bitfield(no_derive(Debug))]`.
+#[macro_export]
+macro_rules! bitfield_without_debug {
+    // The fields of a bitfield's group, for `svd_writer`
+    [ @svd_writer $header:tt { $($fields:tt)* } ] => {
+        $crate::svd_writer! { @fields $header { $($fields)* } }
+    };
+
+    // Field groups from `bitfield_fields` are expanded by calling the group's macro,
+    // which calls back into `@includes` with its fields appended to the accumulator.
+    // Prefixed groups are expanded the same way by `bitfield_fields` itself. The
+    // attributes are kept as plain tokens so that `#[bitfield(...)]` can be matched
+    // after the groups are expanded.
+    [
+        $( #[ $($meta:tt)* ] )*
+        $vis:vis struct $struct:ident($inner_vis:vis $underlying_type:ty)
+        $( where $invariant:path )? {
+            include $($body:tt)*
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @includes [
+                $( #[ $($meta)* ] )*
+                $vis struct $struct($inner_vis $underlying_type) $( where $invariant )?
+            ] [] { include $($body)* }
+        }
+    };
+
+    [
+        $( #[ $($meta:tt)* ] )*
+        $vis:vis struct $struct:ident($inner_vis:vis $underlying_type:ty)
+        $( where $invariant:path )? {
+            group $($body:tt)*
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @includes [
+                $( #[ $($meta)* ] )*
+                $vis struct $struct($inner_vis $underlying_type) $( where $invariant )?
+            ] [] { group $($body)* }
+        }
+    };
+
+    [
+        @includes $header:tt [ $($fields:tt)* ] {
+            include $group:ident $( , $($body:tt)* )?
+        }
+    ] => {
+        $group! { @include $header [ $($fields)* ] { $( $($body)* )? } }
+    };
+
+    [
+        @includes $header:tt [ $($fields:tt)* ] {
+            group $prefix:ident { $($group:tt)* } $( , $($body:tt)* )?
+        }
+    ] => {
+        $crate::bitfield_fields! {
+            @prefix $header [ $($fields)* ] $prefix { $($group)* } { $( $($body)* )? }
+        }
+    };
+
+    [
+        @includes [ $($header:tt)* ] [ $($fields:tt)* ] { $($body:tt)* }
+    ] => {
+        $crate::bitfield_without_debug! {
+            $($header)* {
+                $($fields)*
+                $($body)*
+            }
+        }
+    };
+
+    [
+        $( #[ $($meta:tt)* ] )*
+        $vis:vis struct $struct:ident($inner_vis:vis $underlying_type:ty)
+        $( where $invariant:path )? {
+            $($body:tt)*
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @options [] [0] [] ([] [Default] [PartialEq] [Eq] []) [] {}
+            $( #[ $($meta)* ] )*
+            $vis struct $struct($inner_vis $underlying_type) $( where $invariant )? {
+                $($body)*
+            }
+        }
+    };
+
+    // Options in `#[bitfield(...)]` attributes are applied one at a time, and the other
+    // attributes are kept for the struct. The options are the base bit number, the name
+    // of the field that holds the value (`0` for a tuple struct), the reset values of
+    // fields, and the derives: the built-in ones, which start out as `Debug` (set by
+    // `bitfield!`), `Default`, `PartialEq`, and `Eq`, followed by any others.
+    [
+        @options $offset:tt $inner:tt $reset:tt $derives:tt $attrs:tt {}
+        #[bitfield( $($options:tt)* )]
+        $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @options $offset $inner $reset $derives $attrs { $($options)* } $($rest)*
+        }
+    };
+
+    [
+        @options $offset:tt $inner:tt $reset:tt $derives:tt [ $($attrs:tt)* ] {}
+        #[ $($meta:tt)* ]
+        $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @options $offset $inner $reset $derives [ $($attrs)* #[ $($meta)* ] ] {}
+            $($rest)*
+        }
+    };
+
+    [
+        @options $offset:tt $inner:tt $reset:tt $derives:tt [ $($attrs:tt)* ] {}
+        $vis:vis struct $struct:ident($inner_vis:vis $underlying_type:ty)
+        $( where $invariant:path )? {
+            $($body:tt)*
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @ranges $offset ($underlying_type) [
+                $derives $inner $reset
+                $($attrs)*
+                $vis struct $struct($inner_vis $underlying_type) $( where $invariant )?
+            ] [] { $($body)* }
+        }
+    };
+
+    [
+        @options [] $inner:tt $reset:tt $derives:tt $attrs:tt {
+            bit_offset = $offset:literal $( , $($options:tt)* )?
+        }
+        $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @options [ $offset ] $inner $reset $derives $attrs {
+                $( $($options)* )?
+            }
+            $($rest)*
+        }
+    };
+
+    [
+        @options $offset:tt [0] $reset:tt $derives:tt $attrs:tt {
+            storage = $name:ident $( , $($options:tt)* )?
+        }
+        $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @options $offset [ $name ] $reset $derives $attrs {
+                $( $($options)* )?
+            }
+            $($rest)*
+        }
+    };
+
+    [
+        @options $offset:tt $inner:tt [] $derives:tt $attrs:tt {
+            reset( $( $field:ident = $value:expr ),* $(,)? ) $( , $($options:tt)* )?
+        }
+        $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @options $offset $inner [ $( $field = $value ),* ] $derives $attrs {
+                $( $($options)* )?
+            }
+            $($rest)*
+        }
+    };
+
+    [
+        @options $offset:tt $inner:tt $reset:tt
+        ( [] $default:tt $partial_eq:tt $eq:tt $extra:tt ) $attrs:tt {
+            debug $( , $($options:tt)* )?
+        }
+        $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @options $offset $inner $reset
+            ( [Debug] $default $partial_eq $eq $extra ) $attrs {
+                $( $($options)* )?
+            }
+            $($rest)*
+        }
+    };
+
+    [
+        @options $offset:tt $inner:tt $reset:tt $derives:tt $attrs:tt {
+            no_derive( $($names:ident),* $(,)? ) $( , $($options:tt)* )?
+        }
+        $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @no_derive [ $($names)* ] $offset $inner $reset $derives $attrs {
+                $( $($options)* )?
+            }
+            $($rest)*
+        }
+    };
+
+    [
+        @options $offset:tt $inner:tt $reset:tt $derives:tt $attrs:tt {
+            derive( $($names:tt)* ) $( , $($options:tt)* )?
+        }
+        $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @derive [ $($names)* ] $offset $inner $reset $derives $attrs {
+                $( $($options)* )?
+            }
+            $($rest)*
+        }
+    };
+
+    [
+        @options $offset:tt $inner:tt $reset:tt $derives:tt $attrs:tt {
+            $option:tt $($options:tt)*
+        }
+        $($rest:tt)*
+    ] => {
+        ::core::compile_error!(::core::concat!(
+            "unsupported bitfield option `",
+            ::core::stringify!($option $($options)*),
+            "`; ",
+            "expected `bit_offset = N`, `storage = name`, `reset(...)`, `derive(...)`, ",
+            "or `no_derive(...)`",
+        ));
+    };
+
+    [
+        @no_derive [ Debug $($names:ident)* ] $offset:tt $inner:tt $reset:tt
+        ( $debug:tt $default:tt $partial_eq:tt $eq:tt $extra:tt ) $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @no_derive [ $($names)* ] $offset $inner $reset
+            ( [] $default $partial_eq $eq $extra ) $($rest)*
+        }
+    };
+
+    [
+        @no_derive [ Default $($names:ident)* ] $offset:tt $inner:tt $reset:tt
+        ( $debug:tt $default:tt $partial_eq:tt $eq:tt $extra:tt ) $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @no_derive [ $($names)* ] $offset $inner $reset
+            ( $debug [] $partial_eq $eq $extra ) $($rest)*
+        }
+    };
+
+    [
+        @no_derive [ PartialEq $($names:ident)* ] $offset:tt $inner:tt $reset:tt
+        ( $debug:tt $default:tt $partial_eq:tt $eq:tt $extra:tt ) $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @no_derive [ $($names)* ] $offset $inner $reset
+            ( $debug $default [] $eq $extra ) $($rest)*
+        }
+    };
+
+    [
+        @no_derive [ Eq $($names:ident)* ] $offset:tt $inner:tt $reset:tt
+        ( $debug:tt $default:tt $partial_eq:tt $eq:tt $extra:tt ) $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @no_derive [ $($names)* ] $offset $inner $reset
+            ( $debug $default $partial_eq [] $extra ) $($rest)*
+        }
+    };
+
+    // Built-in derives are turned back on if they were skipped, rather than conflicting
+    // with the ones that are already there
+    [
+        @derive [ Debug $( , $($names:tt)* )? ] $offset:tt $inner:tt $reset:tt
+        ( $debug:tt $default:tt $partial_eq:tt $eq:tt $extra:tt ) $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @derive [ $( $($names)* )? ] $offset $inner $reset
+            ( [Debug] $default $partial_eq $eq $extra )
+            $($rest)*
+        }
+    };
+
+    [
+        @derive [ Default $( , $($names:tt)* )? ] $offset:tt $inner:tt $reset:tt
+        ( $debug:tt $default:tt $partial_eq:tt $eq:tt $extra:tt ) $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @derive [ $( $($names)* )? ] $offset $inner $reset
+            ( $debug [Default] $partial_eq $eq $extra )
+            $($rest)*
+        }
+    };
+
+    [
+        @derive [ PartialEq $( , $($names:tt)* )? ] $offset:tt $inner:tt $reset:tt
+        ( $debug:tt $default:tt $partial_eq:tt $eq:tt $extra:tt ) $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @derive [ $( $($names)* )? ] $offset $inner $reset
+            ( $debug $default [PartialEq] $eq $extra )
+            $($rest)*
+        }
+    };
+
+    [
+        @derive [ Eq $( , $($names:tt)* )? ] $offset:tt $inner:tt $reset:tt
+        ( $debug:tt $default:tt $partial_eq:tt $eq:tt $extra:tt ) $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @derive [ $( $($names)* )? ] $offset $inner $reset
+            ( $debug $default $partial_eq [Eq] $extra )
+            $($rest)*
+        }
+    };
+
+    [
+        @derive [ Clone $( , $($names:tt)* )? ] $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! { @derive [ $( $($names)* )? ] $($rest)* }
+    };
+
+    [
+        @derive [ Copy $( , $($names:tt)* )? ] $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! { @derive [ $( $($names)* )? ] $($rest)* }
+    };
+
+    [
+        @derive [ $name:path $( , $($names:tt)* )? ] $offset:tt $inner:tt $reset:tt
+        ( $debug:tt $default:tt $partial_eq:tt $eq:tt [ $($extra:path),* ] ) $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! {
+            @derive [ $( $($names)* )? ] $offset $inner $reset
+            ( $debug $default $partial_eq $eq [ $($extra,)* $name ] )
+            $($rest)*
+        }
+    };
+
+    [
+        @derive [] $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! { @options $($rest)* }
+    };
+
+    [
+        @no_derive [] $($rest:tt)*
+    ] => {
+        $crate::bitfield_without_debug! { @options $($rest)* }
+    };
+
+    [
+        @no_derive [ $name:ident $($names:ident)* ] $($rest:tt)*
+    ] => {
+        ::core::compile_error!(::core::concat!(
+            "`", ::core::stringify!($name), "` is not a built-in derive; ",
+            "expected `Debug`, `Default`, `PartialEq`, or `Eq`",
+        ));
+    };
+
+    // Ranges are rewritten one field at a time so that the accessors only have to
+    // handle closed ranges. Single bits with a type become one-bit integer ranges, so
+    // only untyped single bits are flags. Missing ends of open ranges are filled in with
+    // the bounds of the underlying type. The whole-value range doesn't depend on the
+    // base bit number, but other positions relative to a base bit number get a
+    // subtraction, which the accessors evaluate along with the rest of the bit math.
+    [
+        @ranges [ $( $offset:literal )? ] ($storage:ty) $header:tt [ $($fields:tt)* ] {
+            $( #[$meta:meta] )* [ $bit:literal ] $vis:vis $field:ident
+            : $underlying_type:ty $( as $interface_type:ty )? $( where $check:expr )?
+            $( , $($body:tt)* )?
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @ranges [ $( $offset )? ] ($storage) $header [
+                $($fields)*
+                $( #[$meta] )* [ $bit ..= $bit $( - $offset )? ] $vis $field
+                : $underlying_type $( as $interface_type )? $( where $check )?,
+            ] { $( $($body)* )? }
+        }
+    };
+
+    [
+        @ranges [ $( $offset:literal )? ] ($storage:ty) $header:tt [ $($fields:tt)* ] {
+            $( #[$meta:meta] )* [ $bit:literal ] $vis:vis $field:ident
+            $( , $($body:tt)* )?
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @ranges [ $( $offset )? ] ($storage) $header [
+                $($fields)*
+                $( #[$meta] )* [ $bit $( - $offset )? ] $vis $field,
+            ] { $( $($body)* )? }
+        }
+    };
+
+    [
+        @ranges [ $( $offset:literal )? ] ($storage:ty) $header:tt [ $($fields:tt)* ] {
+            $( #[$meta:meta] )* [ $lsb:literal .. $msb:literal ] $vis:vis $field:ident
+            $(
+                : $underlying_type:ty $( as $interface_type:ty )?
+                $( where $check:expr )?
+            )?
+            $( , $($body:tt)* )?
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @ranges [ $( $offset )? ] ($storage) $header [
+                $($fields)*
+                $( #[$meta] )* [ $lsb .. $msb $( - $offset )? ] $vis $field
+                $( : $underlying_type $( as $interface_type )? $( where $check )? )?,
+            ] { $( $($body)* )? }
+        }
+    };
+
+    [
+        @ranges [ $( $offset:literal )? ] ($storage:ty) $header:tt [ $($fields:tt)* ] {
+            $( #[$meta:meta] )* [ $lsb:literal ..= $msb:literal ] $vis:vis $field:ident
+            $(
+                : $underlying_type:ty $( as $interface_type:ty )?
+                $( where $check:expr )?
+            )?
+            $( , $($body:tt)* )?
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @ranges [ $( $offset )? ] ($storage) $header [
+                $($fields)*
+                $( #[$meta] )* [ $lsb ..= $msb $( - $offset )? ] $vis $field
+                $( : $underlying_type $( as $interface_type )? $( where $check )? )?,
+            ] { $( $($body)* )? }
+        }
+    };
+
+    [
+        @ranges [ $( $offset:literal )? ] ($storage:ty) $header:tt [ $($fields:tt)* ] {
+            $( #[$meta:meta] )* [ $lsb:literal .. ] $vis:vis $field:ident
+            $(
+                : $underlying_type:ty $( as $interface_type:ty )?
+                $( where $check:expr )?
+            )?
+            $( , $($body:tt)* )?
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @ranges [ $( $offset )? ] ($storage) $header [
+                $($fields)*
+                $( #[$meta] )* [
+                    $lsb .. (<$storage as $crate::FieldStorage>::MAX_BITS $( + $offset )?)
+                    $( - $offset )?
+                ] $vis $field
+                $( : $underlying_type $( as $interface_type )? $( where $check )? )?,
+            ] { $( $($body)* )? }
+        }
+    };
+
+    [
+        @ranges [  ] ($storage:ty) $header:tt [ $($fields:tt)* ] {
+            $( #[$meta:meta] )* [ .. $msb:literal ] $vis:vis $field:ident
+            $(
+                : $underlying_type:ty $( as $interface_type:ty )?
+                $( where $check:expr )?
+            )?
+            $( , $($body:tt)* )?
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @ranges [  ] ($storage) $header [
+                $($fields)*
+                $( #[$meta] )* [ 0 .. $msb ] $vis $field
+                $( : $underlying_type $( as $interface_type )? $( where $check )? )?,
+            ] { $( $($body)* )? }
+        }
+    };
+
+    [
+        @ranges [ $offset:literal ] ($storage:ty) $header:tt [ $($fields:tt)* ] {
+            $( #[$meta:meta] )* [ .. $msb:literal ] $vis:vis $field:ident
+            $(
+                : $underlying_type:ty $( as $interface_type:ty )?
+                $( where $check:expr )?
+            )?
+            $( , $($body:tt)* )?
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @ranges [ $offset ] ($storage) $header [
+                $($fields)*
+                $( #[$meta] )* [ $offset .. $msb - $offset ] $vis $field
+                $( : $underlying_type $( as $interface_type )? $( where $check )? )?,
+            ] { $( $($body)* )? }
+        }
+    };
+
+    [
+        @ranges [  ] ($storage:ty) $header:tt [ $($fields:tt)* ] {
+            $( #[$meta:meta] )* [ ..= $msb:literal ] $vis:vis $field:ident
+            $(
+                : $underlying_type:ty $( as $interface_type:ty )?
+                $( where $check:expr )?
+            )?
+            $( , $($body:tt)* )?
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @ranges [  ] ($storage) $header [
+                $($fields)*
+                $( #[$meta] )* [ 0 ..= $msb ] $vis $field
+                $( : $underlying_type $( as $interface_type )? $( where $check )? )?,
+            ] { $( $($body)* )? }
+        }
+    };
+
+    [
+        @ranges [ $offset:literal ] ($storage:ty) $header:tt [ $($fields:tt)* ] {
+            $( #[$meta:meta] )* [ ..= $msb:literal ] $vis:vis $field:ident
+            $(
+                : $underlying_type:ty $( as $interface_type:ty )?
+                $( where $check:expr )?
+            )?
+            $( , $($body:tt)* )?
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @ranges [ $offset ] ($storage) $header [
+                $($fields)*
+                $( #[$meta] )* [ $offset ..= $msb - $offset ] $vis $field
+                $( : $underlying_type $( as $interface_type )? $( where $check )? )?,
+            ] { $( $($body)* )? }
+        }
+    };
+
+    [
+        @ranges [ $( $offset:literal )? ] ($storage:ty) $header:tt [ $($fields:tt)* ] {
+            $( #[$meta:meta] )* [ .. ] $vis:vis $field:ident
+            $(
+                : $underlying_type:ty $( as $interface_type:ty )?
+                $( where $check:expr )?
+            )?
+            $( , $($body:tt)* )?
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @ranges [ $( $offset )? ] ($storage) $header [
+                $($fields)*
+                $( #[$meta] )* [ 0 .. (<$storage as $crate::FieldStorage>::MAX_BITS) ]
+                $vis $field
+                $( : $underlying_type $( as $interface_type )? $( where $check )? )?,
+            ] { $( $($body)* )? }
+        }
+    };
+
+    // Anything else, including malformed fields, is left as-is to be reported later
+    [
+        @ranges [ $( $offset:literal )? ] ($storage:ty)
+        [ $($header:tt)* ] [ $($fields:tt)* ]
+        { $($body:tt)* }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @define $($header)* {
+                $($fields)*
+                $($body)*
+            }
+        }
+    };
+
+    // With reset values, `Default` returns them instead of being derived
+    [
+        @define
+        ( $debug:tt [ Default ] $partial_eq:tt $eq:tt $extra:tt )
+        $inner:tt [ $($reset:tt)+ ]
+        $( #[$meta:meta] )*
+        $vis:vis struct $struct:ident $($rest:tt)*
+    ] => {
+        impl ::core::default::Default for $struct {
+            #[inline(always)]
+            fn default() -> Self {
+                Self::RESET
+            }
+        }
+
+        $crate::bitfield_without_debug! {
+            @define ( $debug [] $partial_eq $eq $extra ) $inner [ $($reset)+ ]
+            $( #[$meta] )*
+            $vis struct $struct $($rest)*
+        }
+    };
+
+    [
+        @define
+        (
+            [ $( $debug:ident )? ] [ $( $default:ident )? ]
+            [ $( $partial_eq:ident )? ] [ $( $eq:ident )? ] [ $( $extra:path ),* ]
+        )
+        $inner:tt $reset:tt
+        $( #[$meta:meta] )*
+        $vis:vis struct $struct:ident($inner_vis:vis $underlying_type:ty)
+        $( where $invariant:path )? {
+            $($body:tt)*
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @struct $inner [
+                $( #[$meta] )*
+                #[doc = ""]
+                #[doc = $crate::bitfield_accessors!(@layout_doc $($body)*)]
+                #[repr(transparent)]
+                #[derive($( $default, )? Clone, Copy $( , $partial_eq )? $( , $eq )?)]
+                $( #[derive($extra)] )*
+            ] $vis $struct($inner_vis $underlying_type)
+        }
+
+        impl $crate::FromMut<$underlying_type> for $struct {
+            #[inline(always)]
+            fn from_mut(value: &mut $underlying_type) -> &mut Self {
+                // SAFETY: The struct is `#[repr(transparent)]` around the underlying
+                // type, and the result borrows the value for as long as the argument
+                unsafe { &mut *::core::ptr::from_mut(value).cast::<Self>() }
+            }
+        }
+
+        $( $crate::bitfield_without_debug! { @debug $debug $struct $inner } )?
+
+        $crate::bitfield_without_debug! {
+            @reset $vis $struct $inner ($underlying_type) $reset
+        }
+
+        $crate::bitfield_without_debug! {
+            @impls $inner $vis struct $struct($underlying_type) $( where $invariant )? {
+                $($body)*
+            }
+        }
+
+        $crate::bitfield_fields! { @inherit $struct { $($body)* } }
+    };
+
+    [
+        @struct [0] [ $($attrs:tt)* ]
+        $vis:vis $struct:ident($inner_vis:vis $underlying_type:ty)
+    ] => {
+        $($attrs)*
+        $vis struct $struct($inner_vis $underlying_type);
+    };
+
+    [
+        @struct [ $name:ident ] [ $($attrs:tt)* ]
+        $vis:vis $struct:ident($inner_vis:vis $underlying_type:ty)
+    ] => {
+        $($attrs)*
+        $vis struct $struct {
+            $inner_vis $name: $underlying_type,
+        }
+    };
+
+    [
+        @debug Debug $struct:ident $inner:tt
+    ] => {
+        $crate::bitfield! { @debug $struct $inner }
+    };
+
+    [ @reset $vis:vis $struct:ident $inner:tt ($underlying_type:ty) [] ] => {};
+
+    // Each value is checked against the width of its field when the constant is
+    // evaluated, so a value that doesn't fit is a compile-time error
+    [
+        @reset $vis:vis $struct:ident [ $inner:tt ] ($underlying_type:ty)
+        [ $( $field:ident = $value:expr ),* ]
+    ] => {
+        $crate::paste! {
+            impl $struct {
+                /// The value at reset, with the fields given in `#[bitfield(reset(...))]`
+                /// set and every other bit clear
+                #[allow(dead_code, clippy::cast_possible_truncation)]
+                $vis const RESET: Self = {
+                    let mut value: u128 = 0;
+                    $(
+                        let range = Self::[< $field:upper _RANGE >];
+                        let field = ($value) as u128;
+                        ::core::assert!(
+                            field >> (range.end - range.start - 1) >> 1 == 0,
+                            ::core::concat!(
+                                "reset value does not fit in field `",
+                                ::core::stringify!($field), "`",
+                            ),
+                        );
+                        value |= field << range.start;
+                    )*
+                    Self { $inner: value as $underlying_type }
+                };
+            }
+        }
+    };
+
+    // The derive macro only supports tuple structs
+    [
+        @impls $vis:vis struct $struct:ident($underlying_type:ty)
+        $( where $invariant:path )? {
+            $($body:tt)*
+        }
+    ] => {
+        $crate::bitfield_without_debug! {
+            @impls [0] $vis struct $struct($underlying_type) $( where $invariant )? {
+                $($body)*
+            }
+        }
+    };
+
+    // Everything but the struct definition, for the derive macro
+    [
+        @impls [ $inner:tt ] $vis:vis struct $struct:ident($underlying_type:ty)
+        $( where $invariant:path )? {
+            $($body:tt)*
+        }
+    ] => {
+        impl $struct {
+            $crate::bitfield_accessors! {
+                @inherent [ $inner ] ($underlying_type) $($body)*
+            }
+
+            /// The value with every bit clear. Unlike `Default::default()`, this can be
+            /// used in constants and patterns.
+            #[allow(dead_code)]
+            $vis const ZERO: Self = Self { $inner: <$underlying_type>::MIN };
+
+            /// The name of every field, in the order they were declared
+            #[allow(dead_code)]
+            $vis const FIELD_NAMES: &'static [&'static str] =
+                $crate::bitfield_accessors!(@field_names $($body)*);
+
+            /// Parse the underlying value from a string of digits in the given base. See
+            /// [`u32::from_str_radix`].
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the string contains invalid digits or the value does
+            /// not fit in the underlying type.
+            #[allow(dead_code)]
+            $vis fn from_str_radix(
+                s: &str,
+                radix: u32,
+            ) -> ::core::result::Result<
+                Self,
+                <$underlying_type as $crate::FromStrRadix>::Err,
+            > {
+                <$underlying_type as $crate::FromStrRadix>::from_str_radix(s, radix)
+                    .map(Self::from)
+            }
+
+            /// Construct a value from a wider integer, discarding the bits that don't fit
+            /// in the underlying type. See `TruncateInto`.
+            #[allow(dead_code)]
+            #[must_use]
+            $vis fn from_value_truncated<V>(value: V) -> Self
+            where
+                V: $crate::TruncateInto<$underlying_type>,
+            {
+                Self::from(value.truncate_into())
+            }
+
+            /// Construct a value from its underlying representation, but only if it
+            /// satisfies the constraints on its fields and the invariant function, if
+            /// there is one. See `Validate`.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error naming the first field constraint or invariant that the
+            /// value does not satisfy.
+            #[allow(dead_code)]
+            $vis fn try_new(
+                value: $underlying_type,
+            ) -> ::core::result::Result<Self, $crate::ValidationError> {
+                let result = Self::from(value);
+                $crate::Validate::validate(&result)?;
+                Ok(result)
+            }
+
+            /// Iterate over the name and value of each field, in the order they were
+            /// declared.
+            #[allow(dead_code)]
+            $vis fn fields(&self) -> $crate::FieldValues {
+                $crate::FieldValues::new(*self)
+            }
+
+            /// Iterate over the names of the single-bit fields that are set, in the order
+            /// they were declared.
+            #[allow(dead_code)]
+            $vis fn iter_set_flags(&self) -> $crate::SetFlags {
+                $crate::SetFlags::new(*self)
+            }
+
+            /// Iterate over the bits of the underlying value, starting from the least
+            /// significant bit.
+            #[allow(dead_code)]
+            $vis fn iter_bits(&self) -> $crate::Bits {
+                $crate::Bits::new(*self)
+            }
+
+            /// Iterate over every combination of field values, with the bits that aren't
+            /// part of any field clear. See `AllValues`.
+            #[allow(dead_code)]
+            $vis fn iter_all() -> $crate::AllValues<Self, $underlying_type> {
+                $crate::AllValues::new()
+            }
+
+            /// A packet diagram of the fields and their values, for display. See
+            /// `LayoutDiagram`.
+            #[allow(dead_code)]
+            $vis fn layout_diagram(
+                &self,
+            ) -> $crate::LayoutDiagram<Self, $underlying_type> {
+                $crate::LayoutDiagram::new(*self)
+            }
+
+            /// Write a table of the name, bit range, and value of each field. See
+            /// `write_field_table`.
+            ///
+            /// # Errors
+            ///
+            /// Returns an error if the writer fails.
+            #[allow(dead_code)]
+            $vis fn format_table<W>(&self, w: &mut W) -> ::core::fmt::Result
+            where
+                W: ::core::fmt::Write + ?Sized,
+            {
+                $crate::write_field_table(*self, w)
+            }
+
+            /// Take the bits of `other` where `mask` is set, and the bits of `self`
+            /// elsewhere.
+            #[must_use]
+            #[allow(dead_code)]
+            $vis fn merge(self, other: Self, mask: $underlying_type) -> Self {
+                let mask = $crate::widen_to_packed::<Self, _, _>(mask);
+                let packed = $crate::packed_value(self);
+                let other = $crate::packed_value(other);
+                $crate::from_packed_value(packed & !mask | other & mask)
+            }
+
+            /// Replace the value with `new`, returning the old value. This is like
+            /// [`core::mem::replace`], for updating a shadow copy of a register.
+            #[allow(dead_code)]
+            $vis fn replace(&mut self, new: Self) -> Self {
+                ::core::mem::replace(self, new)
+            }
+
+            /// Reset the value to its default, returning the old value. This is like
+            /// [`core::mem::take`], for collecting flags like pending interrupts.
+            #[allow(dead_code)]
+            $vis fn take(&mut self) -> Self {
+                ::core::mem::take(self)
+            }
+
+            /// True if the bits of `self` and `value` are equal where `mask` is set. The
+            /// mask and value can be built from field values with `bitfield_pattern!`.
+            #[must_use]
+            #[allow(dead_code)]
+            $vis fn matches_masked(&self, mask: Self, value: Self) -> bool {
+                let mask = $crate::packed_value(mask);
+                $crate::packed_value(*self) & mask == $crate::packed_value(value) & mask
+            }
+
+            /// Replace the bits starting at `offset` with all the bits of another
+            /// bitfield.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `sub` doesn't fit at the given offset.
+            #[must_use]
+            #[allow(dead_code)]
+            $vis fn insert_at<S>(self, offset: u8, sub: S) -> Self
+            where
+                S: $crate::Bitfield<S::Storage> + $crate::FieldStorage,
+                S::Storage: $crate::FieldStorage
+                    + ::core::convert::Into<
+                        <S::Storage as $crate::FieldStorage>::Storage
+                    >,
+                <S::Storage as $crate::FieldStorage>::Storage: $crate::PackedBits,
+            {
+                $crate::insert_bitfield(self, offset, sub)
+            }
+
+            /// Extract another bitfield from the bits starting at `offset`.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `S` doesn't fit at the given offset.
+            #[must_use]
+            #[allow(dead_code)]
+            $vis fn extract_at<S>(self, offset: u8) -> S
+            where
+                S: $crate::Bitfield<S::Storage> + $crate::FieldStorage,
+                S::Storage: $crate::FieldStorage,
+                <S::Storage as $crate::FieldStorage>::Storage:
+                    $crate::PackedBits + $crate::TruncateInto<S::Storage>,
+            {
+                $crate::extract_bitfield(self, offset)
+            }
+
+            /// Set every bit that is part of a field, leaving other bits unchanged.
+            #[allow(dead_code)]
+            $vis fn fill_all_fields(&mut self) {
+                let mask = $crate::field_mask::<Self, _>();
+                *self = $crate::from_packed_value($crate::packed_value(*self) | mask);
+            }
+
+            /// Clear every bit that is part of a field, leaving other bits unchanged.
+            #[allow(dead_code)]
+            $vis fn clear_all_fields(&mut self) {
+                let mask = $crate::field_mask::<Self, _>();
+                *self = $crate::from_packed_value($crate::packed_value(*self) & !mask);
+            }
+
+            /// Invert every bit that is part of a field, leaving other bits unchanged.
+            #[allow(dead_code)]
+            $vis fn invert_fields(&mut self) {
+                let mask = $crate::field_mask::<Self, _>();
+                *self = $crate::from_packed_value($crate::packed_value(*self) ^ mask);
+            }
+
+            /// True if every bit of the underlying value is clear, including bits that
+            /// aren't part of any field.
+            #[must_use]
+            #[allow(dead_code)]
+            $vis fn is_zero(&self) -> bool {
+                $crate::packed_value(*self) == $crate::packed_value(Self::from(
+                    <$underlying_type as ::core::default::Default>::default(),
+                ))
+            }
+
+            /// True if any bit that is part of a field is set.
+            #[must_use]
+            #[allow(dead_code)]
+            $vis fn any_field_set(&self) -> bool {
+                let mask = $crate::field_mask::<Self, _>();
+                let zero = <$underlying_type as ::core::default::Default>::default();
+                let empty = $crate::packed_value(Self::from(zero));
+                $crate::packed_value(*self) & mask != empty
+            }
+
+            /// True if every bit that is part of a field is set.
+            #[must_use]
+            #[allow(dead_code)]
+            $vis fn all_fields_set(&self) -> bool {
+                let mask = $crate::field_mask::<Self, _>();
+                $crate::packed_value(*self) & mask == mask
+            }
+
+            /// Construct a value from its bits, starting from the least significant bit.
+            /// Missing bits are clear and extra bits are ignored.
+            #[allow(dead_code)]
+            $vis fn from_bits<I>(bits: I) -> Self
+            where
+                I: ::core::iter::IntoIterator<Item = bool>,
+            {
+                $crate::bitfield_from_bits(bits)
+            }
+        }
+
+        impl $crate::Bitfield<$underlying_type> for $struct {}
+
+        impl $crate::FieldLayout for $struct {
+            const LAYOUT: &'static [$crate::FieldInfo] =
+                $crate::bitfield_accessors!(@layout $($body)*);
+        }
+
+        impl $crate::FieldStorage for $struct {
+            type Storage = $underlying_type;
+            const MIN_BITS: u8 = $crate::bitfield_accessors!(@max_msb $($body)*);
+            const MAX_BITS: u8 = <$underlying_type as $crate::FieldStorage>::MAX_BITS;
+        }
+
+        const _: () = {
+            ::core::assert!(
+                <$struct as $crate::FieldStorage>::MIN_BITS
+                    <= <$struct as $crate::FieldStorage>::MAX_BITS,
+                "fields extend past the end of the underlying type",
+            );
+            $crate::bitfield_accessors! { @check_widths $($body)* }
+        };
+
+        impl ::core::convert::From<$underlying_type> for $struct {
+            #[inline(always)]
+            fn from(val: $underlying_type) -> Self { Self { $inner: val } }
+        }
+
+        impl ::core::convert::From<$struct> for $underlying_type {
+            #[inline(always)]
+            fn from(val: $struct) -> Self { val.$inner }
+        }
+
+        impl $crate::ParseFields for $struct {
+            fn set_field_from_str(
+                &mut self,
+                name: &str,
+                value: &str,
+            ) -> ::core::result::Result<(), $crate::FieldParseError> {
+                self.parse_field(name, value)
+            }
+        }
+
+        impl $crate::Validate for $struct {
+            fn validate(&self) -> ::core::result::Result<(), $crate::ValidationError> {
+                self.validate_fields()?;
+                $(
+                    if !$invariant(*self) {
+                        let name = stringify!($invariant);
+                        return Err($crate::ValidationError::Invariant(name));
+                    }
+                )?
+                Ok(())
+            }
+        }
+
+        impl $crate::WriteFields for $struct {
+            fn write_fields<W>(&self, w: &mut W) -> ::core::fmt::Result
+            where
+                W: ::core::fmt::Write + ?Sized,
+            {
+                self.write_field_pairs(w)
+            }
+        }
+
+        impl ::core::iter::FromIterator<bool> for $struct {
+            fn from_iter<I>(bits: I) -> Self
+            where
+                I: ::core::iter::IntoIterator<Item = bool>,
+            {
+                $crate::bitfield_from_bits(bits)
+            }
+        }
+
+        impl $crate::svd::FieldReader for $struct {
+            type Bits = $underlying_type;
+
+            #[inline(always)]
+            fn bits(self) -> $underlying_type {
+                self.$inner
+            }
+        }
+
+        impl $crate::DebugSummary for $struct {
+            fn fmt_summary(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                let mut struct_out = f.debug_struct(stringify!($struct));
+                self.fmt_changed_fields(Self::default(), &mut struct_out);
+                struct_out.finish()
+            }
+        }
+
+        impl ::core::str::FromStr for $struct {
+            type Err = <$underlying_type as $crate::FromStrRadix>::Err;
+
+            fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                $crate::parse_prefixed::<$underlying_type>(s).map(Self::from)
+            }
+        }
+
+        $crate::bitfield_rand! { $struct($underlying_type) }
+        $crate::bitfield_rkyv! { $struct($underlying_type) }
+
+        $crate::bitfield_without_debug! { @flag_set $vis $struct { $($body)* } }
+    };
+
+    // Set operations, only for bitfields where every field is a single-bit flag
+    [
+        @flag_set $vis:vis $struct:ident {
+            $(
+                $( #[$meta:meta] )* [ $bit:literal $( - $offset:literal )? ]
+                $field_vis:vis $field:ident
+            ),+
+            $(,)?
+        }
+    ] => {
+        impl $struct {
+            /// True if every bit that is set in `other` is also set in `self`
+            #[must_use]
+            #[allow(dead_code)]
+            $vis fn contains(&self, other: Self) -> bool {
+                let other = $crate::packed_value(other);
+                $crate::packed_value(*self) & other == other
+            }
+
+            /// Set the bits that are set in `other`
+            #[allow(dead_code)]
+            $vis fn insert(&mut self, other: Self) {
+                *self = self.union(other);
+            }
+
+            /// Clear the bits that are set in `other`
+            #[allow(dead_code)]
+            $vis fn remove(&mut self, other: Self) {
+                let packed = $crate::packed_value(*self);
+                *self = $crate::from_packed_value(packed & !$crate::packed_value(other));
+            }
+
+            /// The bits that are set in either `self` or `other`
+            #[must_use]
+            #[allow(dead_code)]
+            $vis fn union(self, other: Self) -> Self {
+                let packed = $crate::packed_value(self);
+                $crate::from_packed_value(packed | $crate::packed_value(other))
+            }
+
+            /// The bits that are set in both `self` and `other`
+            #[must_use]
+            #[allow(dead_code)]
+            $vis fn intersection(self, other: Self) -> Self {
+                let packed = $crate::packed_value(self);
+                $crate::from_packed_value(packed & $crate::packed_value(other))
+            }
+        }
+    };
+
+    [ @flag_set $( $rest:tt )* ] => {};
+}
+
+/// Implement `Distribution` for a bitfield with the `rand` feature, or nothing without
+/// it. Helper macro for [`bitfield`].
+#[cfg(feature = "rand")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! bitfield_rand {
+    [ $struct:ident($underlying_type:ty) ] => {
+        impl $crate::rand::distributions::Distribution<$struct>
+            for $crate::rand::distributions::Standard
+        {
+            fn sample<R>(&self, rng: &mut R) -> $struct
+            where
+                R: $crate::rand::Rng + ?::core::marker::Sized,
+            {
+                $crate::random_value::<$struct, $underlying_type, R>(rng)
+            }
+        }
+    };
+}
+
+/// Implement `Distribution` for a bitfield with the `rand` feature, or nothing without
+/// it. Helper macro for [`bitfield`].
+#[cfg(not(feature = "rand"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! bitfield_rand {
+    [ $struct:ident($underlying_type:ty) ] => {};
+}
+
+/// Implement rkyv's `Archive`, `Serialize`, and `Deserialize` for a bitfield with the
+/// `rkyv` feature, or nothing without it. Helper macro for [`bitfield`].
+#[cfg(feature = "rkyv")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! bitfield_rkyv {
+    [ $struct:ident($underlying_type:ty) ] => {
+        impl $crate::rkyv::Archive for $struct {
+            type Archived = $crate::ArchivedBitfield<
+                $crate::rkyv::Archived<
+                    <$underlying_type as $crate::FieldStorage>::Storage
+                >,
+                $struct,
+            >;
+            type Resolver = $crate::rkyv::Resolver<
+                <$underlying_type as $crate::FieldStorage>::Storage
+            >;
+
+            #[inline(always)]
+            fn resolve(
+                &self,
+                resolver: Self::Resolver,
+                out: $crate::rkyv::Place<Self::Archived>,
+            ) {
+                $crate::resolve_bitfield::<$struct, $underlying_type>(
+                    *self, resolver, out,
+                );
+            }
+        }
+
+        impl<S> $crate::rkyv::Serialize<S> for $struct
+        where
+            S: $crate::rkyv::rancor::Fallible + ?::core::marker::Sized,
+        {
+            #[inline(always)]
+            fn serialize(
+                &self,
+                serializer: &mut S,
+            ) -> ::core::result::Result<Self::Resolver, S::Error> {
+                let packed = $crate::packed_value::<$struct, $underlying_type>(*self);
+                $crate::rkyv::Serialize::serialize(&packed, serializer)
+            }
+        }
+    };
+}
+
+/// Implement rkyv's `Archive`, `Serialize`, and `Deserialize` for a bitfield with the
+/// `rkyv` feature, or nothing without it. Helper macro for [`bitfield`].
+#[cfg(not(feature = "rkyv"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! bitfield_rkyv {
+    [ $struct:ident($underlying_type:ty) ] => {};
+}
+
+/// Define a named group of fields that can be included in several [`bitfield`]
+/// definitions.
+///
+/// This defines a local macro with the name of the group, which follows the usual
+/// scoping rules for `macro_rules!` macros, so the group must be defined before the
+/// bitfields that use it. A bitfield includes groups with `include` entries before its
+/// own fields:
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, bitfield_fields};
+/// bitfield_fields! {
+///     /// Flags shared by every status register
+///     common_status {
+///         [0]    pub ready,
+///         [1]    pub error,
+///         [2..5] pub state,
+///     }
+/// }
+///
+/// bitfield! {
+///     pub struct UartStatus(u32) {
+///         include common_status,
+///         [8..16] pub rx_count: u8,
+///     }
+/// }
+///
+/// bitfield! {
+///     pub struct SpiStatus(u16) {
+///         include common_status,
+///         [8] pub busy,
+///     }
+/// }
+///
+/// assert!(UartStatus(0x0301).ready());
+/// assert_eq!(UartStatus(0x0301).rx_count(), 3);
+/// assert_eq!(SpiStatus(0x0112).state(), 4);
+/// assert!(SpiStatus(0x0112).busy());
+/// ```
+///
+/// Every struct defined with [`bitfield`] or [`bitfield_without_debug`] is also a group
+/// with the same name, so a new struct can extend an existing one, like a later revision
+/// of a register that defines formerly reserved bits:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct ControlV1(u32) {
+///         [0]    pub enable,
+///         [4..8] pub mode,
+///     }
+/// }
+///
+/// bitfield! {
+///     pub struct ControlV2(u32) {
+///         include ControlV1,
+///         [16..20] pub rate,
+///     }
+/// }
+///
+/// let x = ControlV2::default().with_mode(3).with_rate(9);
+/// assert_eq!(x, ControlV2(0x0009_0030));
+/// assert_eq!(ControlV1(x.0).mode(), 3);
+/// ```
+///
+/// Included fields keep the bit positions declared in the group. To share accessors
+/// between types without duplicating the fields in each one, define them in a trait with
+/// [`bitfield_accessors`] instead.
+#[macro_export]
+macro_rules! bitfield_fields {
+    [
+        @define ($d:tt) $( #[$group_meta:meta] )* $name:ident { $($fields:tt)* }
+    ] => {
+        $( #[$group_meta] )*
+        macro_rules! $name {
+            [
+                @include $d header:tt [ $d( $d included:tt )* ] { $d( $d body:tt )* }
+            ] => {
+                $crate::bitfield_without_debug! {
+                    @includes $d header [ $d( $d included )* $($fields)* ] {
+                        $d( $d body )*
+                    }
+                }
+            };
+        }
+    };
+
+    // Every bitfield defines a group with its own name, so other bitfields can extend it
+    [
+        @inherit $name:ident {
+            $(
+                $( #[$meta:meta] )*
+                [ $( $range:tt )* ]
+                $vis:vis $field:ident
+                $(
+                    : $underlying_type:ty $( as $interface_type:ty )?
+                    $( where $check:expr )?
+                )?
+            ),*
+            $(,)?
+        }
+    ] => {
+        $crate::bitfield_fields! {
+            @define ($) #[allow(unused_macros)] $name {
+                $(
+                    $( #[$meta] )*
+                    [ $( $range )* ]
+                    $vis $field
+                    $( : $underlying_type $( as $interface_type )? $( where $check )? )?,
+                )*
+            }
+        }
+    };
+
+    // Malformed fields are reported by `bitfield_accessors` instead
+    [ @inherit $( $rest:tt )* ] => {};
+
+    // Fields declared in a `group` entry of a bitfield, renamed with the group's prefix
+    [
+        @prefix $header:tt [ $($included:tt)* ] $prefix:ident {
+            $(
+                $( #[$meta:meta] )*
+                [ $( $range:tt )* ]
+                $vis:vis $field:ident
+                $(
+                    : $underlying_type:ty $( as $interface_type:ty )?
+                    $( where $check:expr )?
+                )?
+            ),*
+            $(,)?
+        } { $($body:tt)* }
+    ] => {
+        $crate::paste! {
+            $crate::bitfield_without_debug! {
+                @includes $header [
+                    $($included)*
+                    $(
+                        $( #[$meta] )*
+                        [ $( $range )* ]
+                        $vis [< $prefix _ $field >]
+                        $(
+                            : $underlying_type $( as $interface_type )?
+                            $( where $check )?
+                        )?,
+                    )*
+                ] { $($body)* }
+            }
+        }
+    };
+
+    [
+        $( #[$group_meta:meta] )*
+        $name:ident {
+            $(
+                $( #[$meta:meta] )*
+                [ $( $range:tt )* ]
+                $vis:vis $field:ident
+                $(
+                    : $underlying_type:ty $( as $interface_type:ty )?
+                    $( where $check:expr )?
+                )?
+            ),*
+            $(,)?
+        }
+    ] => {
+        $crate::bitfield_fields! {
+            @define ($) $( #[$group_meta] )* $name {
+                $(
+                    $( #[$meta] )*
+                    [ $( $range )* ]
+                    $vis $field
+                    $( : $underlying_type $( as $interface_type )? $( where $check )? )?,
+                )*
+            }
+        }
+    };
+}
+
+/// Define getters and setters for certain bit ranges. The containing type must
+/// implement the [`Bitfield`] trait.
+///
+/// This is most commonly invoked by the [`bitfield`] macro, but it can be used on its own
+/// to define common accessors as part of a trait, as in the example below:
+///
+/// ```
+/// # use tartan_bitfield::{Bitfield, bitfield, bitfield_accessors};
+/// #
+/// trait CommonFields: Bitfield<u32> {
+///     bitfield_accessors! {
+///         // Since these are part of a trait, the `pub` keyword must be omitted.
+///         [ 0.. 6] a: u8,
+///         [14]     b,
+///         [18..32] c: u16,
+///     }
+/// }
+///
+/// bitfield! {
+///     struct SomeFields(u32) {
+///         [ 7] pub x,
+///         [16] pub y,
+///     }
+/// }
+///
+/// bitfield! {
+///     struct OtherFields(u32) {
+///         [10] pub z,
+///         [12] pub q,
+///     }
+/// }
+///
+/// impl CommonFields for SomeFields {}
 /// impl CommonFields for OtherFields {}
 ///
-/// let f = SomeFields(0xabcd_1234);
-/// assert_eq!(f.a(), 0x34); // has accessors from CommonFields
-/// assert_eq!(f.y(), true); // has accessors from SomeFields
-/// //assert_eq!(f.z(), false); // COMPILE ERROR: no accessors from OtherFields
+/// let f = SomeFields(0xabcd_1234);
+/// assert_eq!(f.a(), 0x34); // has accessors from CommonFields
+/// assert_eq!(f.y(), true); // has accessors from SomeFields
+/// //assert_eq!(f.z(), false); // COMPILE ERROR: no accessors from OtherFields
+///
+/// let g = OtherFields(0xabcd_1234);
+/// assert_eq!(g.a(), 0x34); // has accessors from CommonFields
+/// //assert_eq!(g.y(), true); // COMPILE ERROR: no accessors from SomeFields
+/// assert_eq!(g.z(), false); // has accessors from OtherFields
+/// ```
+///
+/// Along with the accessors, the macro defines helper methods like `fmt_fields` that
+/// the [`bitfield`] macro uses to implement [`Debug`] and other traits. A type that
+/// implements more than one trait with these helpers can't call them without
+/// qualifying the trait. Start the fields with `#![accessors_only]` to define only the
+/// accessors and their range constants:
+///
+/// ```
+/// # use tartan_bitfield::{Bitfield, bitfield, bitfield_accessors};
+/// trait Transmit: Bitfield<u16> {
+///     bitfield_accessors! {
+///         #![accessors_only]
+///         [0]    tx_enable,
+///         [1..8] tx_len: u8,
+///     }
+/// }
+///
+/// trait Receive: Bitfield<u16> {
+///     bitfield_accessors! {
+///         #![accessors_only]
+///         [8]     rx_enable,
+///         [9..16] rx_len: u8,
+///     }
+/// }
+///
+/// bitfield! {
+///     struct Link(u16) {}
+/// }
+///
+/// impl Transmit for Link {}
+/// impl Receive for Link {}
+///
+/// let link = Link(0x0a07);
+/// assert_eq!((link.tx_len(), link.rx_len()), (3, 5));
+/// assert_eq!(format!("{link:?}"), "Link { <value>: 2567 }");
+/// ```
+#[macro_export]
+macro_rules! bitfield_accessors {
+    // Accessors without helper methods, for traits that share fields with others
+    [ #![accessors_only] $( $body:tt )* ] => {
+        $crate::bitfield_accessors! { @accessors [] $( $body )* }
+    };
+
+    // Accessors on the struct defined by `bitfield`, which operate directly on its field
+    [ @inherent [ $inner:tt ] ( $packed_type:ty ) $( $body:tt )* ] => {
+        $crate::bitfield_accessors! { @helpers [ [ $inner ] $packed_type ] $( $body )* }
+    };
+
+    // The source of the packed value is `[]` for accessors in a trait, which go through
+    // the `Bitfield` trait, or the struct's field and underlying type for `@inherent`
+    [
+        @accessors $src:tt
+        $(
+            $( #[$meta:meta] )*
+            [ $( $range:tt )* ]
+            $vis:vis $field:ident
+            $(
+                : $underlying_type:ty $( as $interface_type:ty )?
+                $( where $check:expr )?
+            )?
+        ),*
+        $(,)?
+    ] => {
+        $(
+            $crate::bitfield_accessors! {
+                @field getter $src
+                $( #[$meta] )*
+                #[doc = ""]
+                #[doc = $crate::bitfield_accessors!(@range_doc [ $( $range )* ])]
+                [ $( $range )* ]
+                $vis $field
+                $( : $underlying_type $( as $interface_type )? )?
+            }
+        )*
+
+        $(
+            $crate::bitfield_accessors! {
+                @field setter $src
+                $( #[$meta] )*
+                #[doc = ""]
+                #[doc = $crate::bitfield_accessors!(@range_doc [ $( $range )* ])]
+                [ $( $range )* ]
+                $vis $field
+                $( : $underlying_type $( as $interface_type )? )?
+            }
+        )*
+
+        $(
+            $crate::bitfield_accessors! {
+                @field checked_setter $src
+                $( #[$meta] )*
+                #[doc = ""]
+                #[doc = $crate::bitfield_accessors!(@range_doc [ $( $range )* ])]
+                [ $( $range )* ]
+                $vis $field
+                $( : $underlying_type $( as $interface_type )? $( where $check )? )?
+            }
+        )*
+
+        $(
+            $crate::bitfield_accessors! {
+                @if_well_formed [ $( $range )* ] $( : $underlying_type )? {
+                    $crate::paste! {
+                        #[doc = concat!(
+                            "The bit range of the `", stringify!($field), "` field"
+                        )]
+                        $(#[$meta])*
+                        #[allow(dead_code)]
+                        $vis const [< $field:upper _RANGE >]: ::core::ops::Range<u8> =
+                            $crate::bitfield_accessors!(@lsb $( $range )*)
+                                ..$crate::bitfield_accessors!(@msb $( $range )*);
+                    }
+                }
+            }
+        )*
+    };
+
+    [
+        @helpers $src:tt
+        $(
+            $( #[$meta:meta] )*
+            [ $( $range:tt )* ]
+            $vis:vis $field:ident
+            $(
+                : $underlying_type:ty $( as $interface_type:ty )?
+                $( where $check:expr )?
+            )?
+        ),*
+        $(,)?
+    ] => {
+        $crate::bitfield_accessors! {
+            @accessors $src
+            $(
+                $( #[$meta] )*
+                [ $( $range )* ]
+                $vis $field
+                $( : $underlying_type $( as $interface_type )? $( where $check )? )?,
+            )*
+        }
+
+        /// Call `visit` with the name and bit range of each field, along with a function
+        /// that copies the field from its second argument to its first. Helper method
+        /// for tests generated by `bitfield_tests` and `bitfield_proofs`.
+        #[allow(dead_code)]
+        fn visit_field_accessors(
+            mut visit: impl FnMut(&'static str, u8, u8, &dyn Fn(Self, Self) -> Self),
+        ) {
+            $(
+                $(#[$meta])*
+                $crate::bitfield_accessors! {
+                    @if_well_formed [ $( $range )* ] $( : $underlying_type )? {
+                        $crate::paste! {
+                            visit(
+                                stringify!($field),
+                                $crate::bitfield_accessors!(@lsb $( $range )*),
+                                $crate::bitfield_accessors!(@msb $( $range )*),
+                                &|mut dest: Self, src: Self| {
+                                    dest.[< set_ $field >](src.$field());
+                                    dest
+                                },
+                            );
+                        }
+                    }
+                }
+            )*
+        }
+
+        /// Print this object's bitfield values. Helper method for `Debug`
+        /// implementations.
+        fn fmt_fields(&self, f: &mut ::core::fmt::DebugStruct) {
+            $(
+                $(#[$meta])*
+                $crate::bitfield_accessors! {
+                    @if_well_formed [ $( $range )* ] $( : $underlying_type )? {
+                        f.field(stringify!($field), &self.$field());
+                    }
+                }
+            )*
+        }
+
+        /// Print the bitfield values that differ from those of `base`. Helper method for
+        /// `DebugSummary` implementations.
+        fn fmt_changed_fields(&self, base: Self, f: &mut ::core::fmt::DebugStruct) {
+            let packed = $crate::packed_value(*self);
+            let base = $crate::packed_value(base);
+            $(
+                $(#[$meta])*
+                $crate::bitfield_accessors! {
+                    @if_well_formed [ $( $range )* ] $( : $underlying_type )? {
+                        {
+                            let lsb = $crate::bitfield_accessors!(@lsb $( $range )*);
+                            let msb = $crate::bitfield_accessors!(@msb $( $range )*);
+                            if $crate::PackedBits::packed_field(packed, lsb, msb)
+                                != $crate::PackedBits::packed_field(base, lsb, msb)
+                            {
+                                f.field(stringify!($field), &self.$field());
+                            }
+                        }
+                    }
+                }
+            )*
+            let _ = (packed, base);
+        }
+
+        /// Write this object's bitfield values as comma-separated `name=value` pairs.
+        /// Helper method for `WriteFields` implementations.
+        fn write_field_pairs<W>(&self, w: &mut W) -> ::core::fmt::Result
+        where
+            W: ::core::fmt::Write + ?Sized,
+        {
+            let mut first = true;
+            $(
+                $(#[$meta])*
+                $crate::bitfield_accessors! {
+                    @if_well_formed [ $( $range )* ] $( : $underlying_type )? {
+                        {
+                            if !::core::mem::replace(&mut first, false) {
+                                w.write_str(", ")?;
+                            }
+                            w.write_str(stringify!($field))?;
+                            w.write_char('=')?;
+                            $crate::bitfield_accessors! {
+                                @write self, w, [ $( $range )* ] $field
+                                $( : $underlying_type $( as $interface_type )? )?
+                            }?;
+                        }
+                    }
+                }
+            )*
+            let _ = first;
+            Ok(())
+        }
+
+        /// Check this object's bitfield values against their declared constraints.
+        /// Helper method for `Validate` implementations.
+        fn validate_fields(&self) -> ::core::result::Result<(), $crate::ValidationError> {
+            $(
+                $(#[$meta])*
+                $crate::bitfield_accessors! {
+                    @if_well_formed [ $( $range )* ] $( : $underlying_type )? {
+                        $crate::bitfield_accessors! {
+                            @validate self, $field
+                            $(
+                                : $underlying_type $( as $interface_type )?
+                                $( where $check )?
+                            )?
+                        }?;
+                    }
+                }
+            )*
+            Ok(())
+        }
+
+        /// Assign one of this object's bitfield values by name, parsing the value from
+        /// a string. Helper method for `ParseFields` implementations.
+        fn parse_field(
+            &mut self,
+            name: &str,
+            value: &str,
+        ) -> ::core::result::Result<(), $crate::FieldParseError> {
+            let (field, nested) = match name.split_once('.') {
+                Some((field, nested)) => (field, Some(nested)),
+                None => (name, None),
+            };
+            $(
+                $(#[$meta])*
+                $crate::bitfield_accessors! {
+                    @if_well_formed [ $( $range )* ] $( : $underlying_type )? {
+                        if field == stringify!($field) {
+                            return $crate::bitfield_accessors! {
+                                @parse self, nested, value,
+                                [ $( $range )* ] $field
+                                $( : $underlying_type $( as $interface_type )? )?
+                            };
+                        }
+                    }
+                }
+            )*
+            let _ = (field, nested, value);
+            Err($crate::FieldParseError::UnknownField)
+        }
+    };
+
+    // Write the value of a field, as a bit for single-bit flags or hex for everything
+    // else
+    [
+        @write $this:ident, $w:ident,
+        [ $bit:literal $( - $offset:literal )? ] $field:ident
+    ] => {
+        ::core::write!($w, "{}", u8::from($this.$field()))
+    };
+
+    [
+        @write $this:ident, $w:ident, [ $( $range:tt )* ] $field:ident
+    ] => {
+        $crate::bitfield_accessors! {
+            @write $this, $w, [ $( $range )* ] $field
+            : $crate::DefaultField<{
+                ($crate::bitfield_accessors!(@msb $( $range )*)
+                    - $crate::bitfield_accessors!(@lsb $( $range )*)) as usize
+            }>
+        }
+    };
+
+    [
+        @write $this:ident, $w:ident, [ $( $range:tt )* ] $field:ident
+        : $underlying_type:ty $( as $interface_type:ty )?
+    ] => {
+        {
+            let underlying = $crate::bitfield_accessors! {
+                @convert $this.$field(),
+                $crate::bitfield_accessors!(
+                    @interface $underlying_type $( as $interface_type )?
+                ) => $crate::bitfield_accessors!(
+                    @underlying $underlying_type $( as $interface_type )?
+                )
+            };
+            ::core::write!($w, "{:#x}", underlying)
+        }
+    };
+
+    // Read the bits `lsb..msb` as the integer type `type`. Accessors in a trait only
+    // have the `Bitfield` trait to go through.
+    [ @get [] $self:tt, $lsb:expr, $msb:expr => $type:ty ] => {
+        $crate::PackedBits::packed_field($crate::packed_value(*$self), $lsb, $msb)
+            as $type
+    };
+
+    // Accessors on the struct itself shift and mask its field directly. For storage
+    // wider than 64 bits, a field that lies within one 64-bit half only operates on that
+    // half, since most targets need several instructions to shift a u128.
+    [
+        @get [ [ $inner:tt ] $packed_type:ty ]
+        $self:tt, $lsb:expr, $msb:expr => $type:ty
+    ] => {{
+        // Exact-width storage like `u24` converts to its primitive type; for primitive
+        // storage this is the identity
+        type Packed = <$packed_type as $crate::FieldStorage>::Storage;
+        let packed: Packed = ::core::convert::From::from($self.$inner);
+        let within_half = $lsb / 64 == ($msb - 1) / 64;
+        if ::core::mem::size_of::<Packed>() > 8 && within_half {
+            let half = ((packed as u128) >> ($lsb / 64 * 64)) as u64;
+            let mask: u64 = $crate::bitfield_accessors!(@mask $msb - $lsb);
+            ((half >> ($lsb % 64)) & mask) as $type
+        } else {
+            let mask: Packed = $crate::bitfield_accessors!(@mask $msb - $lsb);
+            ((packed >> $lsb) & mask) as $type
+        }
+    }};
+
+    // Replace the bits `lsb..msb` with the low bits of `field`, returning the new value
+    [ @set [] $self:tt, $lsb:expr, $msb:expr, $field:expr ] => {
+        $crate::from_packed_value($crate::PackedBits::with_packed_field(
+            $crate::packed_value(*$self),
+            $lsb,
+            $msb,
+            // Widen to the packed type through the bitfield type, which is the only
+            // place it's named in this context
+            $crate::widen_to_packed::<Self, _, _>($field),
+        ))
+    };
+
+    [
+        @set [ [ $inner:tt ] $packed_type:ty ]
+        $self:tt, $lsb:expr, $msb:expr, $field:expr
+    ] => {{
+        type Packed = <$packed_type as $crate::FieldStorage>::Storage;
+        let packed: Packed = ::core::convert::From::from($self.$inner);
+        let within_half = $lsb / 64 == ($msb - 1) / 64;
+        let packed = if ::core::mem::size_of::<Packed>() > 8 && within_half {
+            let shift = $lsb / 64 * 64;
+            let half = ((packed as u128) >> shift) as u64;
+            let mask: u64 = $crate::bitfield_accessors!(@mask $msb - $lsb);
+            let field = $field as u64 & mask;
+            let half = half & !(mask << ($lsb % 64)) | field << ($lsb % 64);
+            packed & !((u64::MAX as Packed) << shift) | (half as Packed) << shift
+        } else {
+            let mask: Packed = $crate::bitfield_accessors!(@mask $msb - $lsb);
+            packed & !(mask << $lsb) | ($field as Packed & mask) << $lsb
+        };
+        Self { $inner: $crate::TruncateInto::truncate_into(packed) }
+    }};
+
+    // A mask of the given number of low bits, in whatever integer type is inferred. The
+    // shift is split in two so that a mask covering the whole type doesn't overflow.
+    [ @mask $width:expr ] => { !((!0 << ($width - 1)) << 1) };
+
+    // The bounds of a field's bit range, with the MSB exclusive
+    [ @lsb $bit:literal $( - $offset:literal )? ] => { $bit $( - $offset )? };
+    [ @lsb $lsb:tt .. $msb:tt $( - $offset:literal )? ] => { $lsb $( - $offset )? };
+    [ @lsb $lsb:tt ..= $msb:tt $( - $offset:literal )? ] => { $lsb $( - $offset )? };
+    [ @msb $bit:literal $( - $offset:literal )? ] => { $bit $( - $offset )? + 1 };
+    [ @msb $lsb:tt .. $msb:tt $( - $offset:literal )? ] => { $msb $( - $offset )? };
+    [ @msb $lsb:tt ..= $msb:tt $( - $offset:literal )? ] => { $msb $( - $offset )? + 1 };
+    // Malformed ranges are reported by the field accessors, so don't pile on
+    [ @lsb $( $range:tt )* ] => { 0 };
+    [ @msb $( $range:tt )* ] => { 0 };
+
+    // The bit range of a field for its documentation, with the bit numbers as declared
+    [ @range_doc [ $bit:literal $( - $offset:literal )? ] ] => {
+        ::core::concat!("Bit ", $crate::bitfield_accessors!(@range_text [ $bit ]), ".")
+    };
+    [ @range_doc [ $( $range:tt )* ] ] => {
+        ::core::concat!(
+            "Bits ", $crate::bitfield_accessors!(@range_text [ $( $range )* ]), ".",
+        )
+    };
+    [ @range_text [ $bit:literal $( - $offset:literal )? ] ] => {
+        ::core::concat!("`", ::core::stringify!($bit), "`")
+    };
+    [ @range_text [ $lsb:literal .. ( $( $max:tt )* ) $( - $offset:literal )? ] ] => {
+        ::core::concat!("`", ::core::stringify!($lsb), "..`")
+    };
+    [ @range_text [ $lsb:literal .. $msb:literal $( - $offset:literal )? ] ] => {
+        ::core::concat!(
+            "`", ::core::stringify!($lsb), "..", ::core::stringify!($msb), "`",
+        )
+    };
+    [ @range_text [ $lsb:literal ..= $msb:literal $( - $offset:literal )? ] ] => {
+        ::core::concat!(
+            "`", ::core::stringify!($lsb), "..=", ::core::stringify!($msb), "`",
+        )
+    };
+    [ @range_text [ $( $range:tt )* ] ] => {
+        ::core::concat!("`", ::core::stringify!($( $range )*), "`")
+    };
+
+    // A table of the bit range of every field, for the documentation of the struct
+    [
+        @layout_doc
+        $(
+            $( #[$meta:meta] )*
+            [ $( $range:tt )* ]
+            $vis:vis $field:ident
+            $(
+                : $underlying_type:ty $( as $interface_type:ty )?
+                $( where $check:expr )?
+            )?
+        ),+
+        $(,)?
+    ] => {
+        ::core::concat!(
+            "| Field | Bits |\n",
+            "|-------|------|\n",
+            $(
+                "| `", ::core::stringify!($field), "` | ",
+                $crate::bitfield_accessors!(@range_text [ $( $range )* ]), " |\n",
+            )*
+        )
+    };
+
+    [ @layout_doc $( $rest:tt )* ] => { "" };
+
+    // The interface type of a field, which defaults to the underlying type
+    [
+        @interface $underlying_type:ty
+    ] => {
+        $underlying_type
+    };
+
+    [
+        @interface $underlying_type:ty as $interface_type:ty
+    ] => {
+        $interface_type
+    };
+
+    // The underlying type of a field. Without an explicit interface type, the declared
+    // type is the interface type, and it may be a nested bitfield.
+    [
+        @underlying $field_type:ty
+    ] => {
+        <$field_type as $crate::FieldStorage>::Storage
+    };
+
+    [
+        @underlying $underlying_type:ty as $interface_type:ty
+    ] => {
+        $underlying_type
+    };
+
+    // Convert a value between the underlying and interface types of a field
+    [
+        @convert $value:expr, $source:ty => $target:ty
+    ] => {
+        {
+            #[allow(unused_imports)]
+            use $crate::{FieldConversionFallback as _, FieldConversionDirect as _};
+            (&$crate::FieldConversion::<$source, $target>::new()).convert_field($value)
+        }
+    };
+
+    // Check the value of a field against its constraint, if it has one
+    [
+        @validate $this:ident, $field:ident
+        : $underlying_type:ty $( as $interface_type:ty )? where $check:expr
+    ] => {
+        if $crate::FieldCheck::check(&$check, $this.$field()) {
+            Ok(())
+        } else {
+            Err($crate::ValidationError::Field(stringify!($field)))
+        }
+    };
+
+    [
+        @validate $this:ident, $field:ident $( $rest:tt )*
+    ] => {
+        ::core::result::Result::<(), $crate::ValidationError>::Ok(())
+    };
+
+    // Parse a string value for a field, possibly with a nested field name
+    [
+        @parse $this:ident, $nested:ident, $value:ident,
+        [ $bit:literal $( - $offset:literal )? ] $field:ident
+    ] => {
+        $crate::paste! {
+            match $nested {
+                None => $crate::parse_flag($value).map(|v| $this.[< set_ $field >](v)),
+                Some(_) => Err($crate::FieldParseError::UnknownField),
+            }
+        }
+    };
+
+    [
+        @parse $this:ident, $nested:ident, $value:ident,
+        [ $bit:literal $( - $offset:literal )? ] $field:ident
+        : $underlying_type:ty $( as $interface_type:ty )?
+    ] => {
+        $crate::bitfield_accessors! {
+            @parse $this, $nested, $value, [ $bit ..= $bit $( - $offset )? ] $field
+            : $underlying_type $( as $interface_type )?
+        }
+    };
+
+    [
+        @parse $this:ident, $nested:ident, $value:ident,
+        [ $lsb:tt .. $msb:tt $( - $offset:literal )? ] $field:ident
+        : $underlying_type:ty $( as $interface_type:ty )?
+    ] => {
+        $crate::bitfield_accessors! {
+            @parse_bits $this, $nested, $value, ($msb - $lsb) $field
+            : $crate::bitfield_accessors!(
+                @underlying $underlying_type $( as $interface_type )?
+            ) as $crate::bitfield_accessors!(
+                @interface $underlying_type $( as $interface_type )?
+            )
+        }
+    };
+
+    [
+        @parse $this:ident, $nested:ident, $value:ident,
+        [ $lsb:tt ..= $msb:tt $( - $offset:literal )? ] $field:ident
+        : $underlying_type:ty $( as $interface_type:ty )?
+    ] => {
+        $crate::bitfield_accessors! {
+            @parse_bits $this, $nested, $value, ($msb + 1 - $lsb) $field
+            : $crate::bitfield_accessors!(
+                @underlying $underlying_type $( as $interface_type )?
+            ) as $crate::bitfield_accessors!(
+                @interface $underlying_type $( as $interface_type )?
+            )
+        }
+    };
+
+    [
+        @parse $this:ident, $nested:ident, $value:ident,
+        [ $lsb:tt .. $msb:tt $( - $offset:literal )? ] $field:ident
+    ] => {
+        $crate::bitfield_accessors! {
+            @parse_bits $this, $nested, $value, ($msb - $lsb) $field
+            : <$crate::DefaultField<{ ($msb - $lsb) as usize }>
+                as $crate::FieldStorage>::Storage
+                as $crate::DefaultField<{ ($msb - $lsb) as usize }>
+        }
+    };
+
+    [
+        @parse $this:ident, $nested:ident, $value:ident,
+        [ $lsb:tt ..= $msb:tt $( - $offset:literal )? ] $field:ident
+    ] => {
+        $crate::bitfield_accessors! {
+            @parse_bits $this, $nested, $value, ($msb + 1 - $lsb) $field
+            : <$crate::DefaultField<{ ($msb + 1 - $lsb) as usize }>
+                as $crate::FieldStorage>::Storage
+                as $crate::DefaultField<{ ($msb + 1 - $lsb) as usize }>
+        }
+    };
+
+    [
+        @parse_bits $this:ident, $nested:ident, $value:ident,
+        $width:tt $field:ident : $underlying_type:ty as $interface_type:ty
+    ] => {
+        $crate::paste! {
+            match $nested {
+                None => $crate::parse_prefixed::<$underlying_type>($value)
+                    .ok()
+                    .filter(|&underlying| {
+                        $crate::get_bits(underlying, 0, $width) == underlying
+                    })
+                    .map(|underlying| {
+                        $this.[< set_ $field >]($crate::bitfield_accessors! {
+                            @convert underlying, $underlying_type => $interface_type
+                        })
+                    })
+                    .ok_or($crate::FieldParseError::InvalidValue),
+                Some(nested) => {
+                    #[allow(unused_imports)]
+                    use $crate::{NestedFieldFallback as _, NestedFieldParse as _};
+                    (&$crate::NestedField($this.$field()))
+                        .set_nested_field_from_str(nested, $value)
+                        .map(|v| $this.[< set_ $field >](v))
+                }
+            }
+        }
+    };
+
+    // Setters that enforce the constraint on a field, if it has one
+    [
+        @field checked_setter $src:tt
+        $( #[$meta:meta] )*
+        [ $( $range:tt )* ]
+        $vis:vis $field:ident
+        : $underlying_type:ty $( as $interface_type:ty )? where $check:expr
+    ] => {
+        $crate::paste! {
+            $( #[$meta] )*
+            #[inline(always)]
+            $vis fn [< try_set_ $field >](
+                &mut self,
+                value: $crate::bitfield_accessors!(
+                    @interface $underlying_type $( as $interface_type )?
+                ),
+            ) -> ::core::result::Result<(), $crate::ValidationError> {
+                *self = self.[< try_with_ $field >](value)?;
+                Ok(())
+            }
+
+            $( #[$meta] )*
+            $vis fn [< try_with_ $field >](
+                &self,
+                value: $crate::bitfield_accessors!(
+                    @interface $underlying_type $( as $interface_type )?
+                ),
+            ) -> ::core::result::Result<Self, $crate::ValidationError> {
+                let updated = self.[< with_ $field >](value);
+                if $crate::FieldCheck::check(&$check, updated.$field()) {
+                    Ok(updated)
+                } else {
+                    Err($crate::ValidationError::Field(stringify!($field)))
+                }
+            }
+        }
+    };
+
+    [
+        @field checked_setter $src:tt
+        $( #[$meta:meta] )*
+        [ $( $range:tt )* ]
+        $vis:vis $field:ident
+        $( $rest:tt )*
+    ] => {};
+
+    // Special case for single-bit boolean fields
+    [
+        @field getter $src:tt
+        $( #[$meta:meta] )*
+        [ $bit:literal $( - $offset:literal )? ]
+        $vis:vis $field:ident
+    ] => {
+        $crate::paste! {
+            $( #[$meta] )*
+            $vis fn $field(&self) -> bool {
+                let bit = $crate::bitfield_accessors!(
+                    @get $src self, $bit $( - $offset )?, $bit $( - $offset )? + 1 => u8
+                );
+                bit != 0
+            }
+        }
+    };
+
+    // Special case for single-bit boolean fields
+    [
+        @field setter $src:tt
+        $( #[$meta:meta] )*
+        [ $bit:literal $( - $offset:literal )? ]
+        $vis:vis $field:ident
+    ] => {
+        $crate::paste! {
+            $( #[$meta] )*
+            #[inline(always)]
+            $vis fn [< set_ $field >](&mut self, value: bool) {
+                *self = self.[< with_ $field >](value);
+            }
+
+            $( #[$meta] )*
+            $vis fn [< with_ $field >](&mut self, value: bool) -> Self {
+                $crate::bitfield_accessors!(
+                    @set $src self, $bit $( - $offset )?, $bit $( - $offset )? + 1, value
+                )
+            }
+        }
+    };
+
+    // Without a type, ranges use `bool` for a single bit, or otherwise the smallest
+    // unsigned integer that fits
+    [
+        @field $accessor_type:tt $src:tt
+        $( #[$meta:meta] )*
+        [ $lsb:tt .. $msb:tt $( - $offset:literal )? ]
+        $vis:vis $field:ident
+    ] => {
+        $crate::bitfield_accessors! {
+            @field $accessor_type $src
+            $( #[$meta] )*
+            [$lsb..$msb $( - $offset )?] $vis $field
+            : $crate::DefaultField<{ ($msb - $lsb) as usize }>
+        }
+    };
+
+    [
+        @field $accessor_type:tt $src:tt
+        $( #[$meta:meta] )*
+        [ $lsb:tt ..= $msb:tt $( - $offset:literal )? ]
+        $vis:vis $field:ident
+    ] => {
+        $crate::bitfield_accessors! {
+            @field $accessor_type $src
+            $( #[$meta] )*
+            [$lsb..=$msb $( - $offset )?] $vis $field
+            : $crate::DefaultField<{ ($msb + 1 - $lsb) as usize }>
+        }
+    };
+
+    // A field type and both range bounds are required in all other cases.
+    // When no explicit interface type is given, the declared type is the interface type,
+    // and it determines the underlying type.
+    [
+        @field $accessor_type:tt $src:tt
+        $( #[$meta:meta] )*
+        [ $lsb:tt .. $msb:tt $( - $offset:literal )? ]
+        $vis:vis $field:ident
+        : $field_type:ty
+    ] => {
+        $crate::bitfield_accessors! {
+            @field $accessor_type $src
+            $( #[$meta] )*
+            [$lsb..$msb $( - $offset )?] $vis $field
+            : <$field_type as $crate::FieldStorage>::Storage as $field_type
+        }
+    };
+
+    [
+        @field $accessor_type:tt $src:tt
+        $( #[$meta:meta] )*
+        [ $lsb:tt ..= $msb:tt $( - $offset:literal )? ]
+        $vis:vis $field:ident
+        : $field_type:ty
+    ] => {
+        $crate::bitfield_accessors! {
+            @field $accessor_type $src
+            $( #[$meta] )*
+            [$lsb..=$msb $( - $offset )?] $vis $field
+            : <$field_type as $crate::FieldStorage>::Storage as $field_type
+        }
+    };
+
+    [
+        @field getter $src:tt
+        $( #[$meta:meta] )*
+        [ $lsb:tt .. $msb:tt $( - $offset:literal )? ]
+        $vis:vis $field:ident
+        : $underlying_type:ty as $interface_type:ty
+    ] => {
+        $crate::paste! {
+            $( #[$meta] )*
+            $vis fn $field(&self) -> $interface_type {
+                let underlying = $crate::bitfield_accessors!(
+                    @get $src self, $lsb $( - $offset )?, $msb $( - $offset )?
+                        => $underlying_type
+                );
+                $crate::bitfield_accessors! {
+                    @convert underlying, $underlying_type => $interface_type
+                }
+            }
+        }
+    };
+
+    [
+        @field getter $src:tt
+        $( #[$meta:meta] )*
+        [ $lsb:tt ..= $msb:tt $( - $offset:literal )? ]
+        $vis:vis $field:ident
+        : $underlying_type:ty as $interface_type:ty
+    ] => {
+        $crate::paste! {
+            $( #[$meta] )*
+            $vis fn $field(&self) -> $interface_type {
+                let underlying = $crate::bitfield_accessors!(
+                    @get $src self, $lsb $( - $offset )?, $msb $( - $offset )? + 1
+                        => $underlying_type
+                );
+                $crate::bitfield_accessors! {
+                    @convert underlying, $underlying_type => $interface_type
+                }
+            }
+        }
+    };
+
+    [
+        @field setter $src:tt
+        $( #[$meta:meta] )*
+        [ $lsb:tt .. $msb:tt $( - $offset:literal )? ]
+        $vis:vis $field:ident
+        : $underlying_type:ty as $interface_type:ty
+    ] => {
+        $crate::paste! {
+            $( #[$meta] )*
+            #[inline(always)]
+            $vis fn [< set_ $field >](&mut self, value: $interface_type) {
+                *self = self.[< with_ $field >](value);
+            }
+
+            $( #[$meta] )*
+            $vis fn [< with_ $field >](&self, value: $interface_type) -> Self {
+                let underlying = $crate::bitfield_accessors! {
+                    @convert value, $interface_type => $underlying_type
+                };
+                $crate::bitfield_accessors!(
+                    @set $src self, $lsb $( - $offset )?, $msb $( - $offset )?, underlying
+                )
+            }
+        }
+    };
+
+    [
+        @field setter $src:tt
+        $( #[$meta:meta] )*
+        [ $lsb:tt ..= $msb:tt $( - $offset:literal )? ]
+        $vis:vis $field:ident
+        : $underlying_type:ty as $interface_type:ty
+    ] => {
+        $crate::paste! {
+            $( #[$meta] )*
+            #[inline(always)]
+            $vis fn [< set_ $field >](&mut self, value: $interface_type) {
+                *self = self.[< with_ $field >](value);
+            }
+
+            $( #[$meta] )*
+            $vis fn [< with_ $field >](&self, value: $interface_type) -> Self {
+                let underlying = $crate::bitfield_accessors! {
+                    @convert value, $interface_type => $underlying_type
+                };
+                $crate::bitfield_accessors!(
+                    @set $src self, $lsb $( - $offset )?, $msb $( - $offset )? + 1,
+                        underlying
+                )
+            }
+        }
+    };
+
+    // Single-bit fields with a type are integers, like a one-bit range
+    [
+        @field $accessor_type:tt $src:tt
+        $( #[$meta:meta] )*
+        [ $bit:literal $( - $offset:literal )? ]
+        $vis:vis $field:ident
+        : $( $rest:tt )*
+    ] => {
+        $crate::bitfield_accessors! {
+            @field $accessor_type $src
+            $( #[$meta] )*
+            [ $bit ..= $bit $( - $offset )? ] $vis $field : $( $rest )*
+        }
+    };
+
+    // Malformed fields. Only the getter reports an error, so each problem is reported
+    // once; everything else that would fail to match expands to nothing.
+    [
+        @field getter $src:tt
+        $( #[$meta:meta] )*
+        [ $( $range:tt )* ]
+        $vis:vis $field:ident
+        $( $rest:tt )*
+    ] => {
+        ::core::compile_error!(::core::concat!(
+            "field `", ::core::stringify!($field), "` has an unsupported bit range `[",
+            ::core::stringify!($( $range )*), "]`; expected a single bit like `[3]`, ",
+            "or a range of integer literals like `[0..4]`, `[0..=3]`, or `[4..]`",
+        ));
+    };
+
+    [ @field setter $( $rest:tt )* ] => {};
+
+    // Expand to the given tokens only if the field is well-formed, so that a malformed
+    // field produces a single error from its getter
+    [
+        @if_well_formed [ $bit:literal $( - $offset:literal )? ]
+        $( : $underlying_type:tt )? {
+            $( $body:tt )*
+        }
+    ] => {
+        $( $body )*
+    };
+
+    [
+        @if_well_formed [ $lsb:tt .. $msb:tt $( - $offset:literal )? ]
+        $( : $underlying_type:tt )? {
+            $( $body:tt )*
+        }
+    ] => {
+        $( $body )*
+    };
+
+    [
+        @if_well_formed [ $lsb:tt ..= $msb:tt $( - $offset:literal )? ]
+        $( : $underlying_type:tt )? {
+            $( $body:tt )*
+        }
+    ] => {
+        $( $body )*
+    };
+
+    [ @if_well_formed $( $rest:tt )* ] => {};
+
+    // A description of every field, for `FieldLayout`
+    [
+        @layout
+        $(
+            $( #[$meta:meta] )*
+            [ $( $range:tt )* ]
+            $vis:vis $field:ident
+            $(
+                : $underlying_type:ty $( as $interface_type:ty )?
+                $( where $check:expr )?
+            )?
+        ),*
+        $(,)?
+    ] => {
+        &[
+            $(
+                $(#[$meta])*
+                $crate::FieldInfo {
+                    name: stringify!($field),
+                    lsb: $crate::bitfield_accessors!(@lsb $( $range )*),
+                    msb: $crate::bitfield_accessors!(@msb $( $range )*),
+                    flag: $crate::bitfield_accessors!(@flag [ $( $range )* ]),
+                },
+            )*
+        ]
+    };
+
+    [ @layout $( $rest:tt )* ] => { &[] };
+
+    // The name of every field, for `FIELD_NAMES`
+    [
+        @field_names
+        $(
+            $( #[$meta:meta] )*
+            [ $( $range:tt )* ]
+            $vis:vis $field:ident
+            $(
+                : $underlying_type:ty $( as $interface_type:ty )?
+                $( where $check:expr )?
+            )?
+        ),*
+        $(,)?
+    ] => {
+        &[ $( $(#[$meta])* stringify!($field), )* ]
+    };
+
+    [ @field_names $( $rest:tt )* ] => { &[] };
+
+    [ @flag [ $bit:literal $( - $offset:literal )? ] ] => { true };
+    [ @flag [ $( $range:tt )* ] ] => { false };
+
+    // The highest bit used by any field, plus one
+    [
+        @max_msb
+        $(
+            $( #[$meta:meta] )*
+            [ $( $range:tt )* ]
+            $vis:vis $field:ident
+            $(
+                : $underlying_type:ty $( as $interface_type:ty )?
+                $( where $check:expr )?
+            )?
+        ),*
+        $(,)?
+    ] => {
+        {
+            #[allow(unused_mut)]
+            let mut max: u8 = 0;
+            $(
+                $(#[$meta])*
+                $crate::bitfield_accessors! {
+                    @if_well_formed [ $( $range )* ] $( : $underlying_type )? {
+                        if $crate::bitfield_accessors!(@msb $( $range )*) > max {
+                            max = $crate::bitfield_accessors!(@msb $( $range )*);
+                        }
+                    }
+                }
+            )*
+            max
+        }
+    };
+
+    [ @max_msb $( $rest:tt )* ] => { 0 };
+
+    // Compile-time checks that fields are wide enough for the fields of a nested bitfield
+    // or interface type, and, without an interface type, no wider than its storage
+    [
+        @check_widths
+        $(
+            $( #[$meta:meta] )*
+            [ $( $range:tt )* ]
+            $vis:vis $field:ident
+            $(
+                : $underlying_type:ty $( as $interface_type:ty )?
+                $( where $check:expr )?
+            )?
+        ),*
+        $(,)?
+    ] => {
+        $(
+            $(#[$meta])*
+            $crate::bitfield_accessors! {
+                @check_width [ $( $range )* ] $field
+                $( : $underlying_type $( as $interface_type )? )?
+            }
+        )*
+    };
+
+    [ @check_widths $( $rest:tt )* ] => {};
+
+    [
+        @check_width [ $bit:literal $( - $offset:literal )? ] $field:ident
+        : $underlying_type:ty as $interface_type:ty
+    ] => {
+        $crate::bitfield_accessors! { @assert_interface_width 1 $field : $interface_type }
+    };
+
+    [
+        @check_width [ $lsb:tt .. $msb:tt $( - $offset:literal )? ] $field:ident
+        : $underlying_type:ty as $interface_type:ty
+    ] => {
+        $crate::bitfield_accessors! {
+            @assert_interface_width ($msb - $lsb) $field : $interface_type
+        }
+    };
+
+    [
+        @check_width [ $lsb:tt ..= $msb:tt $( - $offset:literal )? ] $field:ident
+        : $underlying_type:ty as $interface_type:ty
+    ] => {
+        $crate::bitfield_accessors! {
+            @assert_interface_width ($msb + 1 - $lsb) $field : $interface_type
+        }
+    };
+
+    [
+        @check_width [ $lsb:tt .. $msb:tt $( - $offset:literal )? ]
+        $field:ident : $field_type:ty
+    ] => {
+        $crate::bitfield_accessors! { @assert_width ($msb - $lsb) $field : $field_type }
+    };
+
+    [
+        @check_width [ $lsb:tt ..= $msb:tt $( - $offset:literal )? ]
+        $field:ident : $field_type:ty
+    ] => {
+        $crate::bitfield_accessors! {
+            @assert_width ($msb + 1 - $lsb) $field : $field_type
+        }
+    };
+
+    [ @check_width $( $rest:tt )* ] => {};
+
+    [
+        @assert_width $width:tt $field:ident : $field_type:ty
+    ] => {
+        ::core::assert!(
+            <$field_type as $crate::FieldStorage>::MIN_BITS <= $width,
+            ::core::concat!(
+                "field `", ::core::stringify!($field), "` is too narrow for the fields ",
+                "of its type",
+            ),
+        );
+        ::core::assert!(
+            $width <= <$field_type as $crate::FieldStorage>::MAX_BITS,
+            ::core::concat!(
+                "field `", ::core::stringify!($field), "` is too wide for its type",
+            ),
+        );
+    };
+
+    // An interface type whose width is known, like a nested bitfield, must fit in the
+    // field, or converting it to the underlying type would silently drop its high bits
+    [
+        @assert_interface_width $width:tt $field:ident : $interface_type:ty
+    ] => {
+        {
+            #[allow(unused_imports)]
+            use $crate::InterfaceWidthFallback as _;
+            ::core::assert!(
+                <$crate::InterfaceWidth<$interface_type>>::MIN_BITS <= $width,
+                ::core::concat!(
+                    "field `", ::core::stringify!($field), "` is too narrow for the ",
+                    "values of its interface type",
+                ),
+            );
+        }
+    };
+
+    // A syntax error in the list of fields. Walk the fields to find the first one that
+    // is malformed.
+    [
+        @diagnose
+        $( #[$meta:meta] )*
+        [ $( $range:tt )* ]
+        $vis:vis $field:ident
+        $( : $underlying_type:ty $( as $interface_type:ty )? $( where $check:expr )? )?
+        , $( $rest:tt )+
+    ] => {
+        $crate::bitfield_accessors! { @diagnose $( $rest )+ }
+    };
+
+    [
+        @diagnose
+        $( #[$meta:meta] )*
+        [ $( $range:tt )* ]
+        $vis:vis $field:ident
+        : $( , $( $rest:tt )* )?
+    ] => {
+        ::core::compile_error!(::core::concat!(
+            "expected a type after `", ::core::stringify!($field), ":`",
+        ));
+    };
+
+    [
+        @diagnose
+        $( #[$meta:meta] )*
+        [ $( $range:tt )* ]
+        $vis:vis $field:ident
+        : $( $rest:tt )+
+    ] => {
+        ::core::compile_error!(::core::concat!(
+            "expected `,` after the type of field `", ::core::stringify!($field), "`",
+        ));
+    };
+
+    [
+        @diagnose
+        $( #[$meta:meta] )*
+        [ $( $range:tt )* ]
+        $vis:vis $field:ident
+        $unexpected:tt $( $rest:tt )*
+    ] => {
+        ::core::compile_error!(::core::concat!(
+            "expected `,` or `:` after field `", ::core::stringify!($field), "`, found `",
+            ::core::stringify!($unexpected), "`",
+        ));
+    };
+
+    [ @diagnose , $( $rest:tt )* ] => {
+        ::core::compile_error!(
+            "unexpected `,`; fields must be separated by exactly one comma",
+        );
+    };
+
+    [ @diagnose $( $rest:tt )* ] => {
+        ::core::compile_error!(::core::concat!(
+            "expected a field like `[0..4] pub name: u8`, found `",
+            ::core::stringify!($( $rest )*), "`",
+        ));
+    };
+
+    // Anything else is a syntax error in the list of fields. Still define the helper
+    // methods so that the trait implementations don't report errors of their own.
+    [ @helpers $src:tt $( $tokens:tt )+ ] => {
+        $crate::bitfield_accessors! { @diagnose $( $tokens )+ }
+        $crate::bitfield_accessors! { @helpers $src }
+    };
+
+    [ $( $tokens:tt )* ] => {
+        $crate::bitfield_accessors! { @helpers [] $( $tokens )* }
+    };
+}
+
+/// Define an extension trait with getters and setters for certain bit ranges of a type
+/// from another crate, like the register values of a peripheral access crate.
+///
+/// [`bitfield_accessors`] requires the [`Bitfield`] trait, which can't be implemented
+/// for a type from another crate. This only requires the type to be [`Copy`] and
+/// convertible to and from the underlying type with [`Into`] and [`From`]. The trait
+/// has a getter, a `set_` method, and a `with_` method for each field, like the
+/// accessors of [`bitfield`], and it is implemented for the type. Fields are declared
+/// like in a trait using [`bitfield_accessors`], without visibility or constraints.
+///
+/// ```
+/// # use tartan_bitfield::bitfield_ext;
+/// mod pac {
+///     #[derive(Debug, Clone, Copy, PartialEq)]
+///     pub struct CrValue(u32);
+///
+///     impl From<u32> for CrValue {
+///         fn from(bits: u32) -> Self {
+///             Self(bits)
+///         }
+///     }
+///
+///     impl From<CrValue> for u32 {
+///         fn from(value: CrValue) -> Self {
+///             value.0
+///         }
+///     }
+/// }
+///
+/// bitfield_ext! {
+///     pub trait CrExt(u32) for pac::CrValue {
+///         [0]     enable,
+///         [4..8]  mode,
+///         [8..16] divider: u8,
+///     }
+/// }
+///
+/// let mut cr = pac::CrValue::from(0x0431);
+/// assert!(cr.enable());
+/// assert_eq!((cr.mode(), cr.divider()), (3, 4));
+/// cr.set_divider(0x20);
+/// assert_eq!(u32::from(cr.with_enable(false)), 0x2030);
+/// ```
+#[macro_export]
+macro_rules! bitfield_ext {
+    [
+        $( #[$meta:meta] )*
+        $vis:vis trait $trait:ident($underlying_type:ty) for $type:ty {
+            $($body:tt)*
+        }
+    ] => {
+        $crate::bitfield_ext! {
+            @types [ $( #[$meta] )* $vis trait $trait($underlying_type) for $type ]
+            [ $($body)* ] [] { $($body)* }
+        }
+    };
+
+    // The type of each field is worked out one at a time, like the accessors do
+    [
+        @types $header:tt $body:tt [ $($fields:tt)* ] {
+            $( #[$meta:meta] )* [ $bit:literal ] $field:ident $( , $($rest:tt)* )?
+        }
+    ] => {
+        $crate::bitfield_ext! {
+            @types $header $body [
+                $($fields)* ( $( #[$meta] )* [ $bit ] $field: bool )
+            ] { $( $($rest)* )? }
+        }
+    };
+
+    [
+        @types $header:tt $body:tt [ $($fields:tt)* ] {
+            $( #[$meta:meta] )* [ $lsb:literal .. $msb:literal ] $field:ident
+            $( , $($rest:tt)* )?
+        }
+    ] => {
+        $crate::bitfield_ext! {
+            @types $header $body [
+                $($fields)*
+                (
+                    $( #[$meta] )* [ $lsb .. $msb ] $field
+                    : $crate::DefaultField<{ ($msb - $lsb) as usize }>
+                )
+            ] { $( $($rest)* )? }
+        }
+    };
+
+    [
+        @types $header:tt $body:tt [ $($fields:tt)* ] {
+            $( #[$meta:meta] )* [ $lsb:literal ..= $msb:literal ] $field:ident
+            $( , $($rest:tt)* )?
+        }
+    ] => {
+        $crate::bitfield_ext! {
+            @types $header $body [
+                $($fields)*
+                (
+                    $( #[$meta] )* [ $lsb ..= $msb ] $field
+                    : $crate::DefaultField<{ ($msb + 1 - $lsb) as usize }>
+                )
+            ] { $( $($rest)* )? }
+        }
+    };
+
+    [
+        @types $header:tt $body:tt [ $($fields:tt)* ] {
+            $( #[$meta:meta] )* [ $( $range:tt )* ] $field:ident
+            : $underlying_type:ty $( as $interface_type:ty )?
+            $( , $($rest:tt)* )?
+        }
+    ] => {
+        $crate::bitfield_ext! {
+            @types $header $body [
+                $($fields)*
+                (
+                    $( #[$meta] )* [ $( $range )* ] $field
+                    : $crate::bitfield_accessors!(
+                        @interface $underlying_type $( as $interface_type )?
+                    )
+                )
+            ] { $( $($rest)* )? }
+        }
+    };
+
+    // The methods are implemented by a private bitfield type with the same fields
+    [
+        @types
+        [
+            $( #[$meta:meta] )*
+            $vis:vis trait $trait:ident($underlying_type:ty) for $type:ty
+        ]
+        [ $($body:tt)* ]
+        [
+            $( (
+                $( #[$field_meta:meta] )* [ $( $range:tt )* ] $field:ident
+                : $field_type:ty
+            ) )*
+        ]
+        {}
+    ] => {
+        $crate::paste! {
+            $( #[$meta] )*
+            $vis trait $trait: Copy {
+                $(
+                    $( #[$field_meta] )*
+                    #[doc = ""]
+                    #[doc = $crate::bitfield_accessors!(@range_doc [ $( $range )* ])]
+                    fn $field(&self) -> $field_type;
+
+                    $( #[$field_meta] )*
+                    #[doc = ""]
+                    #[doc = $crate::bitfield_accessors!(@range_doc [ $( $range )* ])]
+                    fn [< set_ $field >](&mut self, value: $field_type);
+
+                    $( #[$field_meta] )*
+                    #[doc = ""]
+                    #[doc = $crate::bitfield_accessors!(@range_doc [ $( $range )* ])]
+                    #[must_use]
+                    fn [< with_ $field >](&self, value: $field_type) -> Self;
+                )*
+            }
+
+            const _: () = {
+                $crate::bitfield! {
+                    struct [< $trait View >]($underlying_type) {
+                        $($body)*
+                    }
+                }
+
+                impl $trait for $type {
+                    $(
+                        #[inline(always)]
+                        fn $field(&self) -> $field_type {
+                            let value: $underlying_type =
+                                ::core::convert::Into::into(*self);
+                            [< $trait View >](value).$field()
+                        }
+
+                        #[inline(always)]
+                        fn [< set_ $field >](&mut self, value: $field_type) {
+                            *self = self.[< with_ $field >](value);
+                        }
+
+                        #[inline(always)]
+                        fn [< with_ $field >](&self, value: $field_type) -> Self {
+                            let bits: $underlying_type =
+                                ::core::convert::Into::into(*self);
+                            let view = [< $trait View >](bits).[< with_ $field >](value);
+                            ::core::convert::From::from(view.0)
+                        }
+                    )*
+                }
+            };
+        }
+    };
+}
+
+/// Define the svd2rust-style field writers of [`svd::W`] for a bitfield type, as a
+/// trait with a method for each field.
+///
+/// The bitfield must be defined with [`bitfield`] earlier in the same module or one of
+/// its parents, since its fields are read from the group of the same name. See
+/// [`bitfield_fields`]. Each method returns an [`svd::FieldWriter`] that sets the field
+/// with its `set_` method, so `w.en().set_bit()` works like it does on an svd2rust
+/// writer wherever the trait is in scope:
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, svd_writer};
+/// use tartan_bitfield::svd::Reg;
+/// use tartan_bitfield::uint::u2;
+///
+/// bitfield! {
+///     pub struct Ccr(u16) {
+///         [0]     pub en,
+///         [1..3]  pub pl: u2,
+///         [8..16] pub count: u8,
+///     }
+/// }
+///
+/// svd_writer! {
+///     /// Field writers for `Ccr`
+///     pub trait CcrW for Ccr
+/// }
+///
+/// let ccr = Reg::<Ccr, u16>::default();
+/// ccr.write(|w| w.en().set_bit().pl().bits(u2::new(2)).count().bits(0x40));
+/// assert_eq!(ccr.read(), Ccr(0x4005));
+/// ```
+#[macro_export]
+macro_rules! svd_writer {
+    [ $( #[$meta:meta] )* $vis:vis trait $name:ident for $struct:ident ] => {
+        $struct! {
+            @include [ @svd_writer [ $( #[$meta] )* $vis trait $name for $struct ] ] [] {}
+        }
+    };
+
+    [
+        @fields [ $( #[$meta:meta] )* $vis:vis trait $name:ident for $struct:ident ] {
+            $(
+                $( #[$field_meta:meta] )*
+                [ $( $range:tt )* ]
+                $field_vis:vis $field:ident
+                $(
+                    : $underlying_type:ty $( as $interface_type:ty )?
+                    $( where $check:expr )?
+                )?
+            ),*
+            $(,)?
+        }
+    ] => {
+        $( #[$meta] )*
+        $vis trait $name {
+            $(
+                $( #[$field_meta] )*
+                fn $field(&mut self) -> $crate::svd::FieldWriter<
+                    '_,
+                    $struct,
+                    $crate::svd_writer!(
+                        @type [ $( $range )* ]
+                        $( : $underlying_type $( as $interface_type )? )?
+                    ),
+                >;
+            )*
+        }
+
+        impl $name for $crate::svd::W<$struct> {
+            $(
+                $( #[$field_meta] )*
+                #[inline(always)]
+                fn $field(&mut self) -> $crate::svd::FieldWriter<
+                    '_,
+                    $struct,
+                    $crate::svd_writer!(
+                        @type [ $( $range )* ]
+                        $( : $underlying_type $( as $interface_type )? )?
+                    ),
+                > {
+                    $crate::paste! {
+                        $crate::svd::FieldWriter::new(self, $struct::[< set_ $field >])
+                    }
+                }
+            )*
+        }
+    };
+
+    // Malformed fields are reported by `bitfield` instead
+    [ @fields $( $rest:tt )* ] => {};
+
+    // The type of a field's value, like the setter's argument
+    [ @type [ $( $range:tt )* ] : $underlying_type:ty $( as $interface_type:ty )? ] => {
+        $crate::bitfield_accessors!(@interface $underlying_type $( as $interface_type )?)
+    };
+
+    [ @type [ $( $range:tt )* ] ] => {
+        $crate::DefaultField<{
+            ($crate::bitfield_accessors!(@msb $( $range )*)
+                - $crate::bitfield_accessors!(@lsb $( $range )*)) as usize
+        }>
+    };
+}
+
+/// Generate unit tests that check the accessors of bitfield types against their
+/// declared layouts.
+///
+/// For each field of each type, the tests check that the bit range fits in the
+/// underlying type, that the setter only modifies bits in the range, and that all ones,
+/// walking ones, and walking zeros survive a round trip through the getter and setter.
+/// This catches typos like overlapping ranges in the wrong field or an interface type
+/// whose conversions drop bits.
+///
+/// The types must be defined with [`bitfield`] in the same module as the tests or one
+/// of its parents. One test is generated per type, named after the type in snake case
+/// with a `_field_layout` suffix.
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Control(u32) {
+///         [0..4]  pub mode: u8,
+///         [4]     pub enable,
+///         [8..16] pub count: u8,
+///     }
+/// }
+///
+/// #[cfg(test)]
+/// mod tests {
+///     use super::*;
+///     use tartan_bitfield::bitfield_tests;
+///
+///     bitfield_tests!(Control);
+/// }
+/// ```
+#[macro_export]
+macro_rules! bitfield_tests {
+    [ $( $type:ident ),* $(,)? ] => {
+        $(
+            $crate::paste! {
+                #[test]
+                fn [< $type:snake _field_layout >]() {
+                    $type::visit_field_accessors(|name, lsb, msb, copy_field| {
+                        $crate::assert_field_layout(name, lsb, msb, copy_field);
+                    });
+                }
+            }
+        )*
+    };
+}
+
+/// Generate [Kani](https://github.com/model-checking/kani) proof harnesses that verify
+/// the accessors of bitfield types against their declared layouts.
+///
+/// This makes the same checks as [`bitfield_tests`], but for every possible value of
+/// the field and the surrounding bits, rather than a fixed set of patterns. Each type
+/// gets one harness, named after the type in snake case with a `_field_layout_proof`
+/// suffix. The harnesses are only compiled when running `cargo kani`, which sets
+/// `cfg(kani)`, so there is no need to depend on Kani otherwise. To silence warnings
+/// about the unknown `cfg` in normal builds, add this to `Cargo.toml`:
+///
+/// ```toml
+/// [lints.rust]
+/// unexpected_cfgs = { level = "warn", check-cfg = ["cfg(kani)"] }
+/// ```
+///
+/// Like [`bitfield_tests`], the types must be defined in the same module or one of its
+/// parents.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, bitfield_proofs};
+/// bitfield! {
+///     pub struct Control(u32) {
+///         [0..4]  pub mode: u8,
+///         [4]     pub enable,
+///         [8..16] pub count: u8,
+///     }
+/// }
 ///
-/// let g = OtherFields(0xabcd_1234);
-/// assert_eq!(g.a(), 0x34); // has accessors from CommonFields
-/// //assert_eq!(g.y(), true); // COMPILE ERROR: no accessors from SomeFields
-/// assert_eq!(g.z(), false); // has accessors from OtherFields
+/// bitfield_proofs!(Control);
 /// ```
 #[macro_export]
-macro_rules! bitfield_accessors {
-    [
-        $(
-            $( #[$meta:meta] )*
-            [ $( $range:tt )* ]
-            $vis:vis $field:ident
-            $( : $underlying_type:ty $( as $interface_type:ty )? )?
-        ),*
-        $(,)?
-    ] => {
+macro_rules! bitfield_proofs {
+    [ $( $type:ident ),* $(,)? ] => {
         $(
-            $crate::bitfield_accessors! {
-                @field getter
-                $( #[$meta] )*
-                [ $( $range )* ]
-                $vis $field
-                $( : $underlying_type $( as $interface_type )? )?
+            $crate::paste! {
+                #[cfg(kani)]
+                #[kani::proof]
+                fn [< $type:snake _field_layout_proof >]() {
+                    let background = <$type as $crate::Bitfield<_>>::new(kani::any());
+                    let pattern = <$type as $crate::Bitfield<_>>::new(kani::any());
+                    $type::visit_field_accessors(|name, lsb, msb, copy_field| {
+                        $crate::assert_field_copy(
+                            name, lsb, msb, copy_field, background, pattern,
+                        );
+                    });
+                }
             }
         )*
+    };
+}
 
-        $(
-            $crate::bitfield_accessors! {
-                @field setter
-                $( #[$meta] )*
-                [ $( $range )* ]
-                $vis $field
-                $( : $underlying_type $( as $interface_type )? )?
+/// Define methods that increment integer fields of a bitfield, for sequence numbers and
+/// retry counters.
+///
+/// For each listed field, `increment_<field>` adds one and wraps around to zero past the
+/// largest value that fits in the field, and `increment_<field>_saturating` stays at
+/// that value instead. Only the bits of the field change, regardless of its type. The
+/// methods have the given visibility.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, bitfield_counters};
+/// bitfield! {
+///     pub struct Frame(u16) {
+///         [0..4]  pub seq,
+///         [4..6]  pub retries,
+///         [8..16] pub payload: u8,
+///     }
+/// }
+///
+/// bitfield_counters!(Frame { pub seq, pub retries });
+///
+/// let mut frame = Frame::default().with_seq(14).with_payload(0xff);
+/// frame.increment_seq();
+/// assert_eq!(frame.seq(), 15);
+/// frame.increment_seq();
+/// assert_eq!(frame.seq(), 0);
+///
+/// for _ in 0..5 {
+///     frame.increment_retries_saturating();
+/// }
+/// assert_eq!(frame.retries(), 3);
+/// assert_eq!(frame.payload(), 0xff);
+/// ```
+#[macro_export]
+macro_rules! bitfield_counters {
+    [ $type:ident { $( $vis:vis $field:ident ),* $(,)? } ] => {
+        $crate::paste! {
+            impl $type {
+                $(
+                    #[doc = concat!(
+                        "Add one to the `", stringify!($field), "` field, wrapping ",
+                        "around to zero if it overflows",
+                    )]
+                    #[allow(dead_code)]
+                    $vis fn [< increment_ $field >](&mut self) {
+                        let range = Self::[< $field:upper _RANGE >];
+                        *self = $crate::increment_field(
+                            *self, range.start, range.end, false,
+                        );
+                    }
+
+                    #[doc = concat!(
+                        "Add one to the `", stringify!($field), "` field, unless it is ",
+                        "already at its maximum value",
+                    )]
+                    #[allow(dead_code)]
+                    $vis fn [< increment_ $field _saturating >](&mut self) {
+                        let range = Self::[< $field:upper _RANGE >];
+                        *self = $crate::increment_field(
+                            *self, range.start, range.end, true,
+                        );
+                    }
+                )*
             }
-        )*
+        }
+    };
+}
 
-        /// Print this object's bitfield values. Helper method for `Debug`
-        /// implementations.
-        fn fmt_fields(&self, f: &mut ::core::fmt::DebugStruct) {
-            $(
-                $(#[$meta])*
-                f.field(stringify!($field), &self.$field());
-            )*
+/// Define methods that maintain a single-bit field as the parity of a range of bits.
+///
+/// The parity is either `even`, where the field is set if an odd number of bits in the
+/// range are set, so that the range and the parity bit together have an even number of
+/// set bits, or `odd`, which is the opposite. The range is given the same way as in the
+/// [`bitfield`] macro, and it should not include the parity bit itself.
+///
+/// This generates a `recompute_<field>` method that sets the field from the current
+/// value of the range, and a `<field>_ok` method that checks it, so a bitfield can have
+/// more than one parity bit. Both have the given visibility.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, bitfield_parity};
+/// bitfield! {
+///     pub struct SerialWord(u16) {
+///         [0..8] pub data: u8,
+///         [8]    pub parity,
+///     }
+/// }
+///
+/// bitfield_parity!(SerialWord { pub parity: even [0..8] });
+///
+/// let mut word = SerialWord::default().with_data(0b1011_0000);
+/// assert!(!word.parity_ok());
+/// word.recompute_parity();
+/// assert!(word.parity() && word.parity_ok());
+///
+/// word.set_data(0b1011_0001);
+/// assert!(!word.parity_ok());
+/// ```
+#[macro_export]
+macro_rules! bitfield_parity {
+    [ $type:ident { $vis:vis $field:ident : even [ $( $range:tt )* ] $(,)? } ] => {
+        $crate::bitfield_parity! { @impl $type $vis $field false [ $( $range )* ] }
+    };
+
+    [ $type:ident { $vis:vis $field:ident : odd [ $( $range:tt )* ] $(,)? } ] => {
+        $crate::bitfield_parity! { @impl $type $vis $field true [ $( $range )* ] }
+    };
+
+    [ @impl $type:ident $vis:vis $field:ident $odd:literal [ $( $range:tt )* ] ] => {
+        $crate::paste! {
+            impl $type {
+                /// The expected value of the parity bit for the current value
+                fn [< expected_ $field >](&self) -> bool {
+                    $crate::odd_parity(
+                        *self,
+                        $crate::bitfield_accessors!(@lsb $( $range )*),
+                        $crate::bitfield_accessors!(@msb $( $range )*),
+                    ) != $odd
+                }
+
+                #[doc = concat!(
+                    "Set the `", stringify!($field), "` bit from the parity of bits ",
+                    stringify!($( $range )*),
+                )]
+                #[allow(dead_code)]
+                $vis fn [< recompute_ $field >](&mut self) {
+                    self.[< set_ $field >](self.[< expected_ $field >]());
+                }
+
+                #[doc = concat!(
+                    "True if the `", stringify!($field), "` bit matches the parity of ",
+                    "bits ", stringify!($( $range )*),
+                )]
+                #[allow(dead_code)]
+                #[must_use]
+                $vis fn [< $field _ok >](&self) -> bool {
+                    self.$field() == self.[< expected_ $field >]()
+                }
+            }
         }
     };
+}
 
-    // Special case for single-bit boolean fields
+/// Define methods that maintain a field as a checksum of the rest of the value.
+///
+/// The checksum is computed by the given function, which takes a copy of the bitfield
+/// with the checksum field cleared to its default value and returns a value of the
+/// field's type. This generates a `finalize_<field>` method that stores the checksum in
+/// the field, and a `verify_<field>` method that checks it. Both have the given
+/// visibility.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, bitfield_checksum};
+/// bitfield! {
+///     pub struct Header(u16) {
+///         [0..4]   pub crc,
+///         [4..8]   pub kind,
+///         [8..16]  pub length: u8,
+///     }
+/// }
+///
+/// // Sum of the nibbles, modulo 16
+/// fn nibble_sum(header: Header) -> u8 {
+///     let mut value = header.0;
+///     let mut sum = 0;
+///     while value != 0 {
+///         sum += value & 0xf;
+///         value >>= 4;
+///     }
+///     (sum & 0xf) as u8
+/// }
+///
+/// bitfield_checksum!(Header { pub crc: nibble_sum });
+///
+/// let mut header = Header::default().with_kind(3).with_length(0x2a);
+/// assert!(!header.verify_crc());
+/// header.finalize_crc();
+/// assert_eq!(header, Header(0x2a3f));
+/// assert!(header.verify_crc());
+/// assert!(!header.with_length(0x2b).verify_crc());
+/// ```
+#[macro_export]
+macro_rules! bitfield_checksum {
+    [ $type:ident { $vis:vis $field:ident : $checksum:expr $(,)? } ] => {
+        $crate::paste! {
+            impl $type {
+                /// The value with the checksum field cleared, as the input to the
+                /// checksum
+                fn [< without_ $field >](&self) -> Self {
+                    let mut rest = *self;
+                    rest.[< set_ $field >](::core::default::Default::default());
+                    rest
+                }
+
+                #[doc = concat!(
+                    "Compute the checksum of the value and store it in the `",
+                    stringify!($field), "` field",
+                )]
+                #[allow(dead_code)]
+                $vis fn [< finalize_ $field >](&mut self) {
+                    let checksum = ($checksum)(self.[< without_ $field >]());
+                    self.[< set_ $field >](checksum);
+                }
+
+                #[doc = concat!(
+                    "True if the `", stringify!($field), "` field matches the checksum ",
+                    "of the rest of the value",
+                )]
+                #[allow(dead_code)]
+                #[must_use]
+                $vis fn [< verify_ $field >](&self) -> bool {
+                    self.$field() == ($checksum)(self.[< without_ $field >]())
+                }
+            }
+        }
+    };
+}
+
+/// Define functions to parse and emit messages made of a bitfield header followed by a
+/// payload, where a field of the header holds the length of the payload in bytes.
+///
+/// The header is read and written in the given [`ByteOrder`], taking as many bytes as
+/// its underlying type. This generates a `parse` function that splits a message into its
+/// header and payload, ignoring any bytes after the payload, and an `emit` method that
+/// writes the header and a payload to a buffer, storing the length of the payload in the
+/// field. `emit` returns the number of bytes written. Both have the given visibility and
+/// return a [`FrameError`] if the message doesn't fit.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, bitfield_frame, ByteOrder, FrameError};
+/// bitfield! {
+///     pub struct Header(u16) {
+///         [0..4]   pub kind,
+///         [4..16]  pub length: u16,
+///     }
+/// }
+///
+/// bitfield_frame!(Header { pub length: ByteOrder::Big });
+///
+/// let mut buffer = [0; 8];
+/// let header = Header::default().with_kind(2);
+/// assert_eq!(header.emit(b"hello", &mut buffer), Ok(7));
+/// assert_eq!(buffer, [0x00, 0x52, b'h', b'e', b'l', b'l', b'o', 0]);
+///
+/// let (header, payload) = Header::parse(&buffer).unwrap();
+/// assert_eq!(header.kind(), 2);
+/// assert_eq!(header.length(), 5);
+/// assert_eq!(payload, b"hello");
+///
+/// assert_eq!(Header::parse(&buffer[..6]), Err(FrameError::Truncated));
+/// assert_eq!(header.emit(&[0; 7], &mut buffer), Err(FrameError::BufferTooSmall));
+/// ```
+#[macro_export]
+macro_rules! bitfield_frame {
+    [ $type:ident { $vis:vis $field:ident : $order:expr $(,)? } ] => {
+        $crate::paste! {
+            impl $type {
+                #[doc = concat!(
+                    "Split a message into its header and the payload, whose length is ",
+                    "given by the `", stringify!($field), "` field of the header",
+                )]
+                ///
+                /// # Errors
+                ///
+                /// Returns an error if the message is shorter than the header and the
+                /// payload.
+                #[allow(dead_code)]
+                $vis fn parse(
+                    bytes: &[u8],
+                ) -> ::core::result::Result<(Self, &[u8]), $crate::FrameError> {
+                    $crate::parse_frame(bytes, $order, Self::[< $field:upper _RANGE >])
+                }
+
+                #[doc = concat!(
+                    "Write the header followed by the payload to `dest`, storing the ",
+                    "length of the payload in the `", stringify!($field), "` field, and ",
+                    "return the number of bytes written",
+                )]
+                ///
+                /// # Errors
+                ///
+                /// Returns an error without modifying `dest` if the length of the payload
+                /// doesn't fit in the field, or if `dest` is too small for the message.
+                #[allow(dead_code)]
+                $vis fn emit(
+                    &self,
+                    payload: &[u8],
+                    dest: &mut [u8],
+                ) -> ::core::result::Result<usize, $crate::FrameError> {
+                    $crate::emit_frame(
+                        *self,
+                        payload,
+                        dest,
+                        $order,
+                        Self::[< $field:upper _RANGE >],
+                    )
+                }
+            }
+        }
+    };
+}
+
+/// Define functions to get and set a logical field whose bits are split across fields
+/// of several bitfields, like an address with its high bits in one register and its low
+/// bits in another.
+///
+/// The parts are listed from most significant to least significant, and each one takes
+/// as many bits as its field is wide. This generates a getter named after the logical
+/// field, which joins the parts, and a setter prefixed with `set_`, which splits a value
+/// among them. Both take one argument per part, named after the part's field, and the
+/// value has the given primitive integer type.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, bitfield_scattered};
+/// bitfield! {
+///     pub struct ConfigHi(u8) {
+///         [0..2] pub addr_high,
+///         [7]    pub enable,
+///     }
+/// }
+///
+/// bitfield! {
+///     pub struct ConfigLo(u8) {
+///         [0..8] pub addr_low: u8,
+///     }
+/// }
+///
+/// bitfield_scattered! {
+///     /// The 10-bit device address
+///     pub fn address: u16 = ConfigHi::addr_high, ConfigLo::addr_low;
+/// }
+///
+/// let mut hi = ConfigHi(0x82);
+/// let mut lo = ConfigLo(0x34);
+/// assert_eq!(address(hi, lo), 0x234);
+///
+/// set_address(&mut hi, &mut lo, 0x1ab);
+/// assert_eq!((hi, lo), (ConfigHi(0x81), ConfigLo(0xab)));
+/// ```
+#[macro_export]
+macro_rules! bitfield_scattered {
     [
-        @field getter
         $( #[$meta:meta] )*
-        [ $bit:literal ]
-        $vis:vis $field:ident
+        $vis:vis fn $name:ident : $type:ty =
+            $( $part_type:ident :: $part:ident ),+ $(,)? ;
     ] => {
         $crate::paste! {
             $( #[$meta] )*
-            $vis fn $field(&self) -> bool {
-                $crate::get_bit(<Self as $crate::Bitfield<_>>::value(*self), $bit)
+            #[allow(dead_code)]
+            $vis fn $name( $( $part: $part_type ),+ ) -> $type {
+                let mut value = 0_u128;
+                $(
+                    let range = $part_type::[< $part:upper _RANGE >];
+                    let width = u32::from(range.end - range.start);
+                    value = value.checked_shl(width).unwrap_or(0)
+                        | $crate::raw_field($part, range.start, range.end);
+                )+
+                $crate::PackedBits::from_u128(value)
+            }
+
+            #[doc = concat!("Set the parts of the `", stringify!($name), "` field")]
+            #[allow(dead_code)]
+            $vis fn [< set_ $name >]( $( $part: &mut $part_type, )+ value: $type ) {
+                let value = $crate::PackedBits::into_u128(value);
+                let mut shift = 0 $( + u32::from(
+                    $part_type::[< $part:upper _RANGE >].end
+                        - $part_type::[< $part:upper _RANGE >].start
+                ) )+;
+                $(
+                    let range = $part_type::[< $part:upper _RANGE >];
+                    shift -= u32::from(range.end - range.start);
+                    let bits = value.checked_shr(shift).unwrap_or(0);
+                    *$part = $crate::with_raw_field(*$part, range.start, range.end, bits);
+                )+
             }
         }
     };
+}
 
-    // Special case for single-bit boolean fields
+/// The bit range of a field, as a [`Range<u8>`](core::ops::Range) of bit numbers.
+///
+/// This refers to the `_RANGE` constant that the [`bitfield`] and [`bitfield_accessors`]
+/// macros generate for each field, named after the field in upper case.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, field_range};
+/// bitfield! {
+///     pub struct Example(u32) {
+///         [0..4]   pub a,
+///         [6..=17] pub b: u16,
+///         [25]     pub d,
+///     }
+/// }
+///
+/// assert_eq!(field_range!(Example::a), 0..4);
+/// assert_eq!(field_range!(Example::b), 6..18);
+/// assert_eq!(Example::D_RANGE, 25..26);
+///
+/// const B_WIDTH: u8 = field_range!(Example::b).end - field_range!(Example::b).start;
+/// assert_eq!(B_WIDTH, 12);
+/// ```
+#[macro_export]
+macro_rules! field_range {
+    [ @path [ $( $path:tt )* ] $segment:ident :: $( $rest:tt )+ ] => {
+        $crate::field_range! { @path [ $( $path )* $segment :: ] $( $rest )+ }
+    };
+
+    [ @path [ $( $path:tt )* ] $field:ident ] => {
+        $crate::paste! { $( $path )* [< $field:upper _RANGE >] }
+    };
+
+    [ $( $tokens:tt )+ ] => {
+        $crate::field_range! { @path [] $( $tokens )+ }
+    };
+}
+
+/// Assert at compile time that a field has the given bit range.
+///
+/// This is useful when a layout is part of an ABI or protocol, so that a change to a
+/// field's position or width fails the build instead of silently changing behavior.
+/// The range is given the same way as in the [`bitfield`] macro, and refers to the
+/// constants described in [`field_range`].
+///
+/// ```
+/// # use tartan_bitfield::{assert_field_layout, bitfield};
+/// bitfield! {
+///     pub struct Example(u32) {
+///         [0..4]   pub a,
+///         [6..=17] pub b: u16,
+///         [25]     pub d,
+///     }
+/// }
+///
+/// assert_field_layout!(Example::a, 0..4);
+/// assert_field_layout!(Example::b, 6..=17);
+/// assert_field_layout!(Example::d, 25);
+/// ```
+///
+/// ```compile_fail
+/// # use tartan_bitfield::{assert_field_layout, bitfield};
+/// # bitfield! {
+/// #     pub struct Example(u32) {
+/// #         [0..4] pub a,
+/// #     }
+/// # }
+/// // ERROR: field `Example::a` is not at bits 0..5
+/// assert_field_layout!(Example::a, 0..5);
+/// ```
+#[macro_export]
+macro_rules! assert_field_layout {
+    [ $first:ident $( :: $rest:ident )* , $bit:literal ] => {
+        $crate::assert_field_layout! {
+            @check [ $first $( :: $rest )* ] ($bit, $bit + 1) stringify!($bit)
+        }
+    };
+
+    [ $first:ident $( :: $rest:ident )* , $lsb:literal .. $msb:literal ] => {
+        $crate::assert_field_layout! {
+            @check [ $first $( :: $rest )* ] ($lsb, $msb)
+            concat!(stringify!($lsb), "..", stringify!($msb))
+        }
+    };
+
+    [ $first:ident $( :: $rest:ident )* , $lsb:literal ..= $msb:literal ] => {
+        $crate::assert_field_layout! {
+            @check [ $first $( :: $rest )* ] ($lsb, $msb + 1)
+            concat!(stringify!($lsb), "..=", stringify!($msb))
+        }
+    };
+
+    [
+        @check [ $first:ident $( :: $rest:ident )* ] ($lsb:expr, $msb:expr) $range:expr
+    ] => {
+        const _: () = {
+            let range = $crate::field_range!($first $( :: $rest )*);
+            ::core::assert!(
+                range.start == $lsb && range.end == $msb,
+                concat!(
+                    "field `",
+                    stringify!($first),
+                    $( "::", stringify!($rest), )*
+                    "` is not at bits ",
+                    $range,
+                ),
+            );
+        };
+    };
+}
+
+/// Build a `(mask, value)` pair of bitfields from named field values, for use with the
+/// generated `matches_masked` method.
+///
+/// The mask has all the bits of the named fields set, and the value has the given
+/// values in those fields. Other fields are zero in both.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, bitfield_pattern};
+/// bitfield! {
+///     pub struct Instruction(u32) {
+///         [0..7]   pub opcode,
+///         [7..12]  pub rd,
+///         [12..15] pub funct3,
+///         [15..20] pub rs1,
+///         [20..32] pub imm: u16,
+///     }
+/// }
+///
+/// let (mask, value) = bitfield_pattern!(Instruction { opcode: 0x13, funct3: 0 });
+/// assert_eq!(mask, Instruction(0x0000_707f));
+///
+/// let addi = Instruction(0x02a0_0093); // addi x1, x0, 42
+/// assert!(addi.matches_masked(mask, value));
+/// assert!(!addi.with_funct3(4).matches_masked(mask, value));
+/// ```
+#[macro_export]
+macro_rules! bitfield_pattern {
+    [ $type:path { $( $field:ident : $value:expr ),* $(,)? } ] => {
+        {
+            let ones: $type = $crate::bitfield_from_bits(::core::iter::repeat(true));
+            let mut mask = <$type as ::core::default::Default>::default();
+            let mut value = mask;
+            $(
+                $crate::paste! {
+                    mask.[< set_ $field >](ones.$field());
+                    value.[< set_ $field >]($value);
+                }
+            )*
+            let _ = ones;
+            (mask, value)
+        }
+    };
+}
+
+/// Define an enum of several bitfield layouts that share an underlying type, and decode
+/// raw values into the right one by their discriminating bits.
+///
+/// Each variant holds one bitfield type, and has a mask and value: a raw value is
+/// decoded as the first variant where the bits in `mask` are equal to `value`. The
+/// generated `decode` function returns `None` if no variant matches, and `value` returns
+/// the underlying value of any variant. The underlying type must be a primitive integer.
+/// It's a compile error if a value has bits set outside its mask.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, bitfield_dispatch};
+/// bitfield! {
+///     pub struct TableDescriptor(u64) {
+///         [12..48] pub next_table: u64,
+///         [63]     pub non_secure,
+///     }
+/// }
+///
+/// bitfield! {
+///     pub struct BlockDescriptor(u64) {
+///         [2..5]   pub attr_index: u8,
+///         [10]     pub accessed,
+///         [21..48] pub output: u64,
+///     }
+/// }
+///
+/// bitfield_dispatch! {
+///     #[derive(Debug, Clone, Copy, PartialEq)]
+///     pub enum Descriptor(u64) {
+///         Table(TableDescriptor) { mask: 0b11, value: 0b11 },
+///         Block(BlockDescriptor) { mask: 0b11, value: 0b01 },
+///     }
+/// }
+///
+/// match Descriptor::decode(0x0000_0000_4020_0405) {
+///     Some(Descriptor::Block(block)) => {
+///         assert_eq!(block.output(), 0x201);
+///         assert!(block.accessed());
+///     }
+///     other => panic!("unexpected {other:?}"),
+/// }
+/// let decoded = Descriptor::decode(0x8000_0000_0001_2003);
+/// assert!(matches!(decoded, Some(Descriptor::Table(_))));
+/// assert_eq!(Descriptor::decode(0b10), None);
+///
+/// let table = Descriptor::Table(TableDescriptor(0x3));
+/// assert_eq!(table.value(), 0x3);
+/// ```
+#[macro_export]
+macro_rules! bitfield_dispatch {
     [
-        @field setter
         $( #[$meta:meta] )*
-        [ $bit:literal ]
-        $vis:vis $field:ident
+        $vis:vis enum $name:ident($underlying_type:ty) {
+            $(
+                $( #[$variant_meta:meta] )*
+                $variant:ident($type:ty) { mask: $mask:expr, value: $value:expr $(,)? }
+            ),* $(,)?
+        }
     ] => {
-        $crate::paste! {
-            $( #[$meta] )*
-            #[inline(always)]
-            $vis fn [< set_ $field >](&mut self, value: bool) {
-                *self = self.[< with_ $field >](value);
+        $( #[$meta] )*
+        $vis enum $name {
+            $(
+                $( #[$variant_meta] )*
+                $variant($type),
+            )*
+        }
+
+        const _: () = {
+            $(
+                let mask: $underlying_type = $mask;
+                let value: $underlying_type = $value;
+                ::core::assert!(
+                    value & !mask == 0,
+                    concat!(
+                        "value of `",
+                        stringify!($variant),
+                        "` has bits set outside its mask",
+                    ),
+                );
+            )*
+        };
+
+        impl $name {
+            /// Decode a raw value as the first variant whose mask and value match it, or
+            /// `None` if none of them do.
+            #[allow(dead_code)]
+            $vis fn decode(value: $underlying_type) -> ::core::option::Option<Self> {
+                $(
+                    if value & $mask == $value {
+                        let bitfield = <$type as ::core::convert::From<_>>::from(value);
+                        return ::core::option::Option::Some(Self::$variant(bitfield));
+                    }
+                )*
+                ::core::option::Option::None
             }
 
-            $( #[$meta] )*
-            $vis fn [< with_ $field >](&mut self, value: bool) -> Self {
-                let packed = <Self as $crate::Bitfield<_>>::value(*self);
-                <Self as $crate::Bitfield<_>>::new(
-                    $crate::set_bit(packed, $bit, value))
+            /// The underlying value of the bitfield in any variant
+            #[allow(dead_code)]
+            $vis fn value(&self) -> $underlying_type {
+                match self {
+                    $(
+                        Self::$variant(bitfield) => {
+                            $crate::Bitfield::<$underlying_type>::value(*bitfield)
+                        }
+                    )*
+                }
+            }
+        }
+
+        impl ::core::convert::From<$name> for $underlying_type {
+            #[inline(always)]
+            fn from(value: $name) -> Self {
+                value.value()
             }
         }
     };
+}
+
+/// Match an integer against bit patterns, binding the bits marked with letters to
+/// variables, like a `match` for instruction decoders.
+///
+/// Each pattern is a string with one character per bit, from the most significant bit on
+/// the left to bit 0 on the right:
+///   * `0` and `1` must match the bit exactly
+///   * `?` matches either value
+///   * a lowercase letter matches either value, and the bits marked with the same letter
+///     are bound to a variable with that name, in order from left to right
+///   * `_` is ignored, for grouping digits
+///
+/// Bits above the end of the pattern must be zero. Arms are tried in order, and the last
+/// arm must be `_`, which matches anything else. Patterns are checked at compile time.
+///
+/// Arms bind their variables with a closure-like list, where the types are optional and
+/// inferred from how the variables are used. An arm that constructs a struct or variant
+/// with field shorthand binds the fields it names. The value of each variable is
+/// truncated to its type, and it's a compile error if the variable has more bits than
+/// the type can hold.
+///
+/// ```
+/// # use tartan_bitfield::bitmatch;
+/// #[derive(Debug, PartialEq)]
+/// enum Op {
+///     Add { d: u8, s: u8 },
+///     LoadImmediate(u8, u8),
+///     Jump(u16),
+///     Halt,
+///     Invalid,
+/// }
+///
+/// fn decode(word: u16) -> Op {
+///     bitmatch!(word {
+///         "0100_dddd_ssss_0011" => Op::Add { d, s },
+///         "0101_dddd_iiii_iiii" => |d, i| Op::LoadImmediate(d, i),
+///         "11jj_jjjj_jjjj_jjjj" => |j| Op::Jump(j),
+///         "0000_0000_0000_0000" => Op::Halt,
+///         _ => Op::Invalid,
+///     })
+/// }
+///
+/// assert_eq!(decode(0x4213), Op::Add { d: 2, s: 1 });
+/// assert_eq!(decode(0x53ff), Op::LoadImmediate(3, 0xff));
+/// assert_eq!(decode(0xc123), Op::Jump(0x0123));
+/// assert_eq!(decode(0x0000), Op::Halt);
+/// assert_eq!(decode(0x4214), Op::Invalid);
+/// ```
+///
+/// The bits for one variable don't have to be contiguous, like immediates that are split
+/// across an instruction word:
+///
+/// ```
+/// # use tartan_bitfield::bitmatch;
+/// let imm = bitmatch!(0b1010_0110_u8 {
+///     "iiii_01ii" => |i: u8| i,
+///     _ => 0,
+/// });
+/// assert_eq!(imm, 0b10_1010);
+/// ```
+#[macro_export]
+macro_rules! bitmatch {
+    // Split the tokens of the word expression from the block of arms at the end
+    [ @split [ $( $word:tt )* ] { $( $arms:tt )* } ] => {
+        $crate::bitmatch! { @arms ( $( $word )* ) [] $( $arms )* }
+    };
+
+    [ @split [ $( $word:tt )* ] $next:tt $( $rest:tt )+ ] => {
+        $crate::bitmatch! { @split [ $( $word )* $next ] $( $rest )+ }
+    };
 
-    // A field type and both range bounds are required in all other cases.
-    // When no explicit interface type is given, use the underlying type.
+    // Arms with a list of bindings
     [
-        @field $accessor_type:tt
-        $( #[$meta:meta] )*
-        [ $lsb:literal .. $msb:literal ]
-        $vis:vis $field:ident
-        : $field_type:ty
+        @arms $word:tt [ $( $out:tt )* ]
+        $pattern:literal => | $( $binding:ident $( : $type:ty )? ),* $(,)? | $body:block
+        $( $rest:tt )*
     ] => {
-        $crate::bitfield_accessors! {
-            @field $accessor_type
-            $( #[$meta] )*
-            [$lsb..$msb] $vis $field: $field_type as $field_type
+        $crate::bitmatch! {
+            @arms $word [
+                $( $out )*
+                ( $pattern [ $( $binding [ $( $type )? ] )* ] $body )
+            ]
+            $( $rest )*
         }
     };
 
     [
-        @field $accessor_type:tt
-        $( #[$meta:meta] )*
-        [ $lsb:literal ..= $msb:literal ]
-        $vis:vis $field:ident
-        : $field_type:ty
+        @arms $word:tt [ $( $out:tt )* ]
+        $pattern:literal => | $( $binding:ident $( : $type:ty )? ),* $(,)? | $body:expr ,
+        $( $rest:tt )*
     ] => {
-        $crate::bitfield_accessors! {
-            @field $accessor_type
-            $( #[$meta] )*
-            [$lsb..=$msb] $vis $field: $field_type as $field_type
+        $crate::bitmatch! {
+            @arms $word [
+                $( $out )*
+                ( $pattern [ $( $binding [ $( $type )? ] )* ] $body )
+            ]
+            $( $rest )*
         }
     };
 
+    // Arms that construct a struct with field shorthand, binding the fields
     [
-        @field getter
-        $( #[$meta:meta] )*
-        [ $lsb:literal .. $msb:literal ]
-        $vis:vis $field:ident
-        : $underlying_type:ty as $interface_type:ty
+        @arms $word:tt [ $( $out:tt )* ]
+        $pattern:literal => $( $path:ident )::+ { $( $binding:ident ),* $(,)? } ,
+        $( $rest:tt )*
     ] => {
-        $crate::paste! {
-            $( #[$meta] )*
-            $vis fn $field(&self) -> $interface_type {
-                use $crate::TruncateInto;
-                let packed = <Self as $crate::Bitfield<_>>::value(*self);
-                let underlying: $underlying_type =
-                    $crate::get_bits(packed, $lsb, $msb).truncate_into();
-                underlying.into()
-            }
+        $crate::bitmatch! {
+            @arms $word [
+                $( $out )*
+                ( $pattern [ $( $binding [] )* ] ( $( $path )::+ { $( $binding ),* } ) )
+            ]
+            $( $rest )*
         }
     };
 
+    // Arms without bindings
     [
-        @field getter
-        $( #[$meta:meta] )*
-        [ $lsb:literal ..= $msb:literal ]
-        $vis:vis $field:ident
-        : $underlying_type:ty as $interface_type:ty
+        @arms $word:tt [ $( $out:tt )* ]
+        $pattern:literal => $body:block
+        $( $rest:tt )*
     ] => {
-        $crate::paste! {
-            $( #[$meta] )*
-            $vis fn $field(&self) -> $interface_type {
-                use $crate::TruncateInto;
-                let packed = <Self as $crate::Bitfield<_>>::value(*self);
-                let underlying: $underlying_type =
-                    $crate::get_bits(packed, $lsb, $msb + 1).truncate_into();
-                underlying.into()
-            }
-        }
+        $crate::bitmatch! { @arms $word [ $( $out )* ( $pattern [] $body ) ] $( $rest )* }
     };
 
     [
-        @field setter
-        $( #[$meta:meta] )*
-        [ $lsb:literal .. $msb:literal ]
-        $vis:vis $field:ident
-        : $underlying_type:ty as $interface_type:ty
+        @arms $word:tt [ $( $out:tt )* ]
+        $pattern:literal => $body:expr ,
+        $( $rest:tt )*
     ] => {
-        $crate::paste! {
-            $( #[$meta] )*
-            #[inline(always)]
-            $vis fn [< set_ $field >](&mut self, value: $interface_type) {
-                *self = self.[< with_ $field >](value);
-            }
+        $crate::bitmatch! { @arms $word [ $( $out )* ( $pattern [] $body ) ] $( $rest )* }
+    };
 
-            $( #[$meta] )*
-            $vis fn [< with_ $field >](&self, value: $interface_type) -> Self {
-                let underlying: $underlying_type = value.into();
-                let packed = <Self as $crate::Bitfield<_>>::value(*self);
-                <Self as $crate::Bitfield<_>>::new(
-                    $crate::set_bits(packed, $lsb, $msb, underlying.into()))
-            }
-        }
+    // Commas after block bodies are optional, like in `match`
+    [ @arms $word:tt [ $( $out:tt )* ] , $( $rest:tt )* ] => {
+        $crate::bitmatch! { @arms $word [ $( $out )* ] $( $rest )* }
     };
 
     [
-        @field setter
-        $( #[$meta:meta] )*
-        [ $lsb:literal ..= $msb:literal ]
-        $vis:vis $field:ident
-        : $underlying_type:ty as $interface_type:ty
+        @arms ( $( $word:tt )* ) [
+            $(
+                ( $pattern:literal [ $( $binding:ident [ $( $type:ty )? ] )* ] $body:tt )
+            )*
+        ]
+        _ => $default:expr $(,)?
     ] => {
-        $crate::paste! {
-            $( #[$meta] )*
-            #[inline(always)]
-            $vis fn [< set_ $field >](&mut self, value: $interface_type) {
-                *self = self.[< with_ $field >](value);
-            }
-
-            $( #[$meta] )*
-            $vis fn [< with_ $field >](&self, value: $interface_type) -> Self {
-                let underlying: $underlying_type = value.into();
-                let packed = <Self as $crate::Bitfield<_>>::value(*self);
-                <Self as $crate::Bitfield<_>>::new(
-                    $crate::set_bits(packed, $lsb, $msb + 1, underlying.into()))
-            }
+        {
+            let word = $crate::PackedBits::into_u128($( $word )*);
+            $(
+                if word & const { $crate::bitmatch_fixed($pattern).0 }
+                    == const { $crate::bitmatch_fixed($pattern).1 }
+                {
+                    $(
+                        let $binding $( : $type )? = $crate::bitmatch_field::<
+                            { $crate::bitmatch_mask($pattern, stringify!($binding)) },
+                            _,
+                        >(word);
+                    )*
+                    $body
+                } else
+            )*
+            { $default }
         }
     };
+
+    [ @arms $( $tokens:tt )* ] => {
+        ::core::compile_error!(::core::concat!(
+            "expected bitmatch arms of the form `\"pattern\" => value`, ",
+            "ending with `_ => value`",
+        ))
+    };
+
+    [ @split $( $tokens:tt )* ] => {
+        ::core::compile_error!("expected a value followed by a block of bitmatch arms")
+    };
+
+    [ $( $input:tt )+ ] => {
+        $crate::bitmatch! { @split [] $( $input )+ }
+    };
 }
 
 /// Get a boolean reflecting a single bit of the value.
@@ -608,15 +5297,345 @@ where
         + ops::Not<Output = T>
         + ops::BitAnd<T, Output = T>
         + ops::BitOr<T, Output = T>,
+{
+    // e.g., 0b0001_1100 for MSB = 5, LSB = 2
+    let position_mask = bit_range_mask::<T>(lsb, msb);
+    let value_mask = field_val.saturating_shl(lsb.into()) & position_mask;
+    packed_val & position_mask.not() | value_mask
+}
+
+/// Extract the same range of bits from every value in `packed_vals` into the
+/// corresponding element of `field_vals`. The result is the same as calling
+/// [`get_bits`] on each value.
+///
+/// The range is checked and the mask is computed once, so the loop is a plain shift and
+/// mask that the compiler can vectorize. This is much faster than calling an accessor
+/// for each element when decoding large buffers, like instruction streams or sample
+/// blocks. The range of a field can be taken from its `_RANGE` constant:
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, get_bits_batch};
+/// bitfield! {
+///     pub struct Instruction(u32) {
+///         [0..7] pub opcode: u8,
+///     }
+/// }
+///
+/// let words = [0x0000_0093_u32, 0x0000_0013, 0x0000_0067];
+/// let mut opcodes = [0; 3];
+/// let range = Instruction::OPCODE_RANGE;
+/// get_bits_batch(&words, range.start, range.end, &mut opcodes);
+/// assert_eq!(opcodes, [0x13, 0x13, 0x67]);
+/// ```
+///
+/// # Panics
+///
+/// Panics if the slices have different lengths.
+pub fn get_bits_batch<T>(packed_vals: &[T], lsb: u8, msb: u8, field_vals: &mut [T])
+where
+    T: Default
+        + Copy
+        + OverflowingShl
+        + ops::Shr<u8, Output = T>
+        + ops::Not<Output = T>
+        + ops::BitAnd<T, Output = T>
+        + ops::BitOr<T, Output = T>,
+{
+    assert_eq!(packed_vals.len(), field_vals.len(), "slices must have the same length");
+    if usize::from(lsb) >= 8 * core::mem::size_of::<T>() {
+        field_vals.fill(T::default());
+        return;
+    }
+    let field_width_mask = bit_range_mask::<T>(0, msb - lsb);
+    for (field_val, &packed_val) in field_vals.iter_mut().zip(packed_vals) {
+        *field_val = (packed_val >> lsb) & field_width_mask;
+    }
+}
+
+/// A mask with the bits in the range `lsb..msb` set, **exclusive** of `msb`, and every
+/// other bit clear.
+///
+/// This is the mask that [`set_bits`] uses to select the bits it updates. It can also be
+/// used to build patterns for registers with per-bit write enables. Like the other
+/// helpers, parts of the range past the bits of `T` are ignored.
+///
+/// ```
+/// # use tartan_bitfield::bit_range_mask;
+/// assert_eq!(bit_range_mask::<u8>(2, 5), 0b0001_1100);
+/// assert_eq!(bit_range_mask::<u16>(8, 16), 0xff00);
+/// assert_eq!(bit_range_mask::<u32>(0, 32), u32::MAX);
+/// assert_eq!(bit_range_mask::<u32>(4, 4), 0);
+/// ```
+#[must_use]
+pub fn bit_range_mask<T>(lsb: u8, msb: u8) -> T
+where
+    T: Default + OverflowingShl + ops::Not<Output = T> + ops::BitOr<T, Output = T>,
 {
     // e.g., 0b1110_0000 for MSB = 5 (exclusive)
     let msb_mask = T::default().not().saturating_shl(msb.into());
     // e.g., 0b0000_0011 for LSB = 2
     let lsb_mask = T::default().not().saturating_shl(lsb.into()).not();
-    // e.g., 0b1110_0011 for MSB = 5, LSB = 2
-    let position_mask = msb_mask | lsb_mask;
-    let value_mask = field_val.saturating_shl(lsb.into()) & position_mask.not();
-    packed_val & position_mask | value_mask
+    (msb_mask | lsb_mask).not()
+}
+
+/// Same as [`get_bits`], but returns `None` if the range is out of order or extends past
+/// the bits of `T`, instead of silently saturating.
+///
+/// This is useful when the range is only known at runtime, like one read from a
+/// register description.
+///
+/// ```
+/// # use tartan_bitfield::checked_get_bits;
+/// assert_eq!(checked_get_bits(0b1100_1110_u8, 3, 7), Some(0b1001));
+/// assert_eq!(checked_get_bits(0b1100_1110_u8, 4, 9), None);
+/// assert_eq!(checked_get_bits(0b1100_1110_u8, 5, 3), None);
+/// ```
+#[must_use]
+pub fn checked_get_bits<T>(packed_val: T, lsb: u8, msb: u8) -> Option<T>
+where
+    T: Default
+        + OverflowingShl
+        + OverflowingShr
+        + ops::Not<Output = T>
+        + ops::BitAnd<T, Output = T>,
+{
+    is_valid_range::<T>(lsb, msb).then(|| get_bits(packed_val, lsb, msb))
+}
+
+/// Same as [`set_bits`], but returns `None` if the range is out of order or extends past
+/// the bits of `T`, instead of silently saturating.
+///
+/// ```
+/// # use tartan_bitfield::checked_set_bits;
+/// assert_eq!(checked_set_bits(0b0000_0000_u8, 6, 8, 0b11), Some(0b1100_0000));
+/// assert_eq!(checked_set_bits(0b0000_0000_u8, 6, 9, 0b11), None);
+/// ```
+#[must_use]
+pub fn checked_set_bits<T>(packed_val: T, lsb: u8, msb: u8, field_val: T) -> Option<T>
+where
+    T: Default
+        + Copy
+        + OverflowingShl
+        + ops::Shl<u8, Output = T>
+        + ops::Not<Output = T>
+        + ops::BitAnd<T, Output = T>
+        + ops::BitOr<T, Output = T>,
+{
+    is_valid_range::<T>(lsb, msb).then(|| set_bits(packed_val, lsb, msb, field_val))
+}
+
+/// Interpret the lowest `width` bits of the value as a two's complement integer, and
+/// extend its sign bit to fill the signed type of the same size. Higher bits are ignored.
+///
+/// This is useful for signed fields, like instruction immediates and sensor readings,
+/// after extracting them with [`get_bits`]. A width of zero produces zero, and a width
+/// greater than or equal to the size of the type reinterprets the whole value.
+///
+/// ```
+/// # use tartan_bitfield::{get_bits, sign_extend};
+/// assert_eq!(sign_extend(0b0111_u8, 4), 7_i8);
+/// assert_eq!(sign_extend(0b1000_u8, 4), -8_i8);
+/// assert_eq!(sign_extend(0xabc_u32, 12), -0x544_i32);
+///
+/// let instruction = 0xfff0_0093_u32; // addi x1, x0, -1
+/// assert_eq!(sign_extend(get_bits(instruction, 20, 32), 12), -1);
+/// ```
+#[must_use]
+pub fn sign_extend<T>(value: T, width: u8) -> T::Signed
+where
+    T: SignExtend,
+{
+    value.sign_extend(width)
+}
+
+// Runtime equivalent of `BitRange::VALID` for the checked variants of the bit helpers
+fn is_valid_range<T>(lsb: u8, msb: u8) -> bool {
+    lsb <= msb && msb as usize <= 8 * core::mem::size_of::<T>()
+}
+
+/// Same as [`get_bits`], but with the bit range fixed at compile time.
+///
+/// The range is checked against the size of `T` when the function is instantiated, so
+/// no saturating shifts are needed at runtime. The result is a plain shift and mask at
+/// any optimization level.
+///
+/// ```
+/// # use tartan_bitfield::get_bits_const;
+/// assert_eq!(get_bits_const::<3, 7, _>(0b1100_1110_u8), 0b1001);
+/// assert_eq!(get_bits_const::<0, 8, _>(0b1010_0101_u8), 0b1010_0101);
+/// ```
+///
+/// A range that doesn't fit in the type is a compile error:
+///
+/// ```compile_fail
+/// # use tartan_bitfield::get_bits_const;
+/// let _ = get_bits_const::<4, 9, _>(0_u8);
+/// ```
+#[must_use]
+pub fn get_bits_const<const LSB: u8, const MSB: u8, T>(packed_val: T) -> T
+where
+    T: Default
+        + Copy
+        + ops::Shl<u8, Output = T>
+        + ops::Shr<u8, Output = T>
+        + ops::Not<Output = T>
+        + ops::BitAnd<T, Output = T>,
+{
+    #[allow(clippy::let_unit_value)]
+    let () = BitRange::<T, LSB, MSB>::VALID;
+    let field_width_mask = split_shl(T::default().not(), MSB - LSB).not();
+    split_shr(packed_val, LSB) & field_width_mask
+}
+
+/// Same as [`set_bits`], but with the bit range fixed at compile time.
+///
+/// The range is checked against the size of `T` when the function is instantiated, so
+/// no saturating shifts are needed at runtime. The result is a plain shift, mask, and
+/// combine at any optimization level.
+///
+/// ```
+/// # use tartan_bitfield::set_bits_const;
+/// assert_eq!(set_bits_const::<6, 8, _>(0b0000_0000_u8, 0b11), 0b1100_0000);
+/// assert_eq!(set_bits_const::<2, 6, _>(0b1010_0110_u8, 0b1110), 0b1011_1010);
+/// ```
+#[must_use]
+pub fn set_bits_const<const LSB: u8, const MSB: u8, T>(packed_val: T, field_val: T) -> T
+where
+    T: Default
+        + Copy
+        + ops::Shl<u8, Output = T>
+        + ops::Not<Output = T>
+        + ops::BitAnd<T, Output = T>
+        + ops::BitOr<T, Output = T>,
+{
+    #[allow(clippy::let_unit_value)]
+    let () = BitRange::<T, LSB, MSB>::VALID;
+    let field_width_mask = split_shl(T::default().not(), MSB - LSB).not();
+    let position_mask = split_shl(field_width_mask, LSB);
+    packed_val & position_mask.not() | split_shl(field_val & field_width_mask, LSB)
+}
+
+/// The number of bits needed to hold every value from zero to `max_value`.
+///
+/// This is a `const fn`, so it can compute the width of a field from the largest value
+/// it has to hold when layouts are defined programmatically. No bits are needed for a
+/// maximum of zero.
+///
+/// ```
+/// # use tartan_bitfield::{bits_required, SmallestUint};
+/// assert_eq!(bits_required(1), 1);
+/// assert_eq!(bits_required(255), 8);
+/// assert_eq!(bits_required(256), 9);
+///
+/// const CHANNELS: u128 = 24;
+/// let channel: SmallestUint<{ bits_required(CHANNELS - 1) as usize }> = 23;
+/// # assert_eq!(channel, 23_u8);
+/// ```
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub const fn bits_required(max_value: u128) -> u8 {
+    (u128::BITS - max_value.leading_zeros()) as u8
+}
+
+// Compile-time validation of the bit range for the `_const` variants of the bit helpers
+struct BitRange<T, const LSB: u8, const MSB: u8>(PhantomData<T>);
+
+impl<T, const LSB: u8, const MSB: u8> BitRange<T, LSB, MSB> {
+    const VALID: () = assert!(
+        LSB <= MSB && MSB as usize <= 8 * core::mem::size_of::<T>(),
+        "Bit range must be in order and fit in the type"
+    );
+}
+
+// Shift by any amount up to and including the width of the type, where a full-width
+// shift produces zero. Each half of the shift is less than the width, so there is no
+// overflow, and no branch like in `saturating_shl`.
+#[inline(always)]
+fn split_shl<T>(val: T, n: u8) -> T
+where
+    T: ops::Shl<u8, Output = T>,
+{
+    val << (n / 2) << (n - n / 2)
+}
+
+#[inline(always)]
+fn split_shr<T>(val: T, n: u8) -> T
+where
+    T: ops::Shr<u8, Output = T>,
+{
+    val >> (n / 2) >> (n - n / 2)
+}
+
+/// An integer type that can be parsed from a string in an arbitrary base.
+///
+/// All basic numeric types have a `from_str_radix` function, but there is no
+/// corresponding trait in [`core`].
+pub trait FromStrRadix
+where
+    Self: Sized,
+{
+    /// The error returned when parsing fails
+    type Err;
+
+    /// Parse a string of digits in the given base. See [`u32::from_str_radix`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string contains invalid digits or the value does not fit
+    /// in the type.
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, Self::Err>;
+}
+
+macro_rules! from_str_radix_impl {
+    ($type:ty) => {
+        impl FromStrRadix for $type {
+            type Err = ParseIntError;
+
+            #[inline(always)]
+            fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseIntError> {
+                <$type>::from_str_radix(s, radix)
+            }
+        }
+    };
+}
+
+from_str_radix_impl!(u8);
+from_str_radix_impl!(u16);
+from_str_radix_impl!(u32);
+from_str_radix_impl!(u64);
+from_str_radix_impl!(u128);
+from_str_radix_impl!(usize);
+
+/// Parse an integer written as a Rust-style literal: hexadecimal with a `0x` prefix,
+/// binary with a `0b` prefix, or decimal with no prefix. Prefixes are case-insensitive.
+///
+/// This is used for the [`FromStr`](core::str::FromStr) implementation of types defined
+/// with the [`bitfield`] macro.
+///
+/// ```
+/// # use tartan_bitfield::parse_prefixed;
+/// assert_eq!(parse_prefixed::<u16>("0x1f2e"), Ok(0x1f2e));
+/// assert_eq!(parse_prefixed::<u16>("0XABCD"), Ok(0xabcd));
+/// assert_eq!(parse_prefixed::<u8>("0b1010"), Ok(0b1010));
+/// assert_eq!(parse_prefixed::<u8>("255"), Ok(255));
+/// assert!(parse_prefixed::<u8>("256").is_err());
+/// assert!(parse_prefixed::<u8>("0x").is_err());
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the string contains invalid digits for its base, or if the value
+/// does not fit in the type.
+pub fn parse_prefixed<T>(s: &str) -> Result<T, T::Err>
+where
+    T: FromStrRadix,
+{
+    let (digits, radix) = match s.get(..2) {
+        Some("0x" | "0X") => (&s[2..], 16),
+        Some("0b" | "0B") => (&s[2..], 2),
+        _ => (s, 10),
+    };
+    T::from_str_radix(digits, radix)
 }
 
 /// A type whose values can be truncated into another type. This is more explicit than
@@ -630,6 +5649,7 @@ macro_rules! truncate_into_impl {
     ($source:ty, $dest:ty) => {
         impl TruncateInto<$dest> for $source {
             #[inline(always)]
+            #[allow(clippy::cast_possible_truncation)]
             fn truncate_into(self) -> $dest {
                 self as $dest
             }
@@ -662,10 +5682,72 @@ truncate_into_impl!(usize, usize);
 truncate_into_impl!(usize, u64);
 #[cfg(any(target_pointer_width = "64", target_pointer_width = "32"))]
 truncate_into_impl!(usize, u32);
-#[cfg(any(target_pointer_width = "64", target_pointer_width = "32"))]
+// `usize` is at least 16 bits on every target, including MSP430 and AVR
 truncate_into_impl!(usize, u16);
-#[cfg(any(target_pointer_width = "64", target_pointer_width = "32"))]
 truncate_into_impl!(usize, u8);
+// No target has a `usize` wider than 64 bits, so these always truncate or keep the value
+truncate_into_impl!(u128, usize);
+truncate_into_impl!(u64, usize);
+
+/// The inverse of [`TruncateInto`], implemented for every type that a [`TruncateInto`]
+/// conversion produces. Like [`From`] and [`Into`], this lets generic code name the
+/// conversion by whichever end is the type parameter.
+///
+/// ```
+/// # use tartan_bitfield::TruncateFrom;
+/// fn low_byte<T: TruncateFrom<u32>>(value: u32) -> T {
+///     T::truncate_from(value)
+/// }
+///
+/// assert_eq!(low_byte::<u8>(0x1234_5678), 0x78);
+/// assert_eq!(low_byte::<u16>(0x1234_5678), 0x5678);
+/// ```
+pub trait TruncateFrom<T> {
+    /// Truncate the value to fit in this type
+    fn truncate_from(value: T) -> Self;
+}
+
+impl<S, T> TruncateFrom<S> for T
+where
+    S: TruncateInto<T>,
+{
+    #[inline(always)]
+    fn truncate_from(value: S) -> Self {
+        value.truncate_into()
+    }
+}
+
+/// A lossless conversion into a type at least as wide, which [`TruncateInto`] undoes.
+/// Implemented for every type with an [`Into`] conversion that has a matching
+/// [`TruncateInto`] conversion in the other direction.
+///
+/// Together with [`TruncateFrom`], this lets generic code bound the source type in both
+/// directions, instead of writing the bounds in terms of the destination:
+///
+/// ```
+/// # use tartan_bitfield::{uint::u24, TruncateFrom, WideningInto};
+/// fn increment<T: WideningInto<u32> + TruncateFrom<u32>>(value: T) -> T {
+///     T::truncate_from(value.widen_into().wrapping_add(1))
+/// }
+///
+/// assert_eq!(increment(0xab_u8), 0xac);
+/// assert_eq!(increment(0xff_u8), 0);
+/// assert_eq!(increment(u24::new(0x12_3456)), u24::new(0x12_3457));
+/// ```
+pub trait WideningInto<T>: Into<T> {
+    /// Convert the value to the wider type without losing any bits
+    #[inline(always)]
+    fn widen_into(self) -> T {
+        self.into()
+    }
+}
+
+impl<S, T> WideningInto<T> for S
+where
+    S: Into<T>,
+    T: TruncateInto<S>,
+{
+}
 
 /// A type with an overflowing left shift operation. Also adds a saturating version.
 ///
@@ -760,3 +5842,39 @@ overflowing_shr_impl!(u32);
 overflowing_shr_impl!(u64);
 overflowing_shr_impl!(u128);
 overflowing_shr_impl!(usize);
+
+/// An unsigned integer type whose low bits can be interpreted as a smaller signed
+/// integer. See [`sign_extend`].
+pub trait SignExtend {
+    /// The signed type with the same size
+    type Signed;
+
+    /// Interpret the lowest `width` bits as a two's complement integer, and extend its
+    /// sign bit to fill the signed type
+    fn sign_extend(self, width: u8) -> Self::Signed;
+}
+
+macro_rules! sign_extend_impl {
+    ($type:ty => $signed:ty) => {
+        impl SignExtend for $type {
+            type Signed = $signed;
+
+            #[inline(always)]
+            #[allow(clippy::cast_possible_wrap)]
+            fn sign_extend(self, width: u8) -> $signed {
+                if width == 0 {
+                    return 0;
+                }
+                let unused_bits = <$type>::BITS.saturating_sub(width.into());
+                (self as $signed).wrapping_shl(unused_bits).wrapping_shr(unused_bits)
+            }
+        }
+    };
+}
+
+sign_extend_impl!(u8 => i8);
+sign_extend_impl!(u16 => i16);
+sign_extend_impl!(u32 => i32);
+sign_extend_impl!(u64 => i64);
+sign_extend_impl!(u128 => i128);
+sign_extend_impl!(usize => isize);
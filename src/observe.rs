@@ -0,0 +1,108 @@
+use crate::{Bitfield, FieldInfo, FieldLayout, FieldStorage, PackedBits};
+use core::fmt;
+use core::marker::PhantomData;
+
+/// A bitfield value that calls a function for every field that changes when it is
+/// modified, for register viewers and test rigs that need to react to changes without
+/// polling.
+///
+/// The function is called with the field and its old and new values, as integers. It is
+/// called once for each field that changed, in the order the fields were declared, after
+/// the whole value has been updated. Fields that were written with the same value they
+/// already had are skipped. Any `FnMut` works, including plain `fn` pointers, so this
+/// doesn't need `std`. To log changes as text instead, see [`Traced`](crate::Traced).
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, FieldInfo, Observed};
+/// bitfield! {
+///     pub struct Control(u32) {
+///         [0]     pub enable,
+///         [4..8]  pub mode,
+///         [8..16] pub divider: u8,
+///     }
+/// }
+///
+/// let mut changes = Vec::new();
+/// let mut cr = Observed::new(Control(0x0410), |field: &FieldInfo, old, new| {
+///     changes.push((field.name, old, new));
+/// });
+/// cr.update(|c| {
+///     c.set_enable(true);
+///     c.set_mode(3);
+///     c.set_divider(4);
+/// });
+/// assert_eq!(cr.get(), Control(0x0431));
+/// drop(cr);
+///
+/// assert_eq!(changes, [("enable", 0, 1), ("mode", 1, 3)]);
+/// ```
+pub struct Observed<B, T, F> {
+    value: B,
+    observer: F,
+    underlying: PhantomData<T>,
+}
+
+impl<B, T, F> Observed<B, T, F>
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits,
+    F: FnMut(&FieldInfo, u128, u128),
+{
+    /// Wrap a bitfield value, calling `observer` for each field that changes
+    #[inline(always)]
+    pub fn new(value: B, observer: F) -> Self {
+        Self { value, observer, underlying: PhantomData }
+    }
+
+    /// The current value of the bitfield
+    #[inline(always)]
+    pub fn get(&self) -> B {
+        self.value
+    }
+
+    /// Replace the bitfield with the given value, notifying the observer of any fields
+    /// that change
+    pub fn set(&mut self, value: B) {
+        let old = crate::packed_value(self.value).into_u128();
+        let new = crate::packed_value(value).into_u128();
+        self.value = value;
+        if old == new {
+            return;
+        }
+        for field in B::LAYOUT {
+            let old = (old & field.mask()) >> field.lsb;
+            let new = (new & field.mask()) >> field.lsb;
+            if old != new {
+                (self.observer)(field, old, new);
+            }
+        }
+    }
+
+    /// Modify the bitfield with the given function, typically by calling its `set_`
+    /// methods. The observer is notified after the function returns.
+    pub fn update<G, R>(&mut self, f: G) -> R
+    where
+        G: FnOnce(&mut B) -> R,
+    {
+        let mut bitfield = self.value;
+        let result = f(&mut bitfield);
+        self.set(bitfield);
+        result
+    }
+
+    /// Stop observing and return the current value of the bitfield and the observer
+    #[inline(always)]
+    pub fn into_inner(self) -> (B, F) {
+        (self.value, self.observer)
+    }
+}
+
+impl<B, T, F> fmt::Debug for Observed<B, T, F>
+where
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Observed").field(&self.value).finish()
+    }
+}
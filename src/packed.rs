@@ -0,0 +1,273 @@
+use crate::{get_bits, set_bits, TruncateInto};
+use core::fmt;
+
+/// The number of bytes needed to store `len` elements of `width` bits each in a
+/// [`PackedArray`].
+///
+/// ```
+/// # use tartan_bitfield::packed_array_bytes;
+/// assert_eq!(packed_array_bytes(4, 10), 5);
+/// assert_eq!(packed_array_bytes(3, 10), 4);
+/// assert_eq!(packed_array_bytes(12, 1), 2);
+/// ```
+pub const fn packed_array_bytes(width: u8, len: usize) -> usize {
+    (width as usize * len).div_ceil(8)
+}
+
+/// A fixed-size array of `LEN` unsigned integers, each `WIDTH` bits wide, stored
+/// contiguously in `BYTES` bytes.
+///
+/// `WIDTH` may be anywhere from 1 to 32 bits. Because stable Rust can't compute the size
+/// of the backing array from the other parameters, `BYTES` must be passed explicitly. It
+/// must equal [`packed_array_bytes(WIDTH, LEN)`](packed_array_bytes), which is checked at
+/// compile time.
+///
+/// Element `i` occupies bits `i * WIDTH` up to `(i + 1) * WIDTH` of the array, where bit
+/// numbers count from the least significant bit of byte 0, continuing into byte 1, and
+/// so on. In other words, the storage is a little-endian integer with `LEN` fields.
+///
+/// ```
+/// # use tartan_bitfield::{packed_array_bytes, PackedArray};
+/// type Nibbles = PackedArray<4, 6, { packed_array_bytes(4, 6) }>;
+///
+/// let mut palette = Nibbles::new();
+/// palette.set(0, 0xa);
+/// palette.set(3, 0x7);
+/// palette.set(5, 0xf);
+/// assert_eq!(palette.get(3), 0x7);
+/// assert_eq!(palette.as_bytes(), &[0x0a, 0x70, 0xf0]);
+///
+/// // Values wider than WIDTH are truncated
+/// palette.set(1, 0x123);
+/// assert_eq!(palette.get(1), 0x3);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PackedArray<const WIDTH: u8, const LEN: usize, const BYTES: usize> {
+    bytes: [u8; BYTES],
+}
+
+impl<const WIDTH: u8, const LEN: usize, const BYTES: usize>
+    PackedArray<WIDTH, LEN, BYTES>
+{
+    const VALID_LAYOUT: () = {
+        assert!(WIDTH >= 1 && WIDTH <= 32, "PackedArray WIDTH must be from 1 to 32");
+        assert!(
+            BYTES == packed_array_bytes(WIDTH, LEN),
+            "PackedArray BYTES must equal packed_array_bytes(WIDTH, LEN)"
+        );
+    };
+
+    /// Create an array with all elements set to zero
+    pub const fn new() -> Self {
+        Self::from_bytes([0; BYTES])
+    }
+
+    /// Create an array from its packed representation
+    pub const fn from_bytes(bytes: [u8; BYTES]) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_LAYOUT;
+        Self { bytes }
+    }
+
+    /// The packed representation of the array
+    pub const fn as_bytes(&self) -> &[u8; BYTES] {
+        &self.bytes
+    }
+
+    /// Unwrap the packed representation of the array
+    pub const fn into_bytes(self) -> [u8; BYTES] {
+        self.bytes
+    }
+
+    /// The number of elements in the array
+    #[allow(clippy::unused_self)]
+    pub const fn len(&self) -> usize {
+        LEN
+    }
+
+    /// True if the array has no elements
+    #[allow(clippy::unused_self)]
+    pub const fn is_empty(&self) -> bool {
+        LEN == 0
+    }
+
+    /// Get the element at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> u32 {
+        assert!(index < LEN, "index {index} out of bounds for PackedArray of {LEN}");
+        packed_get(&self.bytes, WIDTH, index)
+    }
+
+    /// Update the element at `index`. Bits of `value` beyond `WIDTH` are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: u32) {
+        assert!(index < LEN, "index {index} out of bounds for PackedArray of {LEN}");
+        packed_set(&mut self.bytes, WIDTH, index, value);
+    }
+
+    /// Iterate over the values of all elements in order
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..LEN).map(move |i| self.get(i))
+    }
+
+    /// View the array as a [`PackedSlice`]
+    pub fn as_slice(&self) -> PackedSlice<&[u8], WIDTH> {
+        PackedSlice::new(&self.bytes[..])
+    }
+
+    /// View the array as a mutable [`PackedSlice`]
+    pub fn as_mut_slice(&mut self) -> PackedSlice<&mut [u8], WIDTH> {
+        PackedSlice::new(&mut self.bytes[..])
+    }
+}
+
+impl<const WIDTH: u8, const LEN: usize, const BYTES: usize> Default
+    for PackedArray<WIDTH, LEN, BYTES>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const WIDTH: u8, const LEN: usize, const BYTES: usize> fmt::Debug
+    for PackedArray<WIDTH, LEN, BYTES>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// A view of a byte slice as a sequence of unsigned integers, each `WIDTH` bits wide.
+///
+/// This is the dynamically-sized counterpart to [`PackedArray`], and uses the same
+/// layout. The storage can be anything that dereferences to a byte slice, like `&[u8]`,
+/// `&mut [u8]`, or `Vec<u8>`. The number of elements is the largest number that will
+/// fit in the storage.
+///
+/// ```
+/// # use tartan_bitfield::PackedSlice;
+/// let mut buffer = [0_u8; 8];
+/// let mut weights = PackedSlice::<_, 5>::new(&mut buffer[..]);
+/// assert_eq!(weights.len(), 12);
+///
+/// weights.set(0, 0x1f);
+/// weights.set(1, 0x02);
+/// weights.set(11, 0x15);
+/// assert_eq!(weights.get(1), 0x02);
+/// assert_eq!(buffer, [0x5f, 0, 0, 0, 0, 0, 0x80, 0x0a]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PackedSlice<S, const WIDTH: u8> {
+    storage: S,
+}
+
+impl<S, const WIDTH: u8> PackedSlice<S, WIDTH>
+where
+    S: AsRef<[u8]>,
+{
+    const VALID_WIDTH: () =
+        assert!(WIDTH >= 1 && WIDTH <= 32, "PackedSlice WIDTH must be from 1 to 32");
+
+    /// Create a view of the given storage
+    pub fn new(storage: S) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_WIDTH;
+        Self { storage }
+    }
+
+    /// Release the view and return the underlying storage
+    pub fn into_inner(self) -> S {
+        self.storage
+    }
+
+    /// The number of elements that fit in the storage
+    pub fn len(&self) -> usize {
+        self.storage.as_ref().len() * 8 / usize::from(WIDTH)
+    }
+
+    /// True if the storage is too small to hold a single element
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the element at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> u32 {
+        let len = self.len();
+        assert!(index < len, "index {index} out of bounds for PackedSlice of {len}");
+        packed_get(self.storage.as_ref(), WIDTH, index)
+    }
+
+    /// Iterate over the values of all elements in order
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.len()).map(move |i| self.get(i))
+    }
+}
+
+impl<S, const WIDTH: u8> PackedSlice<S, WIDTH>
+where
+    S: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Update the element at `index`. Bits of `value` beyond `WIDTH` are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: u32) {
+        let len = self.len();
+        assert!(index < len, "index {index} out of bounds for PackedSlice of {len}");
+        packed_set(self.storage.as_mut(), WIDTH, index, value);
+    }
+}
+
+impl<S, const WIDTH: u8> fmt::Debug for PackedSlice<S, WIDTH>
+where
+    S: AsRef<[u8]>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Locate element `index` within the byte slice. Returns the range of bytes that
+/// contain the element, and the offset of the element's LSB within the first byte.
+fn packed_location(width: u8, index: usize) -> (core::ops::Range<usize>, u8) {
+    let first_bit = index * usize::from(width);
+    let shift = (first_bit % 8).truncate_into();
+    let first_byte = first_bit / 8;
+    let byte_count = (usize::from(shift) + usize::from(width)).div_ceil(8);
+    (first_byte..first_byte + byte_count, shift)
+}
+
+fn packed_get(bytes: &[u8], width: u8, index: usize) -> u32 {
+    let (range, shift) = packed_location(width, index);
+    // A 32-bit element with a nonzero shift spans at most 5 bytes, so this always fits
+    let window = bytes[range]
+        .iter()
+        .rev()
+        .fold(0_u64, |window, &byte| window << 8 | u64::from(byte));
+    get_bits(window, shift, shift + width).truncate_into()
+}
+
+fn packed_set(bytes: &mut [u8], width: u8, index: usize, value: u32) {
+    let (range, shift) = packed_location(width, index);
+    let window_bytes = &mut bytes[range];
+    let mut window = window_bytes
+        .iter()
+        .rev()
+        .fold(0_u64, |window, &byte| window << 8 | u64::from(byte));
+    window = set_bits(window, shift, shift + width, value.into());
+    for byte in window_bytes {
+        *byte = window.truncate_into();
+        window >>= 8;
+    }
+}
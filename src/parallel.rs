@@ -0,0 +1,105 @@
+use crate::bytes::check_length;
+use crate::{
+    get_bits_batch, Bitfield, ByteOrder, ByteRepr, LengthMismatchError, OverflowingShl,
+};
+use core::ops;
+use rayon::prelude::*;
+
+// Each task extracts a block of this many values, which is large enough to amortize
+// the cost of scheduling and small enough to balance the work across threads
+const BATCH_CHUNK: usize = 4096;
+
+/// Same as [`get_bits_batch`], but splits the slices into blocks that are processed in
+/// parallel with [rayon](https://docs.rs/rayon).
+///
+/// ```
+/// # use tartan_bitfield::par_get_bits_batch;
+/// let words: Vec<u32> = (0..100_000).collect();
+/// let mut low_bytes = vec![0; words.len()];
+/// par_get_bits_batch(&words, 0, 8, &mut low_bytes);
+/// assert_eq!(low_bytes[0x1234], 0x34);
+/// ```
+///
+/// # Panics
+///
+/// Panics if the slices have different lengths.
+pub fn par_get_bits_batch<T>(packed_vals: &[T], lsb: u8, msb: u8, field_vals: &mut [T])
+where
+    T: Default
+        + Copy
+        + Send
+        + Sync
+        + OverflowingShl
+        + ops::Shr<u8, Output = T>
+        + ops::Not<Output = T>
+        + ops::BitAnd<T, Output = T>
+        + ops::BitOr<T, Output = T>,
+{
+    assert_eq!(packed_vals.len(), field_vals.len(), "slices must have the same length");
+    field_vals
+        .par_chunks_mut(BATCH_CHUNK)
+        .zip(packed_vals.par_chunks(BATCH_CHUNK))
+        .for_each(|(field_vals, packed_vals)| {
+            get_bits_batch(packed_vals, lsb, msb, field_vals);
+        });
+}
+
+/// Same as [`copy_bitfields_from_bytes`](crate::copy_bitfields_from_bytes), but
+/// converts the values in parallel with [rayon](https://docs.rs/rayon).
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, par_copy_bitfields_from_bytes, ByteOrder};
+/// bitfield! {
+///     pub struct Sample(u16) {
+///         [0..12] pub reading: u16,
+///     }
+/// }
+///
+/// let log = [0x12, 0x34].repeat(0x8000);
+/// let mut samples = vec![Sample::default(); log.len() / 2];
+/// par_copy_bitfields_from_bytes(&mut samples, &log, ByteOrder::Big).unwrap();
+/// assert!(samples.iter().all(|s| s.reading() == 0x234));
+/// ```
+///
+/// # Errors
+///
+/// Returns an error without modifying `dest` if the length of `bytes` does not match
+/// the combined size of the bitfields in `dest`.
+pub fn par_copy_bitfields_from_bytes<B, T>(
+    dest: &mut [B],
+    bytes: &[u8],
+    order: ByteOrder,
+) -> Result<(), LengthMismatchError>
+where
+    B: Bitfield<T> + Send,
+    T: ByteRepr,
+{
+    check_length::<T>(dest.len(), bytes.len())?;
+    dest.par_iter_mut()
+        .zip(bytes.par_chunks_exact(T::SIZE))
+        .for_each(|(bitfield, chunk)| *bitfield = B::new(T::read_bytes(chunk, order)));
+    Ok(())
+}
+
+/// Same as [`copy_bitfields_to_bytes`](crate::copy_bitfields_to_bytes), but converts
+/// the values in parallel with [rayon](https://docs.rs/rayon).
+///
+/// # Errors
+///
+/// Returns an error without modifying `dest` if the length of `dest` does not match the
+/// combined size of the bitfields in `src`.
+pub fn par_copy_bitfields_to_bytes<B, T>(
+    src: &[B],
+    dest: &mut [u8],
+    order: ByteOrder,
+) -> Result<(), LengthMismatchError>
+where
+    B: Bitfield<T> + Sync,
+    T: ByteRepr,
+{
+    check_length::<T>(src.len(), dest.len())?;
+    src.par_iter()
+        .zip(dest.par_chunks_exact_mut(T::SIZE))
+        .for_each(|(bitfield, chunk)| bitfield.value().write_bytes(chunk, order));
+    Ok(())
+}
@@ -0,0 +1,167 @@
+/// Error returned when parsing a field assignment string like `"a=0xb, d=1"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldParseError {
+    /// An assignment was not in the form `name=value`
+    Syntax,
+    /// The name did not match any field on the bitfield
+    UnknownField,
+    /// The value was not a valid number, or it did not fit in the field
+    InvalidValue,
+}
+
+impl core::fmt::Display for FieldParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Syntax => "expected field assignments like `name=value`",
+            Self::UnknownField => "no field with the given name",
+            Self::InvalidValue => "invalid value for the field",
+        })
+    }
+}
+
+impl core::error::Error for FieldParseError {}
+
+/// Bitfield types whose fields can be assigned from strings at runtime.
+///
+/// This is implemented by all types defined with the [`bitfield`](crate::bitfield)
+/// macro, which accepts the field names declared in the macro. Values are parsed with
+/// [`parse_prefixed`](crate::parse_prefixed), except for single-bit fields, which
+/// accept `0`, `1`, `false`, or `true`. Fields of nested bitfield types can be assigned
+/// with a dotted path.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, FieldParseError, ParseFields};
+/// bitfield! {
+///     pub struct Outer(u16) {
+///         [0..4]  pub a: u8,
+///         [7]     pub d,
+///         [8..16] pub e: u8 as Inner,
+///     }
+/// }
+///
+/// bitfield! {
+///     pub struct Inner(u8) {
+///         [0] pub zero,
+///         [1] pub one,
+///     }
+/// }
+///
+/// let x = Outer::parse_fields("a=0xB, d=1, e.one=true").unwrap();
+/// assert_eq!(x, Outer(0x028b));
+///
+/// let mut y = x;
+/// y.apply_fields("e.one=0, a=0b11").unwrap();
+/// assert_eq!(y, Outer(0x0083));
+///
+/// assert_eq!(Outer::parse_fields("a=16"), Err(FieldParseError::InvalidValue));
+/// assert_eq!(Outer::parse_fields("b=1"), Err(FieldParseError::UnknownField));
+/// assert_eq!(Outer::parse_fields("a.zero=1"), Err(FieldParseError::UnknownField));
+/// assert_eq!(Outer::parse_fields("d"), Err(FieldParseError::Syntax));
+/// ```
+pub trait ParseFields {
+    /// Assign a single field by name, parsing the value from a string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no such field or the value is invalid for it. The
+    /// value is unchanged if there is an error.
+    fn set_field_from_str(
+        &mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<(), FieldParseError>;
+
+    /// Assign fields from a comma-separated list of `name=value` pairs. Whitespace
+    /// around names and values is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any assignment is malformed or invalid. Assignments before
+    /// the one that failed will still have been applied.
+    fn apply_fields(&mut self, assignments: &str) -> Result<(), FieldParseError> {
+        for assignment in assignments.split(',') {
+            if assignment.trim().is_empty() {
+                continue;
+            }
+            let (name, value) =
+                assignment.split_once('=').ok_or(FieldParseError::Syntax)?;
+            self.set_field_from_str(name.trim(), value.trim())?;
+        }
+        Ok(())
+    }
+
+    /// Create a new value from a comma-separated list of `name=value` pairs, starting
+    /// with the default value. Whitespace around names and values is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any assignment is malformed or invalid.
+    fn parse_fields(assignments: &str) -> Result<Self, FieldParseError>
+    where
+        Self: Default,
+    {
+        let mut value = Self::default();
+        value.apply_fields(assignments)?;
+        Ok(value)
+    }
+}
+
+/// Parse the value of a single-bit field.
+#[doc(hidden)]
+pub fn parse_flag(value: &str) -> Result<bool, FieldParseError> {
+    match value {
+        "0" | "false" => Ok(false),
+        "1" | "true" => Ok(true),
+        _ => Err(FieldParseError::InvalidValue),
+    }
+}
+
+// Nested field assignments (`outer.inner=value`) should only work if the field's type
+// implements ParseFields, but the macro has no way to check that. These types use
+// autoref-based specialization to pick the right behavior at compile time: the method
+// from `NestedFieldParse` is preferred when its bounds are satisfied, since it doesn't
+// require an extra reference to match the receiver.
+
+#[doc(hidden)]
+pub struct NestedField<T>(pub T);
+
+#[doc(hidden)]
+pub trait NestedFieldParse<T> {
+    fn set_nested_field_from_str(
+        &self,
+        name: &str,
+        value: &str,
+    ) -> Result<T, FieldParseError>;
+}
+
+impl<T> NestedFieldParse<T> for NestedField<T>
+where
+    T: ParseFields + Copy,
+{
+    #[inline(always)]
+    fn set_nested_field_from_str(
+        &self,
+        name: &str,
+        value: &str,
+    ) -> Result<T, FieldParseError> {
+        let mut nested = self.0;
+        nested.set_field_from_str(name, value)?;
+        Ok(nested)
+    }
+}
+
+#[doc(hidden)]
+pub trait NestedFieldFallback<T> {
+    fn set_nested_field_from_str(
+        &self,
+        name: &str,
+        value: &str,
+    ) -> Result<T, FieldParseError>;
+}
+
+impl<T> NestedFieldFallback<T> for &NestedField<T> {
+    #[inline(always)]
+    fn set_nested_field_from_str(&self, _: &str, _: &str) -> Result<T, FieldParseError> {
+        Err(FieldParseError::UnknownField)
+    }
+}
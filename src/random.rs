@@ -0,0 +1,139 @@
+use crate::{Bitfield, FieldInfo, FieldLayout, FieldStorage, PackedBits, TruncateInto};
+use rand::distributions::Distribution;
+use rand::Rng;
+
+/// Generate a bitfield with every bit of the underlying value random. Helper function for
+/// the `Distribution` implementation generated by [`bitfield`](crate::bitfield).
+#[doc(hidden)]
+pub fn random_value<B, T, R>(rng: &mut R) -> B
+where
+    B: Bitfield<T>,
+    T: FieldStorage,
+    T::Storage: PackedBits + TruncateInto<T>,
+    R: Rng + ?Sized,
+{
+    crate::from_packed_value(T::Storage::from_u128(rng.gen()))
+}
+
+/// A distribution of bitfield values where the bits of each field are random and bits
+/// that aren't part of any field are clear, for randomized tests of hardware models that
+/// expect reserved bits to be zero.
+///
+/// With the `rand` feature, the [`bitfield`](crate::bitfield) macro also implements
+/// `Distribution` for [`Standard`](rand::distributions::Standard), which makes every bit
+/// random, including reserved ones.
+///
+/// Fields are filled with random bits regardless of any constraints on their values, so
+/// the result may not pass [`Validate`](crate::Validate).
+///
+/// ```
+/// # use rand::{rngs::mock::StepRng, Rng};
+/// # use tartan_bitfield::{bitfield, RandomFields};
+/// bitfield! {
+///     pub struct Control(u16) {
+///         [0..4]  pub mode: u8,
+///         [8]     pub enable,
+///     }
+/// }
+///
+/// let mut rng = StepRng::new(u64::MAX, 0);
+/// let any: Control = rng.gen();
+/// assert_eq!(any, Control(0xffff));
+/// let fields: Control = rng.sample(RandomFields);
+/// assert_eq!(fields, Control(0x010f));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RandomFields;
+
+impl<B> Distribution<B> for RandomFields
+where
+    B: Bitfield<B::Storage> + FieldStorage + FieldLayout,
+    B::Storage: FieldStorage,
+    <B::Storage as FieldStorage>::Storage: PackedBits + TruncateInto<B::Storage>,
+{
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> B {
+        let mask = crate::field_mask::<B, B::Storage>().into_u128();
+        let packed = rng.gen::<u128>() & mask;
+        crate::from_packed_value(<B::Storage as FieldStorage>::Storage::from_u128(packed))
+    }
+}
+
+/// Change the value of one randomly chosen field, for structure-aware fuzzing. The other
+/// fields and the bits that aren't part of any field are left as they are.
+///
+/// The new value of the field always differs from the old one. It is usually random, but
+/// is sometimes all ones, zero, or one more or less than the old value, since values at
+/// the edges of a field's range are more likely to find bugs. Returns the value unchanged
+/// if the bitfield has no fields.
+///
+/// ```
+/// # use rand::rngs::mock::StepRng;
+/// # use tartan_bitfield::{bitfield, mutate_field};
+/// bitfield! {
+///     pub struct Header(u16) {
+///         [0..4]   pub version: u8,
+///         [8..16]  pub length: u8,
+///     }
+/// }
+///
+/// let mut rng = StepRng::new(1, 0x9e37_79b9_7f4a_7c15);
+/// let original = Header(0x1004);
+/// for _ in 0..100 {
+///     let mutated = mutate_field(original, &mut rng);
+///     let changed = [mutated.version() != 4, mutated.length() != 0x10];
+///     assert_eq!(changed.iter().filter(|&&c| c).count(), 1);
+///     assert_eq!(mutated.0 & 0x00f0, 0);
+/// }
+/// ```
+pub fn mutate_field<B, T, R>(value: B, rng: &mut R) -> B
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits + TruncateInto<T>,
+    R: Rng + ?Sized,
+{
+    if B::LAYOUT.is_empty() {
+        return value;
+    }
+    let field = &B::LAYOUT[rng.gen_range(0..B::LAYOUT.len())];
+    let old = crate::raw_field(value, field.lsb, field.msb);
+    let max = field_max(field);
+    let new = match rng.gen_range(0..8) {
+        0 => max,
+        1 => 0,
+        2 => old.wrapping_add(1) & max,
+        3 => old.wrapping_sub(1) & max,
+        _ => old,
+    };
+    let new = if new == old { old ^ rng.gen_range(1..=max) } else { new };
+    crate::with_raw_field(value, field.lsb, field.msb, new)
+}
+
+/// Invert one randomly chosen bit that is part of a field, for structure-aware fuzzing.
+/// Bits that aren't part of any field are never flipped. Returns the value unchanged if
+/// the bitfield has no fields.
+pub fn flip_field_bit<B, T, R>(value: B, rng: &mut R) -> B
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits + TruncateInto<T>,
+    R: Rng + ?Sized,
+{
+    let mut mask = crate::field_mask::<B, T>().into_u128();
+    if mask == 0 {
+        return value;
+    }
+    // Clear the lowest bits of the mask until the chosen one is the lowest left
+    for _ in 0..rng.gen_range(0..mask.count_ones()) {
+        mask &= mask - 1;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let bit = mask.trailing_zeros() as u8;
+    let old = crate::raw_field(value, bit, bit + 1);
+    crate::with_raw_field(value, bit, bit + 1, old ^ 1)
+}
+
+/// The largest value of a field, with all of its bits set
+fn field_max(field: &FieldInfo) -> u128 {
+    u128::MAX >> (128 - field.width())
+}
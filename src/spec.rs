@@ -0,0 +1,101 @@
+use crate::FieldInfo;
+use core::ops::Range;
+
+/// The position of a field within any bitfield, as a value that can be stored in tables
+/// and passed to generic code.
+///
+/// Fields described this way can be read and written on any [`Bitfield`](crate::Bitfield)
+/// with [`field`](crate::Bitfield::field), [`with_field`](crate::Bitfield::with_field),
+/// and [`set_field`](crate::Bitfield::set_field). Field values are always given as a
+/// `u128`.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, Bitfield, FieldSpec};
+/// bitfield! {
+///     pub struct Timer(u32) {
+///         [0..8]   pub prescale: u8,
+///         [8..20]  pub reload: u16,
+///         [31]     pub enable,
+///     }
+/// }
+///
+/// const PROGRAM: [(FieldSpec, u128); 3] = [
+///     (FieldSpec::new(0, 8), 0x40),
+///     (FieldSpec::from_range(8..20), 0x123),
+///     (FieldSpec::new(31, 1), 1),
+/// ];
+///
+/// fn program<B: Bitfield<u32>>(reg: &mut B, table: &[(FieldSpec, u128)]) {
+///     for &(spec, value) in table {
+///         reg.set_field(spec, value);
+///     }
+/// }
+///
+/// let mut timer = Timer::default();
+/// program(&mut timer, &PROGRAM);
+/// assert_eq!(timer, Timer(0x8001_2340));
+/// assert_eq!(timer.field(FieldSpec::new(8, 12)), 0x123);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldSpec {
+    /// The least significant bit of the field
+    pub lsb: u8,
+    /// The number of bits in the field
+    pub width: u8,
+}
+
+impl FieldSpec {
+    /// Describe the field of `width` bits starting at `lsb`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is zero or the field extends past bit 128.
+    #[must_use]
+    pub const fn new(lsb: u8, width: u8) -> Self {
+        assert!(width > 0, "field must be at least one bit wide");
+        assert!(lsb as u16 + width as u16 <= 128, "field extends past bit 128");
+        Self { lsb, width }
+    }
+
+    /// Describe the field covering the bits `range`, like the `*_RANGE` constants
+    /// generated by the [`bitfield`](crate::bitfield) macro
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is empty or extends past bit 128.
+    #[must_use]
+    pub const fn from_range(range: Range<u8>) -> Self {
+        assert!(range.end > range.start, "field must be at least one bit wide");
+        Self::new(range.start, range.end - range.start)
+    }
+
+    /// One more than the most significant bit of the field, like the end of a `Range`
+    #[must_use]
+    pub const fn msb(&self) -> u8 {
+        self.lsb + self.width
+    }
+
+    /// The bits covered by the field
+    #[must_use]
+    pub const fn range(&self) -> Range<u8> {
+        self.lsb..self.msb()
+    }
+
+    /// A mask of the bits in the field
+    #[must_use]
+    pub const fn mask(&self) -> u128 {
+        (u128::MAX >> (128 - self.width)) << self.lsb
+    }
+}
+
+impl From<FieldInfo> for FieldSpec {
+    fn from(info: FieldInfo) -> Self {
+        Self::new(info.lsb, info.width())
+    }
+}
+
+impl From<Range<u8>> for FieldSpec {
+    fn from(range: Range<u8>) -> Self {
+        Self::from_range(range)
+    }
+}
@@ -0,0 +1,141 @@
+use crate::Bitfield;
+
+/// An integer type that can be split into high and low halves of the same width.
+///
+/// This is implemented for the unsigned integer types from `u16` to `u128`, and is used
+/// for bitfields stored in a pair of registers. See [`RegisterPair`].
+pub trait SplitHalves
+where
+    Self: Sized + Copy,
+{
+    /// The type of each half, with half the bits of `Self`
+    type Half: Copy + Eq;
+
+    /// Combine the most significant and least significant halves of a value
+    fn from_halves(high: Self::Half, low: Self::Half) -> Self;
+
+    /// Split the value into its most significant and least significant halves
+    fn into_halves(self) -> (Self::Half, Self::Half);
+}
+
+macro_rules! split_halves_impl {
+    ($type:ty => $half:ty) => {
+        impl SplitHalves for $type {
+            type Half = $half;
+
+            #[inline(always)]
+            fn from_halves(high: $half, low: $half) -> Self {
+                <$type>::from(high) << <$half>::BITS | <$type>::from(low)
+            }
+
+            #[inline(always)]
+            #[allow(clippy::cast_possible_truncation)]
+            fn into_halves(self) -> ($half, $half) {
+                ((self >> <$half>::BITS) as $half, self as $half)
+            }
+        }
+    };
+}
+
+split_halves_impl!(u16 => u8);
+split_halves_impl!(u32 => u16);
+split_halves_impl!(u64 => u32);
+split_halves_impl!(u128 => u64);
+
+/// Access to a value that is stored in two registers, each holding half of its bits.
+///
+/// Implement the methods to read and write each half, and the provided methods read and
+/// write whole bitfields. This is common for 64-bit timer counters and MSR-style
+/// register pairs on 32-bit buses:
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, RegisterPair};
+/// bitfield! {
+///     pub struct Timer(u64) {
+///         [0..48]  pub count: u64,
+///         [63]     pub overflow,
+///     }
+/// }
+///
+/// struct TimerRegs {
+///     high: u32,
+///     low: u32,
+/// }
+///
+/// impl RegisterPair for TimerRegs {
+///     type Half = u32;
+///     fn read_high(&mut self) -> u32 { self.high }
+///     fn read_low(&mut self) -> u32 { self.low }
+///     fn write_high(&mut self, value: u32) { self.high = value }
+///     fn write_low(&mut self, value: u32) { self.low = value }
+/// }
+///
+/// let mut regs = TimerRegs { high: 0x8000_0001, low: 0x2345_6789 };
+/// let timer: Timer = regs.read_value();
+/// assert_eq!(timer.count(), 0x1_2345_6789);
+/// assert!(timer.overflow());
+///
+/// regs.modify_value(|t: &mut Timer| t.set_overflow(false));
+/// assert_eq!((regs.high, regs.low), (0x0000_0001, 0x2345_6789));
+/// ```
+pub trait RegisterPair {
+    /// The type of each register, with half the bits of the whole value
+    type Half: Copy + Eq;
+
+    /// Read the register holding the most significant half of the value
+    fn read_high(&mut self) -> Self::Half;
+
+    /// Read the register holding the least significant half of the value
+    fn read_low(&mut self) -> Self::Half;
+
+    /// Write the register holding the most significant half of the value
+    fn write_high(&mut self, value: Self::Half);
+
+    /// Write the register holding the least significant half of the value
+    fn write_low(&mut self, value: Self::Half);
+
+    /// Read the value of both registers as a bitfield.
+    ///
+    /// The high register is read before and after the low one, and both are read
+    /// again if it changed, so a counter that carries into the high half between the
+    /// two reads doesn't produce a torn value.
+    fn read_value<B, T>(&mut self) -> B
+    where
+        B: Bitfield<T>,
+        T: SplitHalves<Half = Self::Half>,
+    {
+        let mut high = self.read_high();
+        loop {
+            let low = self.read_low();
+            let next_high = self.read_high();
+            if next_high == high {
+                return B::new(T::from_halves(high, low));
+            }
+            high = next_high;
+        }
+    }
+
+    /// Write a bitfield to both registers, the low register first
+    fn write_value<B, T>(&mut self, value: B)
+    where
+        B: Bitfield<T>,
+        T: SplitHalves<Half = Self::Half>,
+    {
+        let (high, low) = value.value().into_halves();
+        self.write_low(low);
+        self.write_high(high);
+    }
+
+    /// Read the value of both registers, modify it with the given function, and write
+    /// it back
+    fn modify_value<B, T, F>(&mut self, f: F)
+    where
+        B: Bitfield<T>,
+        T: SplitHalves<Half = Self::Half>,
+        F: FnOnce(&mut B),
+    {
+        let mut value = self.read_value();
+        f(&mut value);
+        self.write_value(value);
+    }
+}
@@ -0,0 +1,358 @@
+//! Compatibility with the register API generated by
+//! [svd2rust](https://docs.rs/svd2rust).
+//!
+//! This allows peripherals to be migrated from an svd2rust peripheral access crate one
+//! register at a time. A [`Reg`] has the same `read`, `write`, and `modify` methods as
+//! an svd2rust register, the traits in this module give field values the same reader
+//! methods, like `bits()` and `bit_is_set()`, and [`svd_writer`](crate::svd_writer)
+//! defines the field writers of [`W`], like `w.en().set_bit()`. Call sites are
+//! unchanged once the writer trait is in scope:
+//!
+//! ```
+//! # use tartan_bitfield::{bitfield, svd_writer};
+//! use tartan_bitfield::svd::{BitReader, FieldReader, Reg};
+//!
+//! bitfield! {
+//!     pub struct Cr(u32) {
+//!         [0]    pub en,
+//!         [4..8] pub mode,
+//!     }
+//! }
+//!
+//! svd_writer!(pub trait CrW for Cr);
+//!
+//! let cr = Reg::<Cr, u32>::default();
+//! cr.write(|w| w.en().set_bit());
+//! cr.modify(|r, w| w.mode().bits(r.mode().bits() + 3));
+//! assert!(cr.read().en().bit_is_set());
+//! assert_eq!(cr.read().mode().bits(), 3);
+//! assert_eq!(cr.read().bits(), 0x31);
+//! ```
+//!
+//! The writer also dereferences to the bitfield, so its setters can be used directly,
+//! and unlike svd2rust, [`W::bits`] is safe, since every value is a valid bitfield.
+//!
+//! By default, [`Reg`] keeps its value in a [`Cell`], which is suitable for simulated
+//! peripherals and tests. With the `vcell` feature, a [`VolatileReg`] keeps it in a
+//! `VolatileCell` from [vcell](https://docs.rs/vcell), like an svd2rust register, so a
+//! `#[repr(C)]` block of them can be placed at the address of a memory-mapped
+//! peripheral. Creating the reference to that address is up to the caller, just as it
+//! is in a peripheral access crate.
+
+use crate::uint::UInt;
+use crate::Bitfield;
+use core::cell::Cell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+
+/// Storage with interior mutability for the value of a [`Reg`].
+pub trait RegCell<T> {
+    /// Create the storage with the given value
+    fn new(value: T) -> Self;
+
+    /// Read the stored value
+    fn get(&self) -> T;
+
+    /// Replace the stored value
+    fn set(&self, value: T);
+}
+
+impl<T> RegCell<T> for Cell<T>
+where
+    T: Copy,
+{
+    #[inline(always)]
+    fn new(value: T) -> Self {
+        Cell::new(value)
+    }
+
+    #[inline(always)]
+    fn get(&self) -> T {
+        Cell::get(self)
+    }
+
+    #[inline(always)]
+    fn set(&self, value: T) {
+        Cell::set(self, value);
+    }
+}
+
+#[cfg(feature = "vcell")]
+impl<T> RegCell<T> for vcell::VolatileCell<T>
+where
+    T: Copy,
+{
+    #[inline(always)]
+    fn new(value: T) -> Self {
+        vcell::VolatileCell::new(value)
+    }
+
+    #[inline(always)]
+    fn get(&self) -> T {
+        vcell::VolatileCell::get(self)
+    }
+
+    #[inline(always)]
+    fn set(&self, value: T) {
+        vcell::VolatileCell::set(self, value);
+    }
+}
+
+/// A register holding a bitfield of type `B` with underlying type `T`, with the same
+/// access methods as an svd2rust register. The value is stored in `C`, which has the
+/// same layout as `T`.
+#[repr(transparent)]
+pub struct Reg<B, T, C = Cell<T>> {
+    value: C,
+    bitfield: PhantomData<fn(B) -> (B, T)>,
+}
+
+/// A [`Reg`] that reads and writes its value with volatile operations, for memory-mapped
+/// registers.
+#[cfg(feature = "vcell")]
+pub type VolatileReg<B, T> = Reg<B, T, vcell::VolatileCell<T>>;
+
+impl<B, T, C> Reg<B, T, C>
+where
+    B: Bitfield<T>,
+    T: Copy,
+    C: RegCell<T>,
+{
+    /// Create a register with the given value
+    #[inline(always)]
+    pub fn new(value: B) -> Self {
+        Self { value: C::new(value.value()), bitfield: PhantomData }
+    }
+
+    /// Read the current value of the register
+    #[inline(always)]
+    pub fn read(&self) -> B {
+        B::new(self.value.get())
+    }
+
+    /// Write the value built by the function, which is given a writer that starts from
+    /// the default value of the bitfield
+    #[inline(always)]
+    pub fn write<F>(&self, f: F)
+    where
+        F: FnOnce(&mut W<B>) -> &mut W<B>,
+    {
+        let mut w = W { value: B::default() };
+        f(&mut w);
+        self.value.set(w.value.value());
+    }
+
+    /// Write the value built by the function, which is given the current value of the
+    /// register to read, and a writer that starts from the current value
+    #[inline(always)]
+    pub fn modify<F>(&self, f: F)
+    where
+        F: for<'w> FnOnce(&B, &'w mut W<B>) -> &'w mut W<B>,
+    {
+        let current = self.read();
+        let mut w = W { value: current };
+        f(&current, &mut w);
+        self.value.set(w.value.value());
+    }
+
+    /// Write the default value of the bitfield
+    #[inline(always)]
+    pub fn reset(&self) {
+        self.value.set(B::default().value());
+    }
+}
+
+impl<B, T, C> Default for Reg<B, T, C>
+where
+    B: Bitfield<T>,
+    T: Copy,
+    C: RegCell<T>,
+{
+    fn default() -> Self {
+        Self::new(B::default())
+    }
+}
+
+impl<B, T, C> fmt::Debug for Reg<B, T, C>
+where
+    B: Bitfield<T>,
+    T: Copy,
+    C: RegCell<T>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Reg").field(&self.read()).finish()
+    }
+}
+
+/// The writer given to the closures of [`Reg::write`] and [`Reg::modify`], holding the
+/// value to write.
+///
+/// Field writers like `w.en()` are methods of a trait defined with
+/// [`svd_writer`](crate::svd_writer). The writer also dereferences to the bitfield.
+pub struct W<B> {
+    value: B,
+}
+
+impl<B> W<B>
+where
+    B: Copy,
+{
+    /// Replace the whole value with the given underlying bits
+    #[inline(always)]
+    pub fn bits<T>(&mut self, bits: T) -> &mut Self
+    where
+        B: Bitfield<T>,
+    {
+        self.value = B::new(bits);
+        self
+    }
+
+    /// Replace the whole value
+    #[inline(always)]
+    pub fn set(&mut self, value: B) -> &mut Self {
+        self.value = value;
+        self
+    }
+}
+
+impl<B> Deref for W<B> {
+    type Target = B;
+
+    #[inline(always)]
+    fn deref(&self) -> &B {
+        &self.value
+    }
+}
+
+impl<B> DerefMut for W<B> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut B {
+        &mut self.value
+    }
+}
+
+impl<B> fmt::Debug for W<B>
+where
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("W").field(&self.value).finish()
+    }
+}
+
+/// A writer for one field of the value in a [`W`], returned by the field methods of a
+/// trait defined with [`svd_writer`](crate::svd_writer).
+pub struct FieldWriter<'a, B, V> {
+    w: &'a mut W<B>,
+    set: fn(&mut B, V),
+}
+
+impl<'a, B, V> FieldWriter<'a, B, V> {
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn new(w: &'a mut W<B>, set: fn(&mut B, V)) -> Self {
+        Self { w, set }
+    }
+
+    /// Set the field to the given value
+    #[inline(always)]
+    pub fn bits(self, value: V) -> &'a mut W<B> {
+        (self.set)(&mut self.w.value, value);
+        self.w
+    }
+
+    /// Set the field to the given value. The same as [`bits`](Self::bits), for fields
+    /// with an enumerated type.
+    #[inline(always)]
+    pub fn variant(self, value: V) -> &'a mut W<B> {
+        self.bits(value)
+    }
+}
+
+impl<'a, B> FieldWriter<'a, B, bool> {
+    /// Set the bit to the given value
+    #[inline(always)]
+    pub fn bit(self, value: bool) -> &'a mut W<B> {
+        self.bits(value)
+    }
+
+    /// Set the bit
+    #[inline(always)]
+    pub fn set_bit(self) -> &'a mut W<B> {
+        self.bits(true)
+    }
+
+    /// Clear the bit
+    #[inline(always)]
+    pub fn clear_bit(self) -> &'a mut W<B> {
+        self.bits(false)
+    }
+}
+
+/// The methods of an svd2rust single-bit field reader, for `bool` field values.
+pub trait BitReader {
+    /// The value of the bit
+    fn bit(self) -> bool;
+
+    /// True if the bit is set
+    fn bit_is_set(self) -> bool;
+
+    /// True if the bit is clear
+    fn bit_is_clear(self) -> bool;
+}
+
+impl BitReader for bool {
+    #[inline(always)]
+    fn bit(self) -> bool {
+        self
+    }
+
+    #[inline(always)]
+    fn bit_is_set(self) -> bool {
+        self
+    }
+
+    #[inline(always)]
+    fn bit_is_clear(self) -> bool {
+        !self
+    }
+}
+
+/// The methods of an svd2rust multi-bit field reader, for integer field values and
+/// whole bitfields.
+pub trait FieldReader {
+    /// The primitive integer type of the raw bits
+    type Bits;
+
+    /// The raw bits of the value
+    fn bits(self) -> Self::Bits;
+}
+
+macro_rules! field_reader_impl {
+    ($type:ty) => {
+        impl FieldReader for $type {
+            type Bits = $type;
+
+            #[inline(always)]
+            fn bits(self) -> $type {
+                self
+            }
+        }
+
+        impl<const BITS: usize> FieldReader for UInt<$type, BITS> {
+            type Bits = $type;
+
+            #[inline(always)]
+            fn bits(self) -> $type {
+                self.value()
+            }
+        }
+    };
+}
+
+field_reader_impl!(u8);
+field_reader_impl!(u16);
+field_reader_impl!(u32);
+field_reader_impl!(u64);
+field_reader_impl!(u128);
@@ -0,0 +1,122 @@
+use crate::{Bitfield, FieldLayout, FieldStorage, PackedBits};
+use core::fmt;
+use core::marker::PhantomData;
+
+/// A bitfield value that reports every field that changes when it is modified.
+///
+/// Each change is passed to the logging function as a line like
+/// `CR.enable: false -> true` or `CR.mode: 0x1 -> 0x3`, with single-bit fields shown as
+/// `bool` and other fields shown as their underlying integer values in hexadecimal.
+/// Fields that were written with the same value they already had are not reported.
+///
+/// The logging function takes [`fmt::Arguments`], so it works with any logging
+/// framework without a dependency on it, e.g. `|args| log::debug!("{args}")`, and
+/// nothing is formatted unless a field actually changes.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, Traced};
+/// bitfield! {
+///     pub struct Control(u32) {
+///         [0]    pub enable,
+///         [4..8] pub mode,
+///         [8..16] pub divider: u8,
+///     }
+/// }
+///
+/// let mut log = Vec::new();
+/// let mut cr = Traced::new("CR", Control(0x0410), |args| log.push(args.to_string()));
+/// cr.update(|c| {
+///     c.set_enable(true);
+///     c.set_mode(3);
+///     c.set_divider(4);
+/// });
+/// assert_eq!(cr.get(), Control(0x0431));
+/// drop(cr);
+///
+/// assert_eq!(log, ["CR.enable: false -> true", "CR.mode: 0x1 -> 0x3"]);
+/// ```
+pub struct Traced<B, T, L> {
+    value: B,
+    name: &'static str,
+    log: L,
+    underlying: PhantomData<T>,
+}
+
+impl<B, T, L> Traced<B, T, L>
+where
+    B: Bitfield<T> + FieldLayout,
+    T: FieldStorage + Into<T::Storage>,
+    T::Storage: PackedBits + Copy + Default + Eq + fmt::LowerHex,
+    L: FnMut(fmt::Arguments),
+{
+    /// Wrap a bitfield value, using `name` to identify it in the log
+    #[inline(always)]
+    pub fn new(name: &'static str, value: B, log: L) -> Self {
+        Self { value, name, log, underlying: PhantomData }
+    }
+
+    /// The current value of the bitfield
+    #[inline(always)]
+    pub fn get(&self) -> B {
+        self.value
+    }
+
+    /// Replace the bitfield with the given value, logging any fields that change
+    pub fn set(&mut self, value: B) {
+        let old = crate::packed_value(self.value);
+        let new = crate::packed_value(value);
+        self.value = value;
+        if old == new {
+            return;
+        }
+        for field in B::LAYOUT {
+            let old = old.packed_field(field.lsb, field.msb);
+            let new = new.packed_field(field.lsb, field.msb);
+            if old == new {
+                continue;
+            }
+            let zero = T::Storage::default();
+            if field.flag {
+                (self.log)(format_args!(
+                    "{}.{}: {} -> {}",
+                    self.name,
+                    field.name,
+                    old != zero,
+                    new != zero,
+                ));
+            } else {
+                (self.log)(format_args!(
+                    "{}.{}: {:#x} -> {:#x}",
+                    self.name, field.name, old, new,
+                ));
+            }
+        }
+    }
+
+    /// Modify the bitfield with the given function, typically by calling its `set_`
+    /// methods. Changed fields are logged after the function returns.
+    pub fn update<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut B) -> R,
+    {
+        let mut bitfield = self.value;
+        let result = f(&mut bitfield);
+        self.set(bitfield);
+        result
+    }
+
+    /// Stop tracing and return the current value of the bitfield
+    #[inline(always)]
+    pub fn into_inner(self) -> B {
+        self.value
+    }
+}
+
+impl<B, T, L> fmt::Debug for Traced<B, T, L>
+where
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Traced").field(&self.name).field(&self.value).finish()
+    }
+}
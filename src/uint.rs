@@ -0,0 +1,357 @@
+//! Unsigned integers with an exact number of bits.
+//!
+//! These are useful as the interface type of a field, since a setter that takes a
+//! [`u3`] can't be given a value that doesn't fit in the field:
+//!
+//! ```
+//! # use tartan_bitfield::bitfield;
+//! use tartan_bitfield::uint::{u3, u12};
+//!
+//! bitfield! {
+//!     pub struct Example(u16) {
+//!         [0..3]  pub mode: u3,
+//!         [4..16] pub count: u16 as u12,
+//!     }
+//! }
+//!
+//! let x = Example::default().with_mode(u3::new(5)).with_count(u12::MAX);
+//! assert_eq!(x, Example(0xfff5));
+//! assert_eq!(x.mode().value(), 5);
+//! assert_eq!(u3::try_new(8), None);
+//! ```
+//!
+//! The field must be exactly as wide as the type when it is declared without an
+//! underlying type, like `mode` above.
+//!
+//! They can also be the underlying type of a bitfield, for protocols with odd-sized
+//! words. Fields must fit within the declared number of bits:
+//!
+//! ```
+//! # use tartan_bitfield::bitfield;
+//! use tartan_bitfield::uint::u24;
+//!
+//! bitfield! {
+//!     pub struct Sample(u24) {
+//!         [0..20]  pub level,
+//!         [20..24] pub channel,
+//!     }
+//! }
+//!
+//! let x = Sample(u24::new(0x3f_ffff));
+//! assert_eq!(x.channel(), 3);
+//! assert_eq!(x.with_channel(0xf).0, u24::MAX);
+//! ```
+//!
+//! With the `ux` feature, the integers of the [ux](https://docs.rs/ux) crate, like
+//! `ux::u3`, can be used as the interface type of a field the same way.
+
+use crate::{
+    FieldConversion, FieldConversionDirect, FieldStorage, FromStrRadix, TruncateInto,
+};
+use core::fmt;
+use core::num::ParseIntError;
+
+/// An unsigned integer with exactly `BITS` bits, stored in the low bits of the primitive
+/// type `T`.
+///
+/// Every value is in range, so conversion into `T` always succeeds, but conversion from
+/// `T` must go through a function that checks or masks the value. See the
+/// [module documentation](self) for type aliases like [`u3`].
+#[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct UInt<T, const BITS: usize>(T);
+
+/// Error returned when converting a value that doesn't fit in the number of bits of a
+/// [`UInt`].
+///
+/// ```
+/// # use tartan_bitfield::uint::{u3, OutOfRangeError};
+/// assert_eq!(u3::try_from(5), Ok(u3::new(5)));
+/// assert_eq!(u3::try_from(8), Err(OutOfRangeError));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OutOfRangeError;
+
+impl fmt::Display for OutOfRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("value does not fit in the number of bits")
+    }
+}
+
+impl core::error::Error for OutOfRangeError {}
+
+/// Error returned when parsing a [`UInt`] from a string.
+///
+/// ```
+/// # use tartan_bitfield::FromStrRadix;
+/// # use tartan_bitfield::uint::{u3, ParseUIntError};
+/// assert_eq!(u3::from_str_radix("7", 10), Ok(u3::new(7)));
+/// assert_eq!(u3::from_str_radix("8", 10), Err(ParseUIntError::OutOfRange));
+/// assert!(matches!(u3::from_str_radix("x", 10), Err(ParseUIntError::Invalid(_))));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseUIntError {
+    /// The string is not a valid integer of the primitive type
+    Invalid(ParseIntError),
+    /// The value does not fit in the number of bits
+    OutOfRange,
+}
+
+impl From<ParseIntError> for ParseUIntError {
+    fn from(error: ParseIntError) -> Self {
+        Self::Invalid(error)
+    }
+}
+
+impl fmt::Display for ParseUIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Invalid(error) => error.fmt(f),
+            Self::OutOfRange => OutOfRangeError.fmt(f),
+        }
+    }
+}
+
+impl core::error::Error for ParseUIntError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Invalid(error) => Some(error),
+            Self::OutOfRange => None,
+        }
+    }
+}
+
+macro_rules! uint_impl {
+    ($type:ty) => {
+        impl<const BITS: usize> UInt<$type, BITS> {
+            const VALID: () = assert!(
+                BITS > 0 && BITS <= <$type>::BITS as usize,
+                "BITS must be between 1 and the number of bits in the storage type",
+            );
+
+            /// The smallest value, zero
+            pub const MIN: Self = Self(0);
+
+            /// The largest value, with all `BITS` bits set
+            #[allow(clippy::cast_possible_truncation)]
+            pub const MAX: Self = {
+                let () = Self::VALID;
+                Self(<$type>::MAX >> (<$type>::BITS - BITS as u32))
+            };
+
+            /// Wrap a value that fits in `BITS` bits.
+            ///
+            /// # Panics
+            ///
+            /// Panics if the value is greater than [`MAX`](Self::MAX).
+            #[must_use]
+            pub const fn new(value: $type) -> Self {
+                match Self::try_new(value) {
+                    Some(x) => x,
+                    None => panic!("value does not fit in the number of bits"),
+                }
+            }
+
+            /// Wrap a value if it fits in `BITS` bits.
+            #[must_use]
+            pub const fn try_new(value: $type) -> Option<Self> {
+                if value <= Self::MAX.0 {
+                    Some(Self(value))
+                } else {
+                    None
+                }
+            }
+
+            /// Wrap the low `BITS` bits of the value, discarding the rest.
+            #[must_use]
+            pub const fn new_masked(value: $type) -> Self {
+                Self(value & Self::MAX.0)
+            }
+
+            /// The value as the primitive storage type
+            #[must_use]
+            pub const fn value(self) -> $type {
+                self.0
+            }
+        }
+
+        impl<const BITS: usize> TryFrom<$type> for UInt<$type, BITS> {
+            type Error = OutOfRangeError;
+
+            #[inline(always)]
+            fn try_from(value: $type) -> Result<Self, OutOfRangeError> {
+                Self::try_new(value).ok_or(OutOfRangeError)
+            }
+        }
+
+        impl<const BITS: usize> From<UInt<$type, BITS>> for $type {
+            #[inline(always)]
+            fn from(value: UInt<$type, BITS>) -> Self {
+                value.0
+            }
+        }
+
+        impl<const BITS: usize> TruncateInto<UInt<$type, BITS>> for $type {
+            #[inline(always)]
+            fn truncate_into(self) -> UInt<$type, BITS> {
+                UInt::<$type, BITS>::new_masked(self)
+            }
+        }
+
+        impl<const BITS: usize> FromStrRadix for UInt<$type, BITS> {
+            type Err = ParseUIntError;
+
+            fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseUIntError> {
+                let value = <$type>::from_str_radix(s, radix)?;
+                Self::try_new(value).ok_or(ParseUIntError::OutOfRange)
+            }
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        impl<const BITS: usize> FieldStorage for UInt<$type, BITS> {
+            type Storage = $type;
+            const MIN_BITS: u8 = BITS as u8;
+            const MAX_BITS: u8 = BITS as u8;
+        }
+
+        // A field declared with `as` can be wider than the type, so its value is masked
+        // rather than checked, like the rest of the bits outside of a field
+        impl<const BITS: usize> FieldConversionDirect<$type, UInt<$type, BITS>>
+            for FieldConversion<$type, UInt<$type, BITS>>
+        {
+            #[inline(always)]
+            fn convert_field(&self, value: $type) -> UInt<$type, BITS> {
+                UInt::<$type, BITS>::new_masked(value)
+            }
+        }
+
+        impl<const BITS: usize> FieldConversionDirect<UInt<$type, BITS>, $type>
+            for FieldConversion<UInt<$type, BITS>, $type>
+        {
+            #[inline(always)]
+            fn convert_field(&self, value: UInt<$type, BITS>) -> $type {
+                value.0
+            }
+        }
+    };
+}
+
+uint_impl!(u8);
+uint_impl!(u16);
+uint_impl!(u32);
+uint_impl!(u64);
+uint_impl!(u128);
+
+macro_rules! uint_fmt_impl {
+    ($($trait:ident)*) => {
+        $(
+            impl<T, const BITS: usize> fmt::$trait for UInt<T, BITS>
+            where
+                T: fmt::$trait,
+            {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    self.0.fmt(f)
+                }
+            }
+        )*
+    };
+}
+
+uint_fmt_impl!(Debug Display Binary Octal LowerHex UpperHex);
+
+macro_rules! uint_aliases {
+    ($type:ty => $( $bits:literal )*) => {
+        paste::paste! {
+            $(
+                #[doc = "An unsigned integer with exactly " $bits " bits"]
+                #[allow(non_camel_case_types)]
+                pub type [< u $bits >] = UInt<$type, $bits>;
+            )*
+        }
+    };
+}
+
+uint_aliases!(u8 => 1 2 3 4 5 6 7);
+uint_aliases!(u16 => 9 10 11 12 13 14 15);
+uint_aliases!(u32 =>
+    17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+);
+uint_aliases!(u64 =>
+    33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48
+    49 50 51 52 53 54 55 56 57 58 59 60 61 62 63
+);
+uint_aliases!(u128 =>
+    65 66 67 68 69 70 71 72 73 74 75 76 77 78 79 80
+    81 82 83 84 85 86 87 88 89 90 91 92 93 94 95 96
+    97 98 99 100 101 102 103 104 105 106 107 108 109 110 111 112
+    113 114 115 116 117 118 119 120 121 122 123 124 125 126 127
+);
+
+// With the `ux` feature, the integers of the `ux` crate work the same way as `UInt`, for
+// code that already uses them elsewhere
+#[cfg(feature = "ux")]
+macro_rules! ux_impl {
+    ($type:ty => $( $bits:literal )*) => {
+        paste::paste! {
+            $(
+                impl FieldStorage for ux::[< u $bits >] {
+                    type Storage = $type;
+                    const MIN_BITS: u8 = $bits;
+                    const MAX_BITS: u8 = $bits;
+                }
+
+                impl TruncateInto<ux::[< u $bits >]> for $type {
+                    #[inline(always)]
+                    fn truncate_into(self) -> ux::[< u $bits >] {
+                        let max = <$type>::from(ux::[< u $bits >]::MAX);
+                        ux::[< u $bits >]::new(self & max)
+                    }
+                }
+
+                impl FromStrRadix for ux::[< u $bits >] {
+                    type Err = ParseUIntError;
+
+                    fn from_str_radix(
+                        s: &str,
+                        radix: u32,
+                    ) -> Result<Self, ParseUIntError> {
+                        let value = <$type>::from_str_radix(s, radix)?;
+                        Self::try_from(value).map_err(|_| ParseUIntError::OutOfRange)
+                    }
+                }
+
+                impl FieldConversionDirect<$type, ux::[< u $bits >]>
+                    for FieldConversion<$type, ux::[< u $bits >]>
+                {
+                    #[inline(always)]
+                    fn convert_field(&self, value: $type) -> ux::[< u $bits >] {
+                        value.truncate_into()
+                    }
+                }
+
+                impl FieldConversionDirect<ux::[< u $bits >], $type>
+                    for FieldConversion<ux::[< u $bits >], $type>
+                {
+                    #[inline(always)]
+                    fn convert_field(&self, value: ux::[< u $bits >]) -> $type {
+                        value.into()
+                    }
+                }
+            )*
+        }
+    };
+}
+
+#[cfg(feature = "ux")]
+ux_impl!(u8 => 1 2 3 4 5 6 7);
+#[cfg(feature = "ux")]
+ux_impl!(u16 => 9 10 11 12 13 14 15);
+#[cfg(feature = "ux")]
+ux_impl!(u32 =>
+    17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+);
+#[cfg(feature = "ux")]
+ux_impl!(u64 =>
+    33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48
+    49 50 51 52 53 54 55 56 57 58 59 60 61 62 63
+);
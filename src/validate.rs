@@ -0,0 +1,152 @@
+use core::{fmt, ops};
+
+/// Error returned when a bitfield value fails one of the checks declared in the
+/// [`bitfield`](crate::bitfield) macro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationError {
+    /// The named field did not satisfy its constraint
+    Field(&'static str),
+    /// The named invariant function returned `false`
+    Invariant(&'static str),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Field(name) => {
+                write!(f, "field `{name}` does not satisfy its constraint")
+            }
+            Self::Invariant(name) => write!(f, "invariant `{name}` does not hold"),
+        }
+    }
+}
+
+impl core::error::Error for ValidationError {}
+
+/// Bitfield types that can check their fields against constraints declared with the
+/// type.
+///
+/// This is implemented by all types defined with the [`bitfield`](crate::bitfield)
+/// macro. A field can be given a constraint with a `where` clause after its type, which
+/// is any value implementing [`FieldCheck`]: usually a range or a function that returns
+/// a `bool`. Fields with constraints also get `try_set_` and `try_with_` setters that
+/// reject invalid values.
+///
+/// Constraints that span multiple fields can be checked by a function named in a
+/// `where` clause after the underlying type. It takes the bitfield value and returns
+/// `true` if it is valid. It is only called if all of the field constraints are
+/// satisfied.
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, Validate, ValidationError};
+/// bitfield! {
+///     pub struct Date(u16) {
+///         [0..5]  pub day: u8 where 1..=31,
+///         [5..9]  pub month: u8 where 1..=12,
+///         [9..16] pub year: u8,
+///     }
+/// }
+///
+/// bitfield! {
+///     pub struct Burst(u8) {
+///         [0..8] pub length: u8 where u8::is_power_of_two,
+///     }
+/// }
+///
+/// let date = Date::default().with_day(25).with_month(12);
+/// assert_eq!(date.validate(), Ok(()));
+/// assert_eq!(Date::default().validate(), Err(ValidationError::Field("day")));
+///
+/// let mut date = date;
+/// assert_eq!(date.try_set_month(13), Err(ValidationError::Field("month")));
+/// assert_eq!(date.month(), 12);
+///
+/// assert!(Burst::default().try_with_length(16).is_ok());
+/// assert!(Burst::default().try_with_length(12).is_err());
+///
+/// bitfield! {
+///     pub struct Clock(u16) where Self::check_config {
+///         [0..4]  pub prescaler: u8 where 1..,
+///         [4..12] pub divider: u8 where 1..,
+///     }
+/// }
+///
+/// impl Clock {
+///     fn check_config(self) -> bool {
+///         u16::from(self.prescaler()) * u16::from(self.divider()) <= 256
+///     }
+/// }
+///
+/// assert_eq!(Clock(0x0402).validate(), Ok(()));
+/// assert_eq!(
+///     Clock(0xff02).validate(),
+///     Err(ValidationError::Invariant("Self::check_config")),
+/// );
+/// ```
+///
+/// Values from untrusted sources can be checked as they are constructed with the
+/// generated `try_new` function, which returns the same errors as `validate`:
+///
+/// ```
+/// # use tartan_bitfield::{bitfield, ValidationError};
+/// # bitfield! {
+/// #     pub struct Date(u16) {
+/// #         [0..5]  pub day: u8 where 1..=31,
+/// #         [5..9]  pub month: u8 where 1..=12,
+/// #         [9..16] pub year: u8,
+/// #     }
+/// # }
+/// let stored: u16 = 0x3399;
+/// assert_eq!(Date::try_new(stored).map(|d| d.day()), Ok(25));
+/// assert_eq!(Date::try_new(0x01c1), Err(ValidationError::Field("month")));
+/// ```
+pub trait Validate {
+    /// Check every field that has a declared constraint, then the invariant function,
+    /// if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first field that does not satisfy its constraint, or
+    /// the invariant function if it fails.
+    fn validate(&self) -> Result<(), ValidationError>;
+}
+
+/// A constraint on the value of a bitfield field, as declared in the
+/// [`bitfield`](crate::bitfield) macro.
+///
+/// This is implemented for ranges, which check that the value is in range, and for
+/// functions and closures taking the value and returning `true` if it is valid.
+pub trait FieldCheck<T> {
+    /// True if the value satisfies the constraint
+    fn check(&self, value: T) -> bool;
+}
+
+impl<T, F> FieldCheck<T> for F
+where
+    F: Fn(T) -> bool,
+{
+    #[inline(always)]
+    fn check(&self, value: T) -> bool {
+        self(value)
+    }
+}
+
+macro_rules! range_check_impl {
+    ($range:ident) => {
+        impl<T> FieldCheck<T> for ops::$range<T>
+        where
+            T: PartialOrd,
+        {
+            #[inline(always)]
+            fn check(&self, value: T) -> bool {
+                self.contains(&value)
+            }
+        }
+    };
+}
+
+range_check_impl!(Range);
+range_check_impl!(RangeInclusive);
+range_check_impl!(RangeFrom);
+range_check_impl!(RangeTo);
+range_check_impl!(RangeToInclusive);
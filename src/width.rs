@@ -0,0 +1,153 @@
+use core::marker::PhantomData;
+
+/// The smallest unsigned integer type with at least `BITS` bits.
+///
+/// This is the type of a field declared in the [`bitfield`](crate::bitfield) macro with
+/// a bit range but no explicit type.
+///
+/// ```
+/// # use tartan_bitfield::SmallestUint;
+/// # use core::any::TypeId;
+/// assert_eq!(TypeId::of::<SmallestUint<1>>(), TypeId::of::<u8>());
+/// assert_eq!(TypeId::of::<SmallestUint<8>>(), TypeId::of::<u8>());
+/// assert_eq!(TypeId::of::<SmallestUint<9>>(), TypeId::of::<u16>());
+/// assert_eq!(TypeId::of::<SmallestUint<33>>(), TypeId::of::<u64>());
+/// assert_eq!(TypeId::of::<SmallestUint<128>>(), TypeId::of::<u128>());
+/// ```
+pub type SmallestUint<const BITS: usize> = <Width<BITS> as WidthType>::Type;
+
+/// The type of a field declared in the [`bitfield`](crate::bitfield) macro with a bit
+/// range but no explicit type: `bool` for a single bit, or the smallest unsigned integer
+/// type that fits.
+#[doc(hidden)]
+pub type DefaultField<const BITS: usize> = <Width<BITS> as WidthType>::Default;
+
+#[doc(hidden)]
+pub struct Width<const BITS: usize>;
+
+#[doc(hidden)]
+pub trait WidthType {
+    type Type;
+    type Default;
+}
+
+macro_rules! width_type_impl {
+    ($type:ty => $( $bits:literal )*) => {
+        $(
+            width_type_impl!($type, $type => $bits);
+        )*
+    };
+
+    ($type:ty, $default:ty => $bits:literal) => {
+        impl WidthType for Width<$bits> {
+            type Type = $type;
+            type Default = $default;
+        }
+    };
+}
+
+width_type_impl!(u8, bool => 1);
+width_type_impl!(u8 => 2 3 4 5 6 7 8);
+width_type_impl!(u16 => 9 10 11 12 13 14 15 16);
+width_type_impl!(u32 =>
+    17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+);
+width_type_impl!(u64 =>
+    33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48
+    49 50 51 52 53 54 55 56 57 58 59 60 61 62 63 64
+);
+width_type_impl!(u128 =>
+    65 66 67 68 69 70 71 72 73 74 75 76 77 78 79 80
+    81 82 83 84 85 86 87 88 89 90 91 92 93 94 95 96
+    97 98 99 100 101 102 103 104 105 106 107 108 109 110 111 112
+    113 114 115 116 117 118 119 120 121 122 123 124 125 126 127 128
+);
+
+/// Types that can be used for a field in the [`bitfield`](crate::bitfield) macro
+/// without an `A as B` clause.
+///
+/// This is implemented for unsigned integers, which are stored as themselves, for
+/// `bool`, which is stored as a single bit of a `u8`, and for all types defined with the
+/// [`bitfield`](crate::bitfield) macro, which are stored as their underlying type. That
+/// allows a bitfield to be nested in another with just its type name:
+///
+/// ```
+/// # use tartan_bitfield::bitfield;
+/// bitfield! {
+///     pub struct Outer(u16) {
+///         [0..8]  pub low,
+///         [8..14] pub inner: Inner,
+///     }
+/// }
+///
+/// bitfield! {
+///     pub struct Inner(u8) {
+///         [0..3] pub mode,
+///         [5]    pub enable,
+///     }
+/// }
+///
+/// let x = Outer(0x2312);
+/// assert_eq!(x.inner(), Inner(0x23));
+/// assert_eq!(x.inner().mode(), 3);
+/// assert!(x.inner().enable());
+/// ```
+///
+/// The width of the field is checked at compile time. It must be at least
+/// [`MIN_BITS`](Self::MIN_BITS), so that it covers every field of a nested bitfield,
+/// and at most [`MAX_BITS`](Self::MAX_BITS).
+pub trait FieldStorage {
+    /// The integer type that holds the bits of the field
+    type Storage;
+
+    /// The fewest bits a field of this type can have
+    const MIN_BITS: u8;
+
+    /// The most bits a field of this type can have
+    const MAX_BITS: u8;
+}
+
+macro_rules! field_storage_impl {
+    ($type:ty) => {
+        #[allow(clippy::cast_possible_truncation)]
+        impl FieldStorage for $type {
+            type Storage = $type;
+            const MIN_BITS: u8 = 1;
+            const MAX_BITS: u8 = <$type>::BITS as u8;
+        }
+    };
+}
+
+field_storage_impl!(u8);
+field_storage_impl!(u16);
+field_storage_impl!(u32);
+field_storage_impl!(u64);
+field_storage_impl!(u128);
+field_storage_impl!(usize);
+
+impl FieldStorage for bool {
+    type Storage = u8;
+    const MIN_BITS: u8 = 1;
+    const MAX_BITS: u8 = 1;
+}
+
+/// The fewest bits a field must have to hold every value of the interface type `T` of a
+/// field declared with `A as T`, or zero if it isn't known. Used by the compile-time
+/// width checks of the [`bitfield`](crate::bitfield) macro.
+///
+/// The width is known if `T` implements [`FieldStorage`], as nested bitfields and
+/// [`UInt`](crate::UInt) do. Otherwise, the associated constant of
+/// [`InterfaceWidthFallback`] is used instead.
+#[doc(hidden)]
+pub struct InterfaceWidth<T: ?Sized>(PhantomData<T>);
+
+impl<T: FieldStorage> InterfaceWidth<T> {
+    pub const MIN_BITS: u8 = T::MIN_BITS;
+}
+
+#[doc(hidden)]
+pub trait InterfaceWidthFallback {
+    const MIN_BITS: u8 = 0;
+}
+
+impl<T: ?Sized> InterfaceWidthFallback for InterfaceWidth<T> {}
@@ -0,0 +1,80 @@
+#![warn(clippy::pedantic)]
+
+use rkyv::rancor::Error;
+use rkyv::{Archive, Deserialize, Serialize};
+use tartan_bitfield::bitfield;
+use tartan_bitfield::uint::u24;
+
+bitfield! {
+    pub struct ArchiveStatus(u32) {
+        [0..12]  pub channel: u16,
+        [12..31] pub reading: u32,
+        [31]     pub overflow,
+    }
+}
+
+bitfield! {
+    pub struct ArchiveFlags(u8) {
+        [0] pub valid,
+        [7] pub last,
+    }
+}
+
+bitfield! {
+    pub struct ArchiveSample(u24) {
+        [0..16]  pub value: u16,
+        [16..24] pub gain: u8,
+    }
+}
+
+#[derive(Archive, Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Record {
+    status: ArchiveStatus,
+    flags: ArchiveFlags,
+    sample: ArchiveSample,
+    wide: Wide,
+}
+
+bitfield! {
+    pub struct Wide(u128) {
+        [100..128] pub high: u32,
+    }
+}
+
+#[test]
+fn test_archive_round_trip() {
+    let record = Record {
+        status: ArchiveStatus::default().with_channel(0x123).with_reading(0x4_5678),
+        flags: ArchiveFlags(0x81),
+        sample: ArchiveSample(u24::new(0x7f_1234)),
+        wide: Wide::default().with_high(0xabc_def0),
+    };
+    let bytes = rkyv::to_bytes::<Error>(&record).unwrap();
+
+    // Fields are read in place, without deserializing the record
+    let archived = rkyv::access::<ArchivedRecord, Error>(&bytes).unwrap();
+    let status = archived.status.get();
+    assert_eq!((status.channel(), status.reading()), (0x123, 0x4_5678));
+    assert!(!status.overflow());
+    assert!(archived.flags.get().last());
+    assert_eq!(archived.sample.get().gain(), 0x7f);
+    assert_eq!(archived.wide.get().high(), 0xabc_def0);
+    assert_eq!(
+        format!("{:?}", archived.flags),
+        "ArchivedBitfield(ArchiveFlags { <value>: 129, valid: true, last: true })",
+    );
+
+    let deserialized = rkyv::deserialize::<Record, Error>(archived).unwrap();
+    assert_eq!(deserialized, record);
+}
+
+#[test]
+fn test_archive_as_integer() {
+    // The archived form of a bitfield is the archived form of its underlying integer
+    let bytes = rkyv::to_bytes::<Error>(&ArchiveStatus(0x8765_4321)).unwrap();
+    let expected = rkyv::to_bytes::<Error>(&0x8765_4321_u32).unwrap();
+    assert_eq!(&*bytes, &*expected);
+
+    let archived = rkyv::access::<rkyv::Archived<ArchiveStatus>, Error>(&bytes).unwrap();
+    assert_eq!(archived.get(), ArchiveStatus(0x8765_4321));
+}
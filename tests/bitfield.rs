@@ -1,8 +1,35 @@
 #![warn(clippy::pedantic)]
 
 use core::mem;
-use tartan_bitfield::bitfield;
-use tartan_bitfield::{get_bit, get_bits, set_bit, set_bits};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use tartan_bitfield::uint::u24;
+use tartan_bitfield::{
+    assert_field_layout, bitfield, bitfield_accessors, bitfield_checksum,
+    bitfield_counters, bitfield_dispatch, bitfield_ext, bitfield_fields, bitfield_parity,
+    bitfield_pattern, bitfield_proofs, bitfield_scattered, bitfield_tests, field_range,
+    AllValues, Bitfield, BitfieldMut, Cached, DebugSummary, Emulated, EmulatorHooks,
+    FieldInfo, FieldLayout, FieldParseError, FieldSpec, FieldValue, FieldValues,
+    Observed, ParseFields, Traced, TruncateFrom, TruncateInto, Validate, ValidationError,
+    WideningInto, WriteFields,
+};
+use tartan_bitfield::{
+    bit_range_mask, bits_required, checked_get_bits, checked_set_bits, get_bit, get_bits,
+    get_bits_batch, get_bits_const, set_bit, set_bits, set_bits_const, sign_extend,
+};
+
+#[test]
+fn test_bits_required() {
+    const WIDTH: u8 = bits_required(1000);
+    assert_eq!(WIDTH, 10);
+
+    assert_eq!(bits_required(0), 0);
+    assert_eq!(bits_required(1), 1);
+    assert_eq!(bits_required(2), 2);
+    assert_eq!(bits_required(7), 3);
+    assert_eq!(bits_required(8), 4);
+    assert_eq!(bits_required(u128::from(u32::MAX)), 32);
+    assert_eq!(bits_required(u128::MAX), 128);
+}
 
 #[test]
 #[rustfmt::skip]
@@ -75,12 +102,14 @@ fn test_get_bit() {
 
 #[test]
 #[should_panic]
+#[allow(clippy::should_panic_without_expect)]
 fn test_get_bit_panic_u8_overflow() {
     let _ = get_bit(0_u8, 8);
 }
 
 #[test]
 #[should_panic]
+#[allow(clippy::should_panic_without_expect)]
 fn test_get_bit_panic_u128_overflow() {
     let _ = get_bit(0_u128, 128);
 }
@@ -166,12 +195,14 @@ fn test_set_bit() {
 
 #[test]
 #[should_panic]
+#[allow(clippy::should_panic_without_expect)]
 fn test_set_bit_panic_u8_overflow() {
     let _ = set_bit(0_u8, 8, true);
 }
 
 #[test]
 #[should_panic]
+#[allow(clippy::should_panic_without_expect)]
 fn test_set_bit_panic_u128_overflow() {
     let _ = set_bit(0_u128, 128, true);
 }
@@ -419,6 +450,121 @@ fn test_set_bits() {
     assert_eq!(set_bits(ones, 96, 128, n), 0xc35a_db69_ffff_ffff_ffff_ffff_ffff_ffff);
 }
 
+#[test]
+fn test_bit_range_mask() {
+    for lsb in 0..=8 {
+        for msb in lsb..=8 {
+            assert_eq!(bit_range_mask::<u8>(lsb, msb), set_bits(0, lsb, msb, 0xff));
+        }
+    }
+
+    assert_eq!(bit_range_mask::<u8>(4, 12), 0xf0);
+    assert_eq!(bit_range_mask::<u16>(3, 13), 0x1ff8);
+    assert_eq!(bit_range_mask::<u64>(63, 64), 1 << 63);
+    assert_eq!(bit_range_mask::<u128>(0, 128), u128::MAX);
+    assert_eq!(bit_range_mask::<u128>(128, 128), 0);
+}
+
+#[test]
+fn test_get_bits_batch() {
+    let words = [0x0000_0000_u32, 0xffff_ffff, 0x1234_5678, 0x8765_4321, 0xa5a5_5a5a];
+    let mut out = [0; 5];
+    for lsb in 0..=32 {
+        for msb in lsb..=32 {
+            get_bits_batch(&words, lsb, msb, &mut out);
+            for (&field, &word) in out.iter().zip(&words) {
+                assert_eq!(field, get_bits(word, lsb, msb));
+            }
+        }
+    }
+
+    let mut empty: [u8; 0] = [];
+    get_bits_batch(&[], 0, 8, &mut empty);
+}
+
+#[test]
+#[should_panic(expected = "same length")]
+fn test_get_bits_batch_panic_length() {
+    get_bits_batch(&[1_u8, 2], 0, 8, &mut [0_u8]);
+}
+
+#[test]
+fn test_checked_bits() {
+    for lsb in 0..=8 {
+        for msb in lsb..=8 {
+            assert_eq!(
+                checked_get_bits(0xa5_u8, lsb, msb),
+                Some(get_bits(0xa5_u8, lsb, msb))
+            );
+            assert_eq!(
+                checked_set_bits(0xa5_u8, lsb, msb, 0x3c),
+                Some(set_bits(0xa5_u8, lsb, msb, 0x3c)),
+            );
+        }
+    }
+
+    assert_eq!(checked_get_bits(0xffff_u16, 0, 17), None);
+    assert_eq!(checked_get_bits(0xffff_u16, 17, 17), None);
+    assert_eq!(checked_get_bits(0xffff_u16, 4, 3), None);
+    assert_eq!(checked_set_bits(0_u128, 120, 129, 1), None);
+    assert_eq!(checked_set_bits(0_u128, 127, 128, 1), Some(1 << 127));
+}
+
+#[test]
+fn test_sign_extend() {
+    for width in 1..8 {
+        let max = (1_u8 << (width - 1)) - 1;
+        let max_signed = i8::try_from(max).unwrap();
+        assert_eq!(sign_extend(max, width), max_signed);
+        assert_eq!(sign_extend(max + 1, width), -max_signed - 1);
+        assert_eq!(sign_extend(!0_u8, width), -1);
+    }
+
+    assert_eq!(sign_extend(0xff_u8, 0), 0);
+    assert_eq!(sign_extend(0xf0_u8, 4), 0);
+    assert_eq!(sign_extend(0x80_u8, 8), i8::MIN);
+    assert_eq!(sign_extend(0x80_u8, 9), i8::MIN);
+    assert_eq!(sign_extend(0x8000_u16, 16), i16::MIN);
+    assert_eq!(sign_extend(0x0001_ffff_u32, 17), -1);
+    assert_eq!(sign_extend(0x0000_ffff_u32, 17), 0xffff);
+    assert_eq!(sign_extend(1_u64 << 47, 48), -(1 << 47));
+    assert_eq!(sign_extend(u128::MAX >> 1, 127), -1);
+    assert_eq!(sign_extend(0x7f_usize, 7), -1);
+}
+
+#[test]
+#[allow(clippy::cast_possible_truncation)]
+fn test_bits_const() {
+    macro_rules! check_range {
+        ($type:ty, $lsb:literal, $msb:literal) => {
+            let nontrivial = 0xe781_2496_c35a_db69_0123_4567_89ab_cdef_u128 as $type;
+            for packed in [0, <$type>::MAX, nontrivial] {
+                assert_eq!(
+                    get_bits_const::<$lsb, $msb, _>(packed),
+                    get_bits(packed, $lsb, $msb)
+                );
+                for field in [0, <$type>::MAX, nontrivial.rotate_left(7)] {
+                    assert_eq!(
+                        set_bits_const::<$lsb, $msb, _>(packed, field),
+                        set_bits(packed, $lsb, $msb, field)
+                    );
+                }
+            }
+        };
+    }
+
+    check_range!(u8, 0, 0);
+    check_range!(u8, 0, 8);
+    check_range!(u8, 3, 7);
+    check_range!(u8, 8, 8);
+    check_range!(u32, 7, 19);
+    check_range!(u32, 31, 32);
+    check_range!(u64, 0, 63);
+    check_range!(u128, 0, 128);
+    check_range!(u128, 61, 67);
+    check_range!(u128, 127, 128);
+}
+
 bitfield! {
     pub struct BasicBitfieldTest(u32) {
         [24..32] pub a: u8,  // 8b, contains MSB
@@ -490,6 +636,7 @@ fn test_bitfield_basic() {
 }
 
 #[test]
+#[allow(clippy::explicit_iter_loop, clippy::ref_as_ptr)]
 fn test_bitfield_conversions() {
     let examples: &[u32] =
         &[0x0000_0000, 0xffff_ffff, 0xc35a_db69, 0x0123_4567, 0x89ab_cdef, 0xa5a5_a5a5];
@@ -507,6 +654,11 @@ fn test_bitfield_conversions() {
         assert_eq!(struct_a, struct_c);
         assert_eq!(struct_a, struct_d);
         assert_eq!(struct_a, struct_e);
+        let mut struct_f = BasicBitfieldTest::default();
+        struct_f.set_value(*inner_orig);
+        assert_eq!(struct_a, struct_f);
+        assert_eq!(struct_a.map(|v| v), struct_a);
+        assert_eq!(struct_a.map(|v| !v), BasicBitfieldTest(!*inner_orig));
 
         // Convert from bitfield struct to underlying representation
         let inner_a = struct_a.0;
@@ -521,3 +673,1705 @@ fn test_bitfield_conversions() {
         assert_eq!(*inner_orig, inner_e);
     }
 }
+
+#[test]
+fn test_bitfield_mut() {
+    let mut storage = [0_u32, 0xffff_ffff, 0xc35a_db69];
+
+    let mut view = BitfieldMut::<BasicBitfieldTest, _>::new(&mut storage[0]);
+    assert_eq!(view.get(), BasicBitfieldTest(0));
+    view.set_a(0xff);
+    view.set_d(true);
+    assert_eq!((view.a(), view.d()), (0xff, true));
+    assert_eq!(view.get(), BasicBitfieldTest(0xff00_0004));
+    // Changes are made in place, so the storage is up to date once the view isn't used
+    assert_eq!(storage[0], 0xff00_0004);
+
+    let mut view = BitfieldMut::<BasicBitfieldTest, _>::new(&mut storage[1]);
+    let old_b = view.update(|x| {
+        let b = x.b();
+        x.set_b(0);
+        b
+    });
+    assert_eq!(old_b, 0x1f);
+
+    let mut view = BitfieldMut::<BasicBitfieldTest, _>::new(&mut storage[2]);
+    view.set(BasicBitfieldTest(0x0123_4567));
+    view.into_inner().set_a(0xfe);
+
+    assert_eq!(storage, [0xff00_0004, 0xffff_07ff, 0xfe23_4567]);
+}
+
+#[test]
+fn test_bitfield_traced() {
+    let mut log = Vec::new();
+    let mut traced = Traced::new("X", BasicBitfieldTest(0), |args| {
+        log.push(args.to_string());
+    });
+    traced.update(|x| {
+        x.set_a(0xff);
+        x.set_b(0x10);
+        x.set_d(true);
+        x.set_e(0);
+    });
+    traced.set(BasicBitfieldTest(0xff00_8004));
+    assert_eq!(traced.into_inner(), BasicBitfieldTest(0xff00_8004));
+
+    // Overlapping fields are reported separately, and unchanged fields are skipped
+    assert_eq!(
+        log,
+        [
+            "X.a: 0x0 -> 0xff",
+            "X.b: 0x0 -> 0x10",
+            "X.d: false -> true",
+            "X.z: 0x0 -> 0x20"
+        ],
+    );
+}
+
+#[test]
+fn test_bitfield_observed() {
+    let mut changes = Vec::new();
+    let mut observed =
+        Observed::new(BasicBitfieldTest(0), |field: &FieldInfo, old, new| {
+            changes.push((field.name, old, new));
+        });
+    observed.update(|x| {
+        x.set_a(0xff);
+        x.set_b(0x10);
+        x.set_d(true);
+        x.set_e(0);
+    });
+    observed.set(BasicBitfieldTest(0xff00_8004));
+    let (value, _) = observed.into_inner();
+    assert_eq!(value, BasicBitfieldTest(0xff00_8004));
+
+    // Overlapping fields are reported separately, and unchanged fields are skipped
+    assert_eq!(changes, [("a", 0, 0xff), ("b", 0, 0x10), ("d", 0, 1), ("z", 0, 0x20)]);
+}
+
+#[test]
+fn test_bitfield_observed_fn_pointer() {
+    static CHANGES: AtomicUsize = AtomicUsize::new(0);
+
+    fn count(_: &FieldInfo, _: u128, _: u128) {
+        CHANGES.fetch_add(1, Ordering::Relaxed);
+    }
+
+    let mut observed = Observed::new(BasicBitfieldTest(0), count as fn(&FieldInfo, _, _));
+    observed.update(|x| x.set_c(0x1f));
+    observed.set(observed.get());
+    assert_eq!(CHANGES.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn test_bitfield_from_str() {
+    assert_eq!("0xff00ffc7".parse(), Ok(BasicBitfieldTest(0xff00_ffc7)));
+    assert_eq!("0XFF00FFC7".parse(), Ok(BasicBitfieldTest(0xff00_ffc7)));
+    assert_eq!("0b101".parse(), Ok(BasicBitfieldTest(5)));
+    assert_eq!("4294967295".parse(), Ok(BasicBitfieldTest(u32::MAX)));
+
+    assert!("4294967296".parse::<BasicBitfieldTest>().is_err());
+    assert!("0b102".parse::<BasicBitfieldTest>().is_err());
+    assert!("0xfg".parse::<BasicBitfieldTest>().is_err());
+    assert!("".parse::<BasicBitfieldTest>().is_err());
+}
+
+#[test]
+fn test_bitfield_from_str_radix() {
+    assert_eq!(
+        BasicBitfieldTest::from_str_radix("ff00ffc7", 16),
+        Ok(BasicBitfieldTest(0xff00_ffc7))
+    );
+    assert_eq!(BasicBitfieldTest::from_str_radix("777", 8), Ok(BasicBitfieldTest(0o777)));
+    assert_eq!(BasicBitfieldTest::from_str_radix("zz", 36), Ok(BasicBitfieldTest(1295)));
+
+    assert!(BasicBitfieldTest::from_str_radix("0xff", 16).is_err());
+    assert!(BasicBitfieldTest::from_str_radix("100000000", 16).is_err());
+}
+
+#[test]
+fn test_bitfield_parse_fields() {
+    let x =
+        BasicBitfieldTest::parse_fields("a=0xff, b=31, c=0b11111, d=true, e=3").unwrap();
+    assert_eq!(x, BasicBitfieldTest(0xff00_ffc7));
+
+    let mut y = BasicBitfieldTest(0xffff_ffff);
+    y.apply_fields(" a = 0 ,b=0,  c=0x0,d=0,e=0b0, ").unwrap();
+    assert_eq!(y, BasicBitfieldTest(0x00ff_0038));
+
+    // Inclusive ranges have the expected width
+    assert_eq!(
+        BasicBitfieldTest::parse_fields("e=4"),
+        Err(FieldParseError::InvalidValue)
+    );
+    assert_eq!(
+        BasicBitfieldTest::parse_fields("z=0x3ff"),
+        Ok(BasicBitfieldTest(0x000f_fc00))
+    );
+    assert_eq!(
+        BasicBitfieldTest::parse_fields("z=0x400"),
+        Err(FieldParseError::InvalidValue)
+    );
+
+    assert_eq!(
+        BasicBitfieldTest::parse_fields("d=2"),
+        Err(FieldParseError::InvalidValue)
+    );
+    assert_eq!(
+        BasicBitfieldTest::parse_fields("d.x=1"),
+        Err(FieldParseError::UnknownField)
+    );
+    assert_eq!(
+        BasicBitfieldTest::parse_fields("y=1"),
+        Err(FieldParseError::UnknownField)
+    );
+    assert_eq!(BasicBitfieldTest::parse_fields("a=1,b"), Err(FieldParseError::Syntax));
+
+    // Failed assignments leave the value unchanged
+    let mut z = BasicBitfieldTest(0x1234_5678);
+    assert!(z.set_field_from_str("a", "0x100").is_err());
+    assert_eq!(z, BasicBitfieldTest(0x1234_5678));
+}
+
+#[test]
+fn test_bitfield_errors() {
+    fn describe(e: &dyn core::error::Error) -> String {
+        e.to_string()
+    }
+
+    assert_eq!(
+        describe(&FieldParseError::Syntax),
+        "expected field assignments like `name=value`",
+    );
+    assert_eq!(describe(&FieldParseError::UnknownField), "no field with the given name");
+    assert_eq!(describe(&FieldParseError::InvalidValue), "invalid value for the field");
+    assert_eq!(
+        describe(&ValidationError::Field("day")),
+        "field `day` does not satisfy its constraint",
+    );
+    assert_eq!(
+        describe(&ValidationError::Invariant("Self::check")),
+        "invariant `Self::check` does not hold",
+    );
+}
+
+#[test]
+fn test_bitfield_write_fields() {
+    let mut out = String::new();
+    BasicBitfieldTest(0xff00_ffc7).write_fields(&mut out).unwrap();
+    assert_eq!(out, "a=0xff, b=0x1f, c=0x1f, d=1, e=0x3, z=0x3f");
+
+    out.clear();
+    BasicBitfieldTest(0).write_fields(&mut out).unwrap();
+    assert_eq!(out, "a=0x0, b=0x0, c=0x0, d=0, e=0x0, z=0x0");
+}
+
+#[test]
+fn test_bitfield_fields() {
+    let fields: Vec<_> = BasicBitfieldTest(0xff00_ffc7).fields().collect();
+    assert_eq!(
+        fields,
+        [
+            ("a", FieldValue::Uint(0xff)),
+            ("b", FieldValue::Uint(0x1f)),
+            ("c", FieldValue::Uint(0x1f)),
+            ("d", FieldValue::Bool(true)),
+            ("e", FieldValue::Uint(0x3)),
+            ("z", FieldValue::Uint(0x3f)),
+        ],
+    );
+
+    // Fields are reported as raw bits regardless of their types
+    let x = NestedTypeTest::default().with_inner(NestedTypeInner(0x2a));
+    assert!(FieldValues::new(x).any(|f| f == ("inner", FieldValue::Uint(0x2a))));
+
+    let wide = WideBitfieldTest(u128::MAX);
+    assert!(wide.fields().all(|(_, value)| value != FieldValue::Uint(0)));
+}
+
+#[test]
+fn test_bitfield_set_flags() {
+    assert_eq!(
+        BasicBitfieldTest(0xffff_ffff).iter_set_flags().collect::<Vec<_>>(),
+        ["d"]
+    );
+    assert_eq!(BasicBitfieldTest(0xffff_fffb).iter_set_flags().count(), 0);
+    assert_eq!(WideBitfieldTest(1 << 63).iter_set_flags().collect::<Vec<_>>(), ["flag"]);
+    assert_eq!(NestedTypeTest(u32::MAX).iter_set_flags().next(), None);
+}
+
+bitfield! {
+    // Fields whose accessors share a name with the generated methods
+    pub struct GeneratedNameTest(u8) {
+        [0]    pub flags,
+        [1..4] pub all_fields: u8,
+        [4..8] pub count: u8,
+    }
+}
+
+#[test]
+fn test_bitfield_generated_names() {
+    let mut x = GeneratedNameTest(0x30);
+    x.set_flags(true);
+    assert!(x.flags());
+    assert_eq!(x.iter_set_flags().collect::<Vec<_>>(), ["flags"]);
+
+    x.set_all_fields(0x5);
+    assert_eq!(x, GeneratedNameTest(0x3b));
+    x.clear_all_fields();
+    x.fill_all_fields();
+    assert_eq!(x.all_fields(), 0x7);
+}
+
+#[test]
+fn test_bitfield_bits() {
+    let x = BasicBitfieldTest(0xc35a_db69);
+    let bits: Vec<_> = x.iter_bits().collect();
+    assert_eq!(bits.len(), 32);
+    assert_eq!(bits[..8], [true, false, false, true, false, true, true, false]);
+    assert_eq!(BasicBitfieldTest::from_bits(bits.iter().copied()), x);
+    assert_eq!(
+        x.iter_bits().rev().collect::<BasicBitfieldTest>(),
+        BasicBitfieldTest(0x96db_5ac3)
+    );
+
+    assert_eq!(BasicBitfieldTest::from_bits([]), BasicBitfieldTest(0));
+    assert_eq!(BasicBitfieldTest::from_bits([true; 40]), BasicBitfieldTest(u32::MAX));
+
+    let wide = WideBitfieldTest(1 << 127 | 1);
+    assert_eq!(wide.iter_bits().filter(|bit| *bit).count(), 2);
+    assert_eq!(wide.iter_bits().collect::<WideBitfieldTest>(), wide);
+}
+
+bitfield! {
+    pub struct EmptyBitfieldTest(u16) {}
+}
+
+bitfield! {
+    pub struct FullWidthTest(u128) {
+        [0..128] pub all: u128,
+    }
+}
+
+#[test]
+fn test_bitfield_iter_all() {
+    let all: Vec<_> = NestedTypeInner::iter_all().collect();
+    assert_eq!(all.len(), 16);
+    assert_eq!(all[..5], [0x00, 0x01, 0x02, 0x03, 0x04].map(NestedTypeInner));
+    assert_eq!(all[8..10], [0x20, 0x21].map(NestedTypeInner));
+    assert_eq!(all.last(), Some(&NestedTypeInner(0x27)));
+    assert!(all.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+    let mut iter = WideBitfieldTest::iter_all();
+    assert_eq!(iter.size_hint(), (1 << 19, Some(1 << 19)));
+    assert_eq!(iter.next(), Some(WideBitfieldTest(0)));
+    assert_eq!(iter.nth(31), Some(WideBitfieldTest(1 << 60)));
+    assert_eq!(iter.size_hint().1, Some((1 << 19) - 33));
+
+    // The count of values doesn't fit in a `usize` when the fields are this wide
+    let mut iter = FullWidthTest::iter_all();
+    assert_eq!(iter.size_hint(), (usize::MAX, None));
+    assert_eq!(iter.nth(2), Some(FullWidthTest(2)));
+
+    // A bitfield without fields has only one value
+    let empty: Vec<_> = AllValues::<EmptyBitfieldTest, u16>::new().collect();
+    assert_eq!(empty, [EmptyBitfieldTest(0)]);
+}
+
+#[test]
+fn test_bitfield_replace_take() {
+    let mut x = BasicBitfieldTest(0x1234_5678);
+    assert_eq!(x.replace(BasicBitfieldTest(0xabcd)), BasicBitfieldTest(0x1234_5678));
+    assert_eq!(x, BasicBitfieldTest(0xabcd));
+    assert_eq!(x.take(), BasicBitfieldTest(0xabcd));
+    assert_eq!(x, BasicBitfieldTest::default());
+    assert_eq!(x.take(), BasicBitfieldTest::default());
+}
+
+#[test]
+fn test_bitfield_merge() {
+    let x = BasicBitfieldTest(0x1234_5678);
+    let y = BasicBitfieldTest(0xfedc_ba98);
+    assert_eq!(x.merge(y, 0), x);
+    assert_eq!(x.merge(y, u32::MAX), y);
+    assert_eq!(x.merge(y, 0xff00_0004), BasicBitfieldTest(0xfe34_5678));
+    assert_eq!(x.merge(y, 0xff00_0004).a(), y.a());
+
+    let wide = WideBitfieldTest(0);
+    assert_eq!(wide.merge(WideBitfieldTest(u128::MAX), 1 << 127).top(), 0x10);
+}
+
+#[test]
+fn test_bitfield_matches_masked() {
+    let (mask, value) = bitfield_pattern!(BasicBitfieldTest { a: 0x12, d: true });
+    assert_eq!(mask, BasicBitfieldTest(0xff00_0004));
+    assert_eq!(value, BasicBitfieldTest(0x1200_0004));
+
+    assert!(BasicBitfieldTest(0x12ff_fffc).matches_masked(mask, value));
+    assert!(!BasicBitfieldTest(0x12ff_fff8).matches_masked(mask, value));
+    assert!(!BasicBitfieldTest(0x13ff_fffc).matches_masked(mask, value));
+
+    let (mask, value) = bitfield_pattern!(BasicBitfieldTest {});
+    assert!(BasicBitfieldTest(0x1234_5678).matches_masked(mask, value));
+
+    let (mask, _) = bitfield_pattern!(NestedTypeTest { inner: NestedTypeInner(0) });
+    assert_eq!(mask, NestedTypeTest(0xff));
+}
+
+#[test]
+fn test_bitfield_insert_extract() {
+    let inner = NestedTypeInner::default().with_mode(5);
+    let x = BasicBitfieldTest(0xffff_ffff).insert_at(20, inner);
+    assert_eq!(x, BasicBitfieldTest(0xf05f_ffff));
+    assert_eq!(x.extract_at::<NestedTypeInner>(20), inner);
+    assert_eq!(x.extract_at::<NestedTypeInner>(24), NestedTypeInner(0xf0));
+    assert_eq!(x.extract_at::<NestedTypeTest>(0), NestedTypeTest(0xf05f_ffff));
+
+    let wide = WideBitfieldTest(0).insert_at(120, NestedTypeInner(0xa5));
+    assert_eq!(wide.top(), 0x14);
+    assert_eq!(wide.extract_at::<NestedTypeInner>(120), NestedTypeInner(0xa5));
+}
+
+#[test]
+#[should_panic(expected = "sub-bitfield extends past the end of the value")]
+fn test_bitfield_insert_panic_overflow() {
+    let _ = BasicBitfieldTest(0).insert_at(25, NestedTypeInner(0));
+}
+
+#[test]
+#[should_panic(expected = "sub-bitfield extends past the end of the value")]
+fn test_bitfield_extract_panic_overflow() {
+    let _ = NestedTypeInner(0).extract_at::<BasicBitfieldTest>(0);
+}
+
+trait CommonFieldsTest: Bitfield<u32> {
+    bitfield_accessors! {
+        [0..8] low: u8,
+    }
+}
+
+impl CommonFieldsTest for BasicBitfieldTest {}
+impl CommonFieldsTest for NestedTypeTest {}
+
+trait AccessorsOnlyTest: Bitfield<u32> {
+    bitfield_accessors! {
+        #![accessors_only]
+        [24..32] high: u8,
+        [31]     top,
+    }
+}
+
+impl AccessorsOnlyTest for BasicBitfieldTest {}
+
+#[test]
+fn test_bitfield_accessors_only() {
+    // Helpers from `CommonFieldsTest` aren't ambiguous, since `AccessorsOnlyTest` has
+    // none
+    fn common_fields<B: CommonFieldsTest + AccessorsOnlyTest>(x: B) -> String {
+        let mut out = String::new();
+        x.write_field_pairs(&mut out).unwrap();
+        format!("{out}; high={:#x}", x.high())
+    }
+
+    let x = BasicBitfieldTest(0xbeef_2325).with_high(0x7f);
+    assert_eq!(x, BasicBitfieldTest(0x7fef_2325));
+    assert!(!x.top());
+    assert_eq!(BasicBitfieldTest::HIGH_RANGE, 24..32);
+    assert_eq!(common_fields(x), "low=0x25; high=0x7f");
+}
+
+#[test]
+fn test_bitfield_reinterpret() {
+    let x = BasicBitfieldTest(0xbeef_2325);
+    let y: NestedTypeTest = x.reinterpret();
+    assert_eq!(y, NestedTypeTest(0xbeef_2325));
+    assert_eq!(y.low(), x.low());
+    assert_eq!(y.inner().mode(), 5);
+    assert_eq!(y.reinterpret::<BasicBitfieldTest>(), x);
+}
+
+bitfield! {
+    pub struct FlagSetTest(u16) {
+        [0]  pub a,
+        [3]  pub b,
+        [15] pub c,
+    }
+}
+
+#[test]
+fn test_bitfield_flag_set() {
+    let a = FlagSetTest::default().with_a(true);
+    let c = FlagSetTest::default().with_c(true);
+    let ac = a.union(c);
+    assert_eq!(ac, FlagSetTest(0x8001));
+    assert!(ac.contains(a));
+    assert!(ac.contains(FlagSetTest(0)));
+    assert!(!a.contains(ac));
+    assert_eq!(ac.intersection(FlagSetTest(0x8008)), c);
+
+    let mut x = FlagSetTest(0x0008);
+    x.insert(ac);
+    assert_eq!(x, FlagSetTest(0x8009));
+    x.remove(a);
+    assert_eq!(x, FlagSetTest(0x8008));
+}
+
+#[test]
+fn test_bitfield_bulk_fields() {
+    // Bits 3..6 and 20..24 aren't part of any field
+    let mut x = BasicBitfieldTest(0x0000_0020);
+    x.fill_all_fields();
+    assert_eq!(x, BasicBitfieldTest(0xff0f_ffe7));
+    x.invert_fields();
+    assert_eq!(x, BasicBitfieldTest(0x0000_0020));
+    x.invert_fields();
+    x.clear_all_fields();
+    assert_eq!(x, BasicBitfieldTest(0x0000_0020));
+
+    let mut y = BasicBitfieldTest(0x1234_5678);
+    y.invert_fields();
+    assert_eq!(y, BasicBitfieldTest(0xed3b_a9bf));
+
+    let mut wide = WideBitfieldTest(0);
+    wide.fill_all_fields();
+    assert_eq!(wide.top(), 0x1f);
+    // Bits 0..5, 60..69, and 123..128, since `middle`, `high`, and `flag` overlap
+    assert_eq!(wide.iter_bits().filter(|bit| *bit).count(), 5 + 9 + 5);
+}
+
+#[test]
+fn test_bitfield_predicates() {
+    assert!(BasicBitfieldTest(0).is_zero());
+    assert!(!BasicBitfieldTest(0).any_field_set());
+    assert!(!BasicBitfieldTest(0).all_fields_set());
+
+    // Bits 3..6 and 20..24 aren't part of any field
+    let reserved = BasicBitfieldTest(0x00f0_0038);
+    assert!(!reserved.is_zero());
+    assert!(!reserved.any_field_set());
+    assert!(BasicBitfieldTest(0x0000_0004).any_field_set());
+    assert!(BasicBitfieldTest(0xff0f_ffc7).all_fields_set());
+    assert!(!BasicBitfieldTest(0xff0f_ffc3).all_fields_set());
+    assert!(BasicBitfieldTest(u32::MAX).all_fields_set());
+
+    let wide = WideBitfieldTest(1 << 127);
+    assert!(wide.any_field_set() && !wide.all_fields_set() && !wide.is_zero());
+}
+
+#[test]
+fn test_bitfield_field_range() {
+    assert_eq!(field_range!(BasicBitfieldTest::a), 24..32);
+    assert_eq!(field_range!(BasicBitfieldTest::d), 2..3);
+    assert_eq!(field_range!(BasicBitfieldTest::e), 0..2);
+    assert_eq!(field_range!(self::WideBitfieldTest::top), 123..128);
+    assert_eq!(<NestedTypeTest as CommonFieldsTest>::LOW_RANGE, 0..8);
+
+    for (field, range) in BasicBitfieldTest::LAYOUT.iter().zip([
+        BasicBitfieldTest::A_RANGE,
+        BasicBitfieldTest::B_RANGE,
+        BasicBitfieldTest::C_RANGE,
+        BasicBitfieldTest::D_RANGE,
+        BasicBitfieldTest::E_RANGE,
+        BasicBitfieldTest::Z_RANGE,
+    ]) {
+        assert_eq!(field.lsb..field.msb, range);
+    }
+}
+
+#[test]
+fn test_bitfield_field_names() {
+    const NAMES: &[&str] = BasicBitfieldTest::FIELD_NAMES;
+    assert_eq!(NAMES, ["a", "b", "c", "d", "e", "z"]);
+    assert!(BasicBitfieldTest::LAYOUT.iter().map(|f| f.name).eq(NAMES.iter().copied()));
+    assert_eq!(PrefixGroupTest::FIELD_NAMES.len(), 5);
+}
+
+assert_field_layout!(BasicBitfieldTest::a, 24..32);
+assert_field_layout!(BasicBitfieldTest::d, 2);
+assert_field_layout!(BasicBitfieldTest::e, 0..=1);
+assert_field_layout!(self::WideBitfieldTest::top, 123..128);
+
+#[test]
+fn test_bitfield_summary() {
+    assert_eq!(format!("{:?}", BasicBitfieldTest(0).summary()), "BasicBitfieldTest");
+    assert_eq!(
+        format!("{:?}", BasicBitfieldTest(0x0000_0804).summary()),
+        "BasicBitfieldTest { b: 1, d: true, z: 2 }",
+    );
+    // Reserved bits aren't part of any field
+    assert_eq!(
+        format!("{:?}", BasicBitfieldTest(0x0020_0038).summary()),
+        "BasicBitfieldTest"
+    );
+}
+
+bitfield! {
+    #[derive(Hash, PartialOrd, Ord)]
+    pub struct DerivePassthroughTest(u16) {
+        [0..8] pub low: u8,
+    }
+}
+
+#[test]
+fn test_bitfield_derive_passthrough() {
+    use std::collections::HashSet;
+
+    let set: HashSet<_> =
+        [DerivePassthroughTest(1), DerivePassthroughTest(2), DerivePassthroughTest(1)]
+            .into();
+    assert_eq!(set.len(), 2);
+    assert!(DerivePassthroughTest(0x00ff) < DerivePassthroughTest(0x0100));
+}
+
+bitfield! {
+    pub struct FloatFieldTest(u128) {
+        [0..32]   pub gain: u32 as f32,
+        [64..128] pub coefficient: u64 as f64,
+    }
+}
+
+#[test]
+#[allow(clippy::float_cmp)]
+fn test_bitfield_float_fields() {
+    let x = FloatFieldTest(0x3ff8_0000_0000_0000_0000_0000_4049_0fdb);
+    assert_eq!(x.gain(), core::f32::consts::PI);
+    assert_eq!(x.coefficient(), 1.5);
+
+    let y = FloatFieldTest::default().with_gain(-2.0).with_coefficient(0.25);
+    assert_eq!(y, FloatFieldTest(0x3fd0_0000_0000_0000_0000_0000_c000_0000));
+
+    let mut out = String::new();
+    y.write_fields(&mut out).unwrap();
+    assert_eq!(out, "gain=0xc0000000, coefficient=0x3fd0000000000000");
+    assert_eq!(FloatFieldTest::parse_fields("gain=0x3f800000").unwrap().gain(), 1.0);
+}
+
+bitfield! {
+    pub struct InferredTypeTest(u128) {
+        [0..8]     pub byte,
+        [8..=16]   pub nine,
+        [17..49]   pub word,
+        [49..82]   pub wide,
+        [0..128]   pub all,
+    }
+}
+
+#[test]
+fn test_bitfield_inferred_types() {
+    let x = InferredTypeTest(u128::MAX);
+    let _: (u8, u16, u32, u64, u128) = (x.byte(), x.nine(), x.word(), x.wide(), x.all());
+    assert_eq!(x.byte(), 0xff);
+    assert_eq!(x.nine(), 0x1ff);
+    assert_eq!(x.word(), u32::MAX);
+    assert_eq!(x.wide(), 0x1_ffff_ffff);
+
+    let y = InferredTypeTest::default().with_nine(0x1ab).with_wide(0x1_2345_6789);
+    assert_eq!(y, InferredTypeTest(0x2_468a_cf12_0000_0001_ab00));
+
+    let mut out = String::new();
+    y.with_all(0).with_byte(0x12).write_fields(&mut out).unwrap();
+    assert_eq!(out, "byte=0x12, nine=0x0, word=0x0, wide=0x0, all=0x12");
+    assert_eq!(InferredTypeTest::parse_fields("nine=0x1ff").unwrap().nine(), 0x1ff);
+    assert!(InferredTypeTest::parse_fields("nine=0x200").is_err());
+}
+
+bitfield! {
+    pub struct NestedTypeTest(u32) {
+        [0..8]   pub inner: NestedTypeInner,
+        [8..=15] pub explicit: u8 as NestedTypeInner,
+        [16..32] pub wide: u16,
+    }
+}
+
+bitfield! {
+    pub struct NestedTypeInner(u8) {
+        [0..3] pub mode,
+        [5]    pub enable,
+    }
+}
+
+#[test]
+fn test_bitfield_nested_type() {
+    let x = NestedTypeTest(0xbeef_2325);
+    assert_eq!(x.inner(), NestedTypeInner(0x25));
+    assert_eq!(x.inner(), x.explicit().with_mode(5));
+    assert!(x.inner().enable());
+    assert_eq!(x.wide(), 0xbeef);
+
+    let y = NestedTypeTest::default().with_inner(NestedTypeInner::default().with_mode(6));
+    assert_eq!(y, NestedTypeTest(0x06));
+
+    let mut out = String::new();
+    y.write_fields(&mut out).unwrap();
+    assert_eq!(out, "inner=0x6, explicit=0x0, wide=0x0");
+    let z = NestedTypeTest::parse_fields("inner.enable=1, inner.mode=2").unwrap();
+    assert_eq!(z, NestedTypeTest(0x22));
+
+    let expected = "\
+NestedTypeTest {
+    <value>: 34,
+    inner: NestedTypeInner {
+        <value>: 34,
+        mode: 2,
+        enable: true,
+    },
+    explicit: NestedTypeInner {
+        <value>: 0,
+        mode: 0,
+        enable: false,
+    },
+    wide: 0,
+}";
+    assert_eq!(format!("{z:#?}"), expected);
+}
+
+bitfield! {
+    pub struct ValidationTest(u32) {
+        [0..4]   pub divider: u8 where 1..=12,
+        [4..8]   pub prescaler: u8 where u8::is_power_of_two,
+        [8]      pub enable,
+        [9..12]  pub mode: u8,
+        [12..16] pub level: u8 where |x: u8| x & 1 == 0,
+    }
+}
+
+#[test]
+fn test_bitfield_validate() {
+    let x = ValidationTest(0x0000_0081);
+    assert_eq!(x.validate(), Ok(()));
+    assert_eq!(
+        ValidationTest(0x0000_0080).validate(),
+        Err(ValidationError::Field("divider"))
+    );
+    assert_eq!(
+        ValidationTest(0x0000_0031).validate(),
+        Err(ValidationError::Field("prescaler"))
+    );
+    assert_eq!(
+        ValidationTest(0x0000_1081).validate(),
+        Err(ValidationError::Field("level"))
+    );
+
+    let mut y = x;
+    assert_eq!(y.try_set_divider(12), Ok(()));
+    assert_eq!(y.divider(), 12);
+    assert_eq!(y.try_set_divider(13), Err(ValidationError::Field("divider")));
+    assert_eq!(y.divider(), 12);
+
+    // Values are checked after truncation
+    assert_eq!(y.try_with_prescaler(0x12), Ok(ValidationTest(0x0000_002c)));
+    assert!(y.try_with_level(3).is_err());
+    assert!(y.try_with_level(4).is_ok());
+}
+
+bitfield! {
+    pub struct InvariantTest(u16) where InvariantTest::check_ordering {
+        [0..4] pub low: u8,
+        [4..8] pub high: u8 where 1..,
+    }
+}
+
+impl InvariantTest {
+    fn check_ordering(self) -> bool {
+        self.low() <= self.high()
+    }
+}
+
+#[test]
+fn test_bitfield_invariant() {
+    assert_eq!(InvariantTest(0x0033).validate(), Ok(()));
+    assert_eq!(
+        InvariantTest(0x0012).validate(),
+        Err(ValidationError::Invariant("InvariantTest::check_ordering"))
+    );
+    // Field constraints are checked first
+    assert_eq!(InvariantTest(0x0001).validate(), Err(ValidationError::Field("high")));
+
+    assert_eq!(InvariantTest::try_new(0x0033), Ok(InvariantTest(0x0033)));
+    assert_eq!(
+        InvariantTest::try_new(0x0012),
+        Err(ValidationError::Invariant("InvariantTest::check_ordering"))
+    );
+    assert_eq!(InvariantTest::try_new(0x0001), Err(ValidationError::Field("high")));
+    assert_eq!(ValidationTest::try_new(0x0000_0081), Ok(ValidationTest(0x0000_0081)));
+}
+
+bitfield_tests!(
+    BasicBitfieldTest,
+    FloatFieldTest,
+    InferredTypeTest,
+    NestedTypeTest,
+    ValidationTest,
+);
+bitfield_proofs!(BasicBitfieldTest, ValidationTest);
+
+#[derive(Debug, Clone, Copy)]
+pub struct DropsLowBit(u8);
+
+impl From<u8> for DropsLowBit {
+    fn from(value: u8) -> Self {
+        Self(value & !1)
+    }
+}
+
+impl From<DropsLowBit> for u8 {
+    fn from(value: DropsLowBit) -> Self {
+        value.0
+    }
+}
+
+bitfield! {
+    pub struct BrokenLayoutTest(u16) {
+        [0..4]  pub ok: u8,
+        [8..16] pub lossy: u8 as DropsLowBit,
+    }
+}
+
+#[test]
+#[should_panic(expected = "field lossy did not round-trip")]
+fn test_bitfield_broken_layout() {
+    BrokenLayoutTest::visit_field_accessors(|name, lsb, msb, copy_field| {
+        tartan_bitfield::assert_field_layout(name, lsb, msb, copy_field);
+    });
+}
+
+bitfield! {
+    pub struct WideBitfieldTest(u128) {
+        [  0..5  ] pub low: u8,
+        [ 60..68 ] pub middle: u8,
+        [ 64..69 ] pub high: u8,
+        [123..128] pub top: u8,
+        [ 63     ] pub flag,
+    }
+}
+
+bitfield_tests!(WideBitfieldTest);
+
+#[test]
+fn test_bitfield_wide_limbs() {
+    let x = WideBitfieldTest(0xf800_0000_0000_0015_a000_0000_0000_0013);
+    assert_eq!(x.low(), 0x13);
+    assert_eq!(x.middle(), 0x5a);
+    assert_eq!(x.high(), 0x15);
+    assert_eq!(x.top(), 0x1f);
+    assert!(x.flag());
+
+    let y = WideBitfieldTest(0).with_middle(0xc3).with_top(0x11).with_low(0x1e);
+    assert_eq!(y, WideBitfieldTest(0x8800_0000_0000_000c_3000_0000_0000_001e));
+}
+
+bitfield_fields! {
+    shared_flags_test {
+        /// Ready flag
+        [0] pub ready,
+        [1] pub error,
+    }
+}
+
+bitfield_fields! {
+    shared_count_test {
+        [4..8] pub count: u8 where |x| x < 10,
+    }
+}
+
+bitfield! {
+    pub struct IncludeFieldsTest(u16) {
+        include shared_flags_test,
+        include shared_count_test,
+        [8..16] pub data: u8,
+    }
+}
+
+bitfield! {
+    pub struct IncludeOnlyTest(u8) {
+        include shared_flags_test
+    }
+}
+
+#[test]
+fn test_bitfield_include_fields() {
+    let x = IncludeFieldsTest(0x3a51);
+    assert!(x.ready());
+    assert!(!x.error());
+    assert_eq!(x.count(), 5);
+    assert_eq!(x.data(), 0x3a);
+    assert_eq!(
+        IncludeFieldsTest::LAYOUT.iter().map(|f| f.name).collect::<Vec<_>>(),
+        ["ready", "error", "count", "data"],
+    );
+    assert_eq!(IncludeFieldsTest::FIELD_NAMES, ["ready", "error", "count", "data"]);
+    assert!(x.validate().is_ok());
+    assert!(IncludeFieldsTest(0x00c0).validate().is_err());
+
+    let y = IncludeOnlyTest::default().with_error(true);
+    assert_eq!(y, IncludeOnlyTest(0x02));
+    assert_eq!(
+        format!("{y:?}"),
+        "IncludeOnlyTest { <value>: 2, ready: false, error: true }"
+    );
+}
+
+bitfield! {
+    pub struct ExtendedFieldsTest(u16) {
+        include IncludeFieldsTest,
+        [2] pub extra,
+    }
+}
+
+#[test]
+fn test_bitfield_extend() {
+    let x = ExtendedFieldsTest(0x1235);
+    assert!(x.ready());
+    assert!(x.extra());
+    assert_eq!(x.count(), 3);
+    assert_eq!(x.data(), 0x12);
+    assert_eq!(ExtendedFieldsTest::LAYOUT.len(), IncludeFieldsTest::LAYOUT.len() + 1);
+}
+
+bitfield! {
+    pub struct PrefixGroupTest(u16) {
+        group tx {
+            [0]    pub enable,
+            [1..8] pub len: u8,
+        },
+        group rx {
+            [8]     pub enable,
+            [9..16] pub len: u8 where |x| x < 100,
+        },
+        [7] pub loopback,
+    }
+}
+
+#[test]
+fn test_bitfield_prefix_group() {
+    let x = PrefixGroupTest::default().with_tx_enable(true).with_tx_len(3).with_rx_len(5);
+    assert_eq!(x, PrefixGroupTest(0x0a07));
+    assert!(x.tx_enable());
+    assert!(!x.rx_enable());
+    assert_eq!((x.tx_len(), x.rx_len()), (3, 5));
+    assert!(!x.loopback());
+    assert_eq!(
+        PrefixGroupTest::LAYOUT.iter().map(|f| f.name).collect::<Vec<_>>(),
+        ["tx_enable", "tx_len", "rx_enable", "rx_len", "loopback"],
+    );
+    assert!(PrefixGroupTest(0xfe00).validate().is_err());
+}
+
+bitfield! {
+    pub struct CounterTest(u16) {
+        [0..3]  pub low,
+        [3]     pub flag,
+        [8..16] pub high: u8,
+    }
+}
+
+bitfield_counters!(CounterTest { pub low, pub(crate) high, flag });
+bitfield_counters!(WideBitfieldTest { pub top });
+
+#[test]
+fn test_bitfield_counters() {
+    let mut x = CounterTest(0x00f6);
+    x.increment_low();
+    assert_eq!(x, CounterTest(0x00f7));
+    x.increment_low();
+    assert_eq!(x, CounterTest(0x00f0));
+    x.increment_low_saturating();
+    x.increment_high();
+    assert_eq!(x, CounterTest(0x01f1));
+
+    x.set_high(0xff);
+    x.increment_high_saturating();
+    assert_eq!(x, CounterTest(0xfff1));
+    x.increment_high();
+    assert_eq!(x, CounterTest(0x00f1));
+
+    x.increment_flag_saturating();
+    x.increment_flag_saturating();
+    assert!(x.flag());
+    x.increment_flag();
+    assert!(!x.flag());
+
+    let mut y = WideBitfieldTest(u128::MAX);
+    y.increment_top_saturating();
+    assert_eq!(y.top(), 0x1f);
+    y.increment_top();
+    assert_eq!(y, WideBitfieldTest(u128::MAX >> 5));
+}
+
+bitfield! {
+    pub struct ParityTest(u32) {
+        [0]       pub odd_bit,
+        [1..=30]  pub data: u32,
+        [31]      pub even_bit,
+    }
+}
+
+bitfield! {
+    pub struct OddParityTest(u32) {
+        [0]       pub odd_bit,
+        [1..=30]  pub data: u32,
+    }
+}
+
+bitfield_parity! {
+    ParityTest { pub even_bit: even [1..=30] }
+}
+
+bitfield_parity! {
+    OddParityTest { odd_bit: odd [1..=30] }
+}
+
+bitfield_parity! {
+    ParityTest { pub odd_bit: odd [1..=30] }
+}
+
+#[test]
+fn test_bitfield_parity() {
+    let mut x = ParityTest::default().with_data(0x0123_4567);
+    assert_eq!(0x0123_4567_u32.count_ones(), 12);
+    assert!(x.even_bit_ok());
+    x.set_data(0x0123_4566);
+    assert!(!x.even_bit_ok());
+    x.recompute_even_bit();
+    assert!(x.even_bit() && x.even_bit_ok());
+    assert!(x.with_odd_bit(true).even_bit_ok());
+
+    // Each parity bit has its own methods
+    assert!(x.odd_bit_ok());
+    x.recompute_odd_bit();
+    assert_eq!(x, ParityTest(0x8246_8acc));
+
+    let mut y = OddParityTest::default().with_data(0x0123_4567);
+    assert!(!y.odd_bit_ok());
+    y.recompute_odd_bit();
+    assert_eq!(y, OddParityTest(0x0246_8acf));
+    assert!(y.odd_bit_ok());
+}
+
+bitfield! {
+    pub struct ChecksumTest(u32) {
+        [0..24]  pub data: u32,
+        [24..32] pub check: u8,
+    }
+}
+
+bitfield_checksum! {
+    ChecksumTest {
+        check: |x: ChecksumTest| u8::try_from(x.0.count_ones()).unwrap() ^ 0xa5
+    }
+}
+
+#[test]
+fn test_bitfield_checksum() {
+    let mut x = ChecksumTest(0xff12_3456);
+    assert!(!x.verify_check());
+    x.finalize_check();
+    assert_eq!(x, ChecksumTest(0xac12_3456));
+    assert!(x.verify_check());
+    x.set_data(0x12_3457);
+    assert!(!x.verify_check());
+}
+
+bitfield! {
+    pub struct ScatteredHighTest(u16) {
+        [12..16] pub top,
+        [0..4]   pub mid,
+    }
+}
+
+bitfield! {
+    pub struct ScatteredLowTest(u128) {
+        [64..128] pub bottom: u64,
+    }
+}
+
+bitfield_scattered! {
+    pub fn scattered: u128 =
+        ScatteredHighTest::top, ScatteredHighTest::mid, ScatteredLowTest::bottom;
+}
+
+#[test]
+fn test_bitfield_scattered() {
+    let mut high = ScatteredHighTest(0x5aa3);
+    let mut low = ScatteredLowTest(0x0123_4567_89ab_cdef << 64 | 0xffff);
+    assert_eq!(scattered(high, high, low), 0x53_0123_4567_89ab_cdef);
+
+    let mut other = ScatteredHighTest(0);
+    set_scattered(&mut high, &mut other, &mut low, 0xfff_fedc_ba98_7654_3210);
+    assert_eq!(high, ScatteredHighTest(0xfaa3));
+    assert_eq!(other, ScatteredHighTest(0x000f));
+    assert_eq!(low, ScatteredLowTest(0xfedc_ba98_7654_3210 << 64 | 0xffff));
+}
+
+bitfield! {
+    #[bitfield(bit_offset = 96)]
+    #[derive(PartialOrd)]
+    pub struct BitOffsetTest(u32) {
+        /// Low flag
+        [96]        pub low,
+        [100..104]  pub mode,
+        [104..=111] pub count: u8 where |x| x < 200,
+        [127]       pub high,
+    }
+}
+
+#[test]
+fn test_bitfield_bit_offset() {
+    let x = BitOffsetTest(0x8000_2a51);
+    assert!(x.low());
+    assert_eq!(x.mode(), 5);
+    assert_eq!(x.count(), 0x2a);
+    assert!(x.high());
+    assert!(BitOffsetTest(0) < x);
+
+    let y = BitOffsetTest::default().with_count(0xc8).with_mode(0xf).with_low(true);
+    assert_eq!(y, BitOffsetTest(0x0000_c8f1));
+    assert!(y.validate().is_err());
+    assert_eq!(BitOffsetTest::COUNT_RANGE, 8..16);
+    assert_eq!(BitOffsetTest::LAYOUT[3].lsb, 31);
+    assert_eq!(format!("{:?}", BitOffsetTest(1)).split(", ").nth(1), Some("low: true"));
+}
+
+bitfield! {
+    pub struct WholeValueTest(u24) {
+        [0..8] pub low: u8,
+        [..]   pub raw,
+    }
+}
+
+bitfield! {
+    #[bitfield(bit_offset = 8)]
+    pub struct WholeValueOffsetTest(u8) {
+        [..]  pub raw: u8,
+        [15]  pub top,
+    }
+}
+
+#[test]
+fn test_bitfield_whole_value() {
+    let x = WholeValueTest(u24::new(0x12_3456));
+    assert_eq!(x.raw(), 0x12_3456_u32);
+    assert_eq!(x.with_raw(0xab_cdef).low(), 0xef);
+    assert_eq!(WholeValueTest::RAW_RANGE, 0..24);
+    assert_eq!(WholeValueTest::FIELD_NAMES, ["low", "raw"]);
+
+    let y = WholeValueOffsetTest(0x81);
+    assert_eq!(y.raw(), 0x81);
+    assert!(y.top());
+    assert_eq!(WholeValueOffsetTest::RAW_RANGE, 0..8);
+}
+
+bitfield! {
+    pub struct OpenRangeTest(u16) {
+        [..4]   pub low,
+        [..=1]  pub lowest,
+        [12..]  pub high,
+        [4..]   pub rest: u16,
+    }
+}
+
+bitfield! {
+    #[bitfield(bit_offset = 16)]
+    pub struct OpenRangeOffsetTest(u16) {
+        [..20]  pub low,
+        [..=17] pub lowest,
+        [28..]  pub high,
+    }
+}
+
+#[test]
+fn test_bitfield_open_range() {
+    let x = OpenRangeTest(0xa5c3);
+    assert_eq!(x.low(), 0x3);
+    assert_eq!(x.lowest(), 0x3);
+    assert_eq!(x.high(), 0xa);
+    assert_eq!(x.rest(), 0xa5c);
+    assert_eq!(x.with_high(0x1).with_low(0xf), OpenRangeTest(0x15cf));
+    assert_eq!(OpenRangeTest::LOW_RANGE, 0..4);
+    assert_eq!(OpenRangeTest::LOWEST_RANGE, 0..2);
+    assert_eq!(OpenRangeTest::HIGH_RANGE, 12..16);
+    assert_eq!(OpenRangeTest::REST_RANGE, 4..16);
+
+    let y = OpenRangeOffsetTest(0xa5c3);
+    assert_eq!(y.low(), 0x3);
+    assert_eq!(y.lowest(), 0x3);
+    assert_eq!(y.high(), 0xa);
+    assert_eq!(OpenRangeOffsetTest::LOW_RANGE, 0..4);
+    assert_eq!(OpenRangeOffsetTest::HIGH_RANGE, 12..16);
+}
+
+bitfield! {
+    pub struct IntegerBitTest(u8) {
+        [0]    pub flag,
+        [1]    pub sel: u8,
+        [2]    pub nested: Bit,
+    }
+}
+
+bitfield! {
+    pub struct Bit(u8) {
+        [0] pub set,
+    }
+}
+
+#[test]
+fn test_bitfield_integer_bit() {
+    let mut x = IntegerBitTest(0b110);
+    assert!(!x.flag());
+    assert_eq!(x.sel(), 1);
+    assert!(x.nested().set());
+    x.set_sel(0);
+    assert_eq!(x, IntegerBitTest(0b100));
+    assert_eq!(x.with_sel(3), IntegerBitTest(0b110));
+    assert_eq!(IntegerBitTest::SEL_RANGE, 1..2);
+    assert_eq!(
+        format!("{x:?}"),
+        "IntegerBitTest { <value>: 4, flag: false, sel: 0, nested: Bit { <value>: 1, \
+         set: true } }",
+    );
+
+    let mut y = IntegerBitTest::default();
+    y.set_field_from_str("sel", "1").unwrap();
+    assert_eq!(y, IntegerBitTest(0b010));
+    assert!(y.set_field_from_str("sel", "2").is_err());
+}
+
+bitfield! {
+    pub struct BoolRangeTest(u8) {
+        [0..1]  pub a,
+        [1..=1] pub b,
+        [2..3]  pub c: bool,
+        [3]     pub d: bool,
+        [4..6]  pub e,
+    }
+}
+
+#[test]
+fn test_bitfield_bool_range() {
+    let mut x = BoolRangeTest(0b1_1010);
+    assert!(!x.a());
+    assert!(x.b());
+    assert!(!x.c());
+    assert!(x.d());
+    assert_eq!(x.e(), 1_u8);
+    x.set_a(true);
+    x.set_c(true);
+    assert_eq!(x.with_b(false).with_d(false), BoolRangeTest(0b1_0101));
+    assert_eq!(BoolRangeTest::A_RANGE, 0..1);
+    assert_eq!(
+        format!("{x:?}"),
+        "BoolRangeTest { <value>: 31, a: true, b: true, c: true, d: true, e: 1 }",
+    );
+
+    let mut y = BoolRangeTest::default();
+    y.apply_fields("a=1,c=0x1").unwrap();
+    assert_eq!(y, BoolRangeTest(0b101));
+    assert!(y.set_field_from_str("b", "2").is_err());
+}
+
+bitfield! {
+    #[derive(PartialOrd)]
+    #[bitfield(no_derive(Default, PartialEq), bit_offset = 8)]
+    pub struct NoDeriveTest(u16) {
+        [8]      pub ready,
+        [12..16] pub code,
+    }
+}
+
+impl Default for NoDeriveTest {
+    fn default() -> Self {
+        Self(0x0081)
+    }
+}
+
+impl PartialEq for NoDeriveTest {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 & 0x00f1 == other.0 & 0x00f1
+    }
+}
+
+bitfield! {
+    #[bitfield(no_derive(Debug))]
+    pub struct NoDebugTest(u8) {
+        [0] pub a,
+    }
+}
+
+impl core::fmt::Debug for NoDebugTest {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "NoDebugTest({})", self.a())
+    }
+}
+
+#[test]
+fn test_bitfield_no_derive() {
+    let x = NoDeriveTest::default();
+    assert!(x.ready());
+    assert_eq!(x.code(), 8);
+    assert!(!x.is_zero());
+    assert!(x.any_field_set());
+    assert_eq!(NoDeriveTest(0x0031), NoDeriveTest(0xff3f));
+    assert_ne!(NoDeriveTest(0x0031), NoDeriveTest(0x0030));
+    assert_eq!(NoDeriveTest::CODE_RANGE, 4..8);
+
+    let mut y = NoDeriveTest(0);
+    assert!(y.is_zero());
+    assert_eq!(y.take(), NoDeriveTest(0));
+    assert_eq!(y, NoDeriveTest(0x0081));
+
+    assert_eq!(format!("{:?}", NoDebugTest(1)), "NoDebugTest(true)");
+    assert_eq!(NoDebugTest(1), NoDebugTest::default().with_a(true));
+}
+
+bitfield! {
+    #[bitfield(no_derive(Default), derive(Hash, PartialOrd))]
+    #[bitfield(derive(Default, core::cmp::Ord, Eq))]
+    pub struct ExtraDeriveTest(u16) {
+        [0..4] pub low,
+        [15]   pub high,
+    }
+}
+
+#[test]
+fn test_bitfield_extra_derives() {
+    use std::collections::BTreeSet;
+    use std::collections::HashSet;
+
+    let x = ExtraDeriveTest(0x8001);
+    assert!(ExtraDeriveTest::default() < x);
+    assert_eq!(x.max(ExtraDeriveTest(3)), x);
+    assert_eq!(HashSet::from([x, x, ExtraDeriveTest(1)]).len(), 2);
+    assert_eq!(
+        BTreeSet::from([x, ExtraDeriveTest(1)]).first(),
+        Some(&ExtraDeriveTest(1))
+    );
+}
+
+bitfield! {
+    pub struct PublicStorageTest(pub u16) {
+        [0]     pub flag,
+        [8..16] pub high: u8,
+    }
+}
+
+bitfield! {
+    #[bitfield(bit_offset = 4, storage = raw)]
+    pub struct NamedStorageTest(pub(crate) u8) {
+        [4]     pub flag,
+        [8..12] pub high,
+    }
+}
+
+#[test]
+fn test_bitfield_storage() {
+    let x = PublicStorageTest(0x1201);
+    assert_eq!(x.0, 0x1201);
+    assert!(x.flag());
+    assert_eq!(x.high(), 0x12);
+
+    let y = NamedStorageTest { raw: 0x51 };
+    assert!(y.flag());
+    assert_eq!(y.high(), 5);
+    assert_eq!(y.with_high(0xa).raw, 0xa1);
+    assert_eq!(NamedStorageTest::from(0x30_u8), NamedStorageTest { raw: 0x30 });
+    assert_eq!(u8::from(y), 0x51);
+    assert_eq!("0x51".parse(), Ok(y));
+    assert_eq!(NamedStorageTest::try_new(0x51), Ok(y));
+    assert_eq!(format!("{y:?}"), "NamedStorageTest { <value>: 81, flag: true, high: 5 }");
+}
+
+// Fields only use the low 16 bits, so this behaves the same on 16-bit targets
+bitfield! {
+    pub struct UsizeTest(usize) {
+        [0..4]  pub low: u8,
+        [4..12] pub mid: u8,
+        [15]    pub top,
+    }
+}
+
+bitfield! {
+    pub struct UsizeByteTest(u8) {
+        [0..4] pub a: u8,
+        [4..8] pub b: u8,
+    }
+}
+
+#[test]
+fn test_bitfield_usize() {
+    let x = UsizeTest(0x8a5c);
+    assert_eq!(x.low(), 0xc);
+    assert_eq!(x.mid(), 0xa5);
+    assert!(x.top());
+    assert_eq!(x.with_mid(0x12).with_top(false), UsizeTest(0x012c));
+    assert_eq!(get_bits(x.0, 4, 12), 0xa5);
+
+    let byte: UsizeByteTest = x.extract_at(4);
+    assert_eq!((byte.a(), byte.b()), (5, 0xa));
+    assert_eq!(x.insert_at(8, UsizeByteTest(0x3c)), UsizeTest(0x3c5c));
+
+    assert_eq!(TruncateInto::<u16>::truncate_into(0x1_2345_usize), 0x2345_u16);
+    assert_eq!(TruncateInto::<u8>::truncate_into(0x1234_usize), 0x34_u8);
+}
+
+// Bounds are only on the narrow type, in both directions
+fn set_top_bit<T: WideningInto<u64> + TruncateFrom<u64>>(value: T, bits: u32) -> T {
+    T::truncate_from(value.widen_into() | 1 << (bits - 1))
+}
+
+#[test]
+fn test_truncate_from_widening_into() {
+    assert_eq!(u8::truncate_from(0x1234_u16), 0x34);
+    assert_eq!(u32::truncate_from(0x1_0000_0002_u64), 2);
+    assert_eq!(u24::truncate_from(0x1234_5678_u32), u24::new(0x34_5678));
+    assert_eq!(WideningInto::<u128>::widen_into(0xff_u8), 0xff_u128);
+    assert_eq!(WideningInto::<u32>::widen_into(u24::MAX), 0x00ff_ffff);
+
+    assert_eq!(set_top_bit(0x01_u8, 8), 0x81);
+    assert_eq!(set_top_bit(0x0001_u16, 16), 0x8001);
+    // Bits above the narrow type are discarded
+    assert_eq!(set_top_bit(0x01_u8, 9), 0x01);
+}
+
+#[test]
+fn test_bitfield_field_spec() {
+    const B: FieldSpec = FieldSpec::new(11, 5);
+    assert_eq!(B.msb(), 16);
+    assert_eq!(B.range(), BasicBitfieldTest::B_RANGE);
+    assert_eq!(B.mask(), 0xf800);
+    assert_eq!(FieldSpec::from(BasicBitfieldTest::C_RANGE), FieldSpec::new(6, 5));
+    assert_eq!(FieldSpec::from(BasicBitfieldTest::LAYOUT[0]), FieldSpec::new(24, 8));
+
+    let mut x = BasicBitfieldTest(0x1234_5678);
+    assert_eq!(x.field(B), u128::from(x.b()));
+    assert_eq!(x.field(FieldSpec::from_range(0..32)), 0x1234_5678);
+
+    x.set_field(B, 0x3f);
+    assert_eq!(x.b(), 0x1f);
+    assert_eq!(x.with_field(FieldSpec::new(2, 1), 0), x.with_d(false));
+    assert_eq!(x.with_field(FieldSpec::new(24, 8), 0xab).a(), 0xab);
+}
+
+#[test]
+#[should_panic(expected = "field extends past the end of the value")]
+fn test_bitfield_field_spec_overflow() {
+    let _ = FlagSetTest(0).field(FieldSpec::new(12, 5));
+}
+
+#[test]
+fn test_bitfield_from_value_truncated() {
+    assert_eq!(
+        BasicBitfieldTest::from_value_truncated(0x1234_5678_u32),
+        BasicBitfieldTest(0x1234_5678)
+    );
+    assert_eq!(
+        BasicBitfieldTest::from_value_truncated(0xffff_ffff_1234_5678_u64),
+        BasicBitfieldTest(0x1234_5678),
+    );
+    assert_eq!(FlagSetTest::from_value_truncated(0x0001_8009_u32), FlagSetTest(0x8009));
+    assert_eq!(FlagSetTest::from_value_truncated(0x8009_usize), FlagSetTest(0x8009));
+    assert_eq!(UsizeTest::from_value_truncated(0x8a5c_u128), UsizeTest(0x8a5c));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Low = 0,
+    High = 1,
+}
+
+impl From<u8> for Mode {
+    fn from(value: u8) -> Self {
+        if value == 0 {
+            Self::Low
+        } else {
+            Self::High
+        }
+    }
+}
+
+impl From<Mode> for u8 {
+    fn from(value: Mode) -> Self {
+        value as u8
+    }
+}
+
+bitfield! {
+    #[bitfield(bit_offset = 8, reset(b = 0x3, c = true, d = Mode::High))]
+    pub struct ResetTest(u16) {
+        [ 8..12] pub a: u8,
+        [12..14] pub b: u8,
+        [14]     pub c,
+        [15]     pub d: u8 as Mode,
+    }
+}
+
+bitfield! {
+    #[bitfield(reset(a = 0x5), no_derive(Default))]
+    pub struct ResetNoDefaultTest(u8) {
+        [0..4] pub a: u8,
+    }
+}
+
+impl Default for ResetNoDefaultTest {
+    fn default() -> Self {
+        Self(0xff)
+    }
+}
+
+#[test]
+fn test_bitfield_zero_reset() {
+    const TABLE: [BasicBitfieldTest; 2] = [BasicBitfieldTest::ZERO; 2];
+    assert_eq!(TABLE[0], BasicBitfieldTest::default());
+    assert_eq!(UsizeTest::ZERO, UsizeTest(0));
+    assert_eq!(ResetTest::ZERO, ResetTest(0));
+
+    assert_eq!(ResetTest::RESET, ResetTest(0x00f0));
+    assert_eq!(ResetTest::default(), ResetTest::RESET);
+    assert_eq!(ResetTest::RESET.b(), 3);
+    assert_eq!(ResetTest::RESET.d(), Mode::High);
+
+    assert_eq!(ResetNoDefaultTest::RESET, ResetNoDefaultTest(0x05));
+    assert_eq!(ResetNoDefaultTest::default(), ResetNoDefaultTest(0xff));
+
+    let describe = |x| match x {
+        ResetTest::ZERO => "zero",
+        ResetTest::RESET => "reset",
+        _ => "other",
+    };
+    assert_eq!(describe(ResetTest(0)), "zero");
+    assert_eq!(describe(ResetTest::default()), "reset");
+    assert_eq!(describe(ResetTest(1)), "other");
+}
+
+// Flag `d` is write-one-to-clear, and `b` counts writes to `a`
+#[derive(Default)]
+struct EmulatorTest {
+    reads: usize,
+}
+
+impl EmulatorHooks<BasicBitfieldTest> for EmulatorTest {
+    fn before_read(&mut self, field: &FieldInfo, _: &mut BasicBitfieldTest) {
+        if field.name == "a" {
+            self.reads += 1;
+        }
+    }
+
+    fn after_read(&mut self, field: &FieldInfo, value: &mut BasicBitfieldTest) {
+        if field.name == "e" {
+            value.set_e(0);
+        }
+    }
+
+    fn on_write(
+        &mut self,
+        field: &FieldInfo,
+        old: BasicBitfieldTest,
+        value: &mut BasicBitfieldTest,
+    ) {
+        match field.name {
+            "a" => value.set_b(old.b().wrapping_add(1) & 0x1f),
+            "d" => value.set_d(old.d() && !value.d()),
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn test_bitfield_emulated() {
+    let initial = BasicBitfieldTest::default().with_d(true).with_e(3);
+    let mut reg = Emulated::new(initial, EmulatorTest::default());
+
+    assert_eq!(reg.read(), initial);
+    assert_eq!(reg.get(), initial.with_e(0));
+    assert_eq!(reg.hooks().reads, 1);
+
+    reg.write(BasicBitfieldTest::default().with_a(0x12).with_b(0x1f));
+    assert_eq!(reg.get(), initial.with_e(0).with_a(0x12).with_b(1));
+    reg.write(reg.get().with_d(true));
+    assert_eq!(reg.get().b(), 2);
+    assert!(!reg.get().d());
+
+    // Access by the device model has no side effects
+    reg.set(initial);
+    reg.update(|r| r.set_c(4));
+    assert_eq!(reg.get(), initial.with_c(4));
+    reg.hooks_mut().reads = 10;
+    let (value, hooks) = reg.into_inner();
+    assert_eq!((value, hooks.reads), (initial.with_c(4), 10));
+}
+
+fn record<B>(writes: &mut Vec<B>) -> impl FnOnce(B) -> Result<(), ()> + '_ {
+    |value| {
+        writes.push(value);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_bitfield_cached() {
+    let initial = BasicBitfieldTest::default().with_a(0x12).with_e(1);
+    let mut reg = Cached::new(initial);
+    let mut writes = Vec::new();
+    assert!(!reg.is_dirty());
+    assert_eq!(reg.flush(record(&mut writes)), Ok(false));
+
+    reg.update(|r| r.set_c(0x1f));
+    assert_eq!(reg.dirty_bits(), 0x0000_07c0);
+    let dirty: Vec<_> = reg.dirty_fields().map(|f| f.name).collect();
+    assert_eq!(dirty, ["c", "z"]);
+
+    // Changing a field back makes it clean again
+    reg.set(reg.get().with_c(0).with_b(1));
+    let dirty: Vec<_> = reg.dirty_fields().map(|f| f.name).collect();
+    assert_eq!(dirty, ["b", "z"]);
+
+    // A failed write leaves the value dirty
+    assert_eq!(reg.flush(|_| Err("bus error")), Err("bus error"));
+    assert!(reg.is_dirty());
+    assert_eq!(reg.flush(record(&mut writes)), Ok(true));
+    assert!(!reg.is_dirty());
+    assert_eq!(writes, [initial.with_b(1)]);
+
+    reg.invalidate();
+    assert_eq!(reg.dirty_bits(), 0xffff_ffff);
+    assert_eq!(reg.dirty_fields().count(), BasicBitfieldTest::LAYOUT.len());
+    assert_eq!(reg.flush(record(&mut writes)), Ok(true));
+    assert_eq!(writes.len(), 2);
+
+    reg.reload(initial);
+    assert!(!reg.is_dirty());
+    assert_eq!(reg.into_inner(), initial);
+}
+
+bitfield! {
+    pub struct DispatchDataTest(u16) {
+        [0..2]  pub kind: u8,
+        [2..16] pub payload: u16,
+    }
+}
+
+bitfield! {
+    pub struct DispatchControlTest(u16) {
+        [0..4]  pub kind: u8,
+        [4..8]  pub command: u8,
+        [15]    pub urgent,
+    }
+}
+
+bitfield_dispatch! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum DispatchTest(u16) {
+        /// Control packets, which take precedence over data packets with the same low
+        /// bits
+        Control(DispatchControlTest) { mask: 0x000f, value: 0x0001 },
+        Data(DispatchDataTest) { mask: 0x0003, value: 0x0001 },
+        Ack(DispatchDataTest) { mask: 0xffff, value: 0x0002, },
+    }
+}
+
+#[test]
+fn test_bitfield_dispatch() {
+    let DispatchTest::Control(control) = DispatchTest::decode(0x8071).unwrap() else {
+        panic!("expected a control packet");
+    };
+    assert_eq!(control.command(), 7);
+    assert!(control.urgent());
+
+    let DispatchTest::Data(data) = DispatchTest::decode(0x0105).unwrap() else {
+        panic!("expected a data packet");
+    };
+    assert_eq!(data.payload(), 0x41);
+
+    assert_eq!(
+        DispatchTest::decode(0x0002),
+        Some(DispatchTest::Ack(DispatchDataTest(2)))
+    );
+    assert_eq!(DispatchTest::decode(0x0102), None);
+    assert_eq!(DispatchTest::decode(0x0000), None);
+
+    assert_eq!(DispatchTest::Data(DispatchDataTest(0x0105)).value(), 0x0105);
+    assert_eq!(u16::from(DispatchTest::Control(control)), 0x8071);
+}
+
+// Stands in for a register value type from another crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForeignRegister(u16);
+
+impl From<u16> for ForeignRegister {
+    fn from(value: u16) -> Self {
+        Self(value)
+    }
+}
+
+impl From<ForeignRegister> for u16 {
+    fn from(value: ForeignRegister) -> Self {
+        value.0
+    }
+}
+
+bitfield_ext! {
+    pub trait ForeignRegisterExt(u16) for ForeignRegister {
+        [0]       flag,
+        [1..4]    small,
+        [4..=7]   nibble: u8,
+        [8]       mode: u8 as Mode,
+        [9..16]   high: u8,
+    }
+}
+
+#[test]
+fn test_bitfield_ext() {
+    let mut reg = ForeignRegister(0xa5f3);
+    assert!(reg.flag());
+    assert_eq!(reg.small(), 1);
+    assert_eq!(reg.nibble(), 0xf);
+    assert_eq!(reg.mode(), Mode::High);
+    assert_eq!(reg.high(), 0x52);
+
+    reg.set_flag(false);
+    reg.set_mode(Mode::Low);
+    assert_eq!(reg, ForeignRegister(0xa4f2));
+    assert_eq!(reg.with_small(7).with_nibble(0), ForeignRegister(0xa40e));
+    assert_eq!(reg.with_high(0x7f), ForeignRegister(0xfef2));
+}
+
+#[test]
+fn test_bitfield_layout_diagram() {
+    // Fields `b` and `c` are hidden by `z`, which overlaps them
+    let expected = [
+        " 31   24 23   20 19   10 9    3  2  1   0",
+        "+-------+-------+-------+------+---+-----+",
+        "|   a   |   -   |   z   |  -   | d |  e  |",
+        "| 0xfe  |  0xd  | 0x32e | 0x53 | 0 | 0x0 |",
+        "+-------+-------+-------+------+---+-----+",
+    ];
+    let diagram = BasicBitfieldTest(0xfedc_ba98).layout_diagram().to_string();
+    assert_eq!(diagram, expected.join("\n"));
+
+    let expected = [
+        "  15  14   4  3  2   1  0",
+        "+----+------+---+-----+---+",
+        "| c  |  -   | b |  -  | a |",
+        "| 1  | 0x0  | 1 | 0x0 | 1 |",
+        "+----+------+---+-----+---+",
+    ];
+    assert_eq!(FlagSetTest(0x8009).layout_diagram().to_string(), expected.join("\n"));
+}
+
+#[test]
+fn test_bitfield_format_table() {
+    let mut out = String::new();
+    BasicBitfieldTest(0xfedc_ba98).format_table(&mut out).unwrap();
+    let expected = [
+        "field | bits   |   hex | decimal",
+        "------+--------+-------+--------",
+        "a     | 24..32 |  0xfe |     254",
+        "b     | 11..16 |  0x17 |      23",
+        "c     | 6..11  |   0xa |      10",
+        "d     | 2      |   0x0 |       0",
+        "e     | 0..2   |   0x0 |       0",
+        "z     | 10..20 | 0x32e |     814",
+        "",
+    ];
+    assert_eq!(out, expected.join("\n"));
+}
@@ -0,0 +1,104 @@
+#![warn(clippy::pedantic)]
+
+use tartan_bitfield::{bitfield, bitmatch};
+
+#[derive(Debug, PartialEq, Eq)]
+enum Insn {
+    Move { d: u8, s: u8 },
+    Immediate(u8, u16),
+    Branch(u16),
+    Nop,
+    Unknown(u16),
+}
+
+fn decode(word: u16) -> Insn {
+    bitmatch!(word {
+        // More specific patterns first, since the first match wins
+        "0000_0000_0000_0000" => Insn::Nop,
+        "0000_dddd_ssss_????" => Insn::Move { d, s },
+        "01dd_iiii_iiii_iiii" => |d, i| Insn::Immediate(d, i),
+        "1bbb_bbbb_bbbb_bbbb" => |b: u16| {
+            Insn::Branch(b)
+        }
+        _ => Insn::Unknown(word),
+    })
+}
+
+#[test]
+fn test_bitmatch_arms() {
+    assert_eq!(decode(0x0000), Insn::Nop);
+    assert_eq!(decode(0x0a5f), Insn::Move { d: 0xa, s: 0x5 });
+    assert_eq!(decode(0x0001), Insn::Move { d: 0, s: 0 });
+    assert_eq!(decode(0x7123), Insn::Immediate(3, 0x123));
+    assert_eq!(decode(0xfffe), Insn::Branch(0x7ffe));
+    assert_eq!(decode(0x2000), Insn::Unknown(0x2000));
+}
+
+#[test]
+fn test_bitmatch_scattered_bits() {
+    // Bits marked with the same letter are concatenated from left to right
+    let value = bitmatch!(0b1011_0110_u8 {
+        "a_bbbb_a_1_a" => |a: u8, b: u8| (a, b),
+        _ => (0xff, 0xff),
+    });
+    assert_eq!(value, (0b110, 0b0110));
+}
+
+#[test]
+fn test_bitmatch_short_pattern() {
+    // Bits above the end of the pattern must be zero
+    let check = |word: u32| {
+        bitmatch!(word {
+            "1x" => |x: u8| Some(x),
+            _ => None,
+        })
+    };
+    assert_eq!(check(0b11), Some(1));
+    assert_eq!(check(0b10), Some(0));
+    assert_eq!(check(0b111), None);
+    assert_eq!(check(0b01), None);
+}
+
+#[test]
+fn test_bitmatch_wide() {
+    let word = u128::MAX - 1;
+    let value = bitmatch!(word {
+        "hhhh_hhhh_????_????_????_????_????_????_\
+         ????_????_????_????_????_????_????_????_\
+         ????_????_????_????_????_????_????_????_\
+         ????_????_????_????_????_????_????_lll?" => |h: u8, l: u8| (h, l),
+        _ => (0, 0),
+    });
+    assert_eq!(value, (0xff, 0x7));
+
+    let whole = bitmatch!(word {
+        "????_????_????_????_????_????_????_????_\
+         ????_????_????_????_????_????_????_????_\
+         ????_????_????_????_????_????_????_????_\
+         ????_????_????_????_????_????_????_????" => 1,
+        _ => 0,
+    });
+    assert_eq!(whole, 1);
+}
+
+bitfield! {
+    pub struct BitmatchTest(u32) {
+        [0..7]   pub opcode: u8,
+        [7..12]  pub rd: u8,
+    }
+}
+
+#[test]
+fn test_bitmatch_expression() {
+    let insn = BitmatchTest(0x02a0_0093);
+    let rd = bitmatch!(insn.0 & 0xfff {
+        "dddd_d001_0011" => |d: u8| Some(d),
+        _ => None,
+    });
+    assert_eq!(rd, Some(insn.rd()));
+
+    let bytes = [0x12_usize, 0x80];
+    let signs: Vec<bool> =
+        bytes.iter().map(|&b| bitmatch!(b { "1???_????" => true, _ => false })).collect();
+    assert_eq!(signs, [false, true]);
+}
@@ -0,0 +1,104 @@
+#![warn(clippy::pedantic)]
+
+use tartan_bitfield::{
+    bitfield, bitfield_frame, bitfields_from_bytes, copy_bitfields_from_bytes,
+    copy_bitfields_to_bytes, ByteOrder, FrameError, LengthMismatchError,
+};
+
+bitfield! {
+    pub struct Wide(u64) {
+        [ 0..32] pub low: u32,
+        [32..64] pub high: u32,
+    }
+}
+
+bitfield! {
+    pub struct FrameHeader(u32) {
+        [ 0..8]  pub kind: u8,
+        [ 8..14] pub length: u8,
+        [31]     pub urgent,
+    }
+}
+
+bitfield_frame!(FrameHeader { pub length: ByteOrder::Little });
+
+#[test]
+fn test_bytes_round_trip() {
+    let values =
+        [Wide(0x0123_4567_89ab_cdef), Wide(0), Wide(u64::MAX), Wide(0xfedc_ba98)];
+
+    for order in [ByteOrder::Little, ByteOrder::Big, ByteOrder::Native] {
+        let mut bytes = [0_u8; 32];
+        copy_bitfields_to_bytes(&values, &mut bytes, order).unwrap();
+
+        let mut copy = [Wide::default(); 4];
+        copy_bitfields_from_bytes(&mut copy, &bytes, order).unwrap();
+        assert_eq!(copy, values);
+
+        let iter = bitfields_from_bytes::<Wide, _>(&bytes, order).unwrap();
+        assert_eq!(iter.len(), 4);
+        assert!(iter.eq(values));
+
+        let iter = bitfields_from_bytes::<Wide, _>(&bytes, order).unwrap();
+        assert!(iter.rev().eq(values.into_iter().rev()));
+    }
+
+    let mut bytes = [0_u8; 8];
+    copy_bitfields_to_bytes(&values[..1], &mut bytes, ByteOrder::Big).unwrap();
+    assert_eq!(bytes, [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]);
+    copy_bitfields_to_bytes(&values[..1], &mut bytes, ByteOrder::Little).unwrap();
+    assert_eq!(bytes, [0xef, 0xcd, 0xab, 0x89, 0x67, 0x45, 0x23, 0x01]);
+}
+
+#[test]
+fn test_bytes_size_mismatch() {
+    let mut bitfields = [Wide(1), Wide(2)];
+    let mut bytes = [0xff_u8; 15];
+
+    assert!(bitfields_from_bytes::<Wide, _>(&bytes, ByteOrder::Little).is_none());
+    let error = LengthMismatchError { expected: 16, actual: 15 };
+    assert_eq!(
+        copy_bitfields_from_bytes(&mut bitfields, &bytes, ByteOrder::Little),
+        Err(error)
+    );
+    assert_eq!(
+        copy_bitfields_to_bytes(&bitfields, &mut bytes, ByteOrder::Little),
+        Err(error)
+    );
+
+    // Nothing was modified
+    assert_eq!(bitfields, [Wide(1), Wide(2)]);
+    assert_eq!(bytes, [0xff; 15]);
+}
+
+#[test]
+fn test_bytes_frame() {
+    let header = FrameHeader::default().with_kind(0x7f).with_urgent(true);
+    let mut bytes = [0xff_u8; 12];
+    assert_eq!(header.emit(&[1, 2, 3], &mut bytes), Ok(7));
+    assert_eq!(bytes, [0x7f, 0x03, 0x00, 0x80, 1, 2, 3, 0xff, 0xff, 0xff, 0xff, 0xff]);
+
+    let (parsed, payload) = FrameHeader::parse(&bytes).unwrap();
+    assert_eq!(parsed, header.with_length(3));
+    assert_eq!(payload, [1, 2, 3]);
+
+    // Empty payload
+    assert_eq!(header.emit(&[], &mut bytes[..4]), Ok(4));
+    assert_eq!(FrameHeader::parse(&bytes[..4]), Ok((header, &[][..])));
+}
+
+#[test]
+fn test_bytes_frame_errors() {
+    let header = FrameHeader::default().with_kind(1);
+    let mut bytes = [0xff_u8; 80];
+
+    assert_eq!(header.emit(&[0; 64], &mut bytes), Err(FrameError::PayloadTooLong));
+    assert_eq!(header.emit(&[0; 10], &mut bytes[..13]), Err(FrameError::BufferTooSmall));
+    // Nothing was modified
+    assert_eq!(bytes, [0xff; 80]);
+
+    assert_eq!(header.emit(&[0; 63], &mut bytes), Ok(67));
+    assert!(FrameHeader::parse(&bytes).is_ok());
+    assert_eq!(FrameHeader::parse(&bytes[..66]), Err(FrameError::Truncated));
+    assert_eq!(FrameHeader::parse(&bytes[..3]), Err(FrameError::Truncated));
+}
@@ -0,0 +1,80 @@
+#![warn(clippy::pedantic)]
+
+use tartan_bitfield::{bitfield, Bitfield, Validate, ValidationError, WriteFields};
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Bitfield)]
+#[repr(transparent)]
+#[field(24..32, pub a: u8)]
+#[field(11..16, pub b: u8)]
+#[field(6..11, pub c: u8 where 1..)]
+#[field(2, pub d)]
+#[field(3, pub f: u8)]
+#[field(0..=1, pub e: u8)]
+#[field(10..20, pub z: u16)]
+pub struct DeriveTest(u32);
+
+bitfield! {
+    pub struct MacroTest(u32) {
+        [24..32] pub a: u8,
+        [11..16] pub b: u8,
+        [ 6..11] pub c: u8 where 1..,
+        [ 2    ] pub d,
+        [ 3    ] pub f: u8,
+        [ 0..=1] pub e: u8,
+        [10..20] pub z: u16,
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Bitfield)]
+#[field(0..4, pub low: u8)]
+#[field(4..8, pub high: u8 as Nibble)]
+#[invariant(Self::check)]
+pub struct InvariantDeriveTest(u8);
+
+impl InvariantDeriveTest {
+    fn check(self) -> bool {
+        self.low() <= self.high().low()
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Bitfield)]
+#[field(0..4, pub low: u8)]
+pub struct Nibble(u8);
+
+#[test]
+fn test_derive_matches_macro() {
+    for value in [0, u32::MAX, 0x1234_5678, 0xdead_beef, 0x0000_ffc7] {
+        let derived = DeriveTest(value);
+        let expanded = MacroTest(value);
+        assert_eq!(derived.a(), expanded.a());
+        assert_eq!(derived.b(), expanded.b());
+        assert_eq!(derived.c(), expanded.c());
+        assert_eq!(derived.d(), expanded.d());
+        assert_eq!(derived.e(), expanded.e());
+        assert_eq!(derived.f(), expanded.f());
+        assert_eq!(derived.z(), expanded.z());
+        assert_eq!(derived.validate(), expanded.validate());
+
+        let mut derived_out = String::new();
+        let mut expanded_out = String::new();
+        derived.write_fields(&mut derived_out).unwrap();
+        expanded.write_fields(&mut expanded_out).unwrap();
+        assert_eq!(derived_out, expanded_out);
+
+        let debug = format!("{derived:?}").replace("DeriveTest", "MacroTest");
+        assert_eq!(debug, format!("{expanded:?}"));
+    }
+
+    assert_eq!(DeriveTest(0).with_a(0xab).with_d(true), DeriveTest(0xab00_0004));
+}
+
+#[test]
+fn test_derive_invariant() {
+    assert_eq!(InvariantDeriveTest(0x53).validate(), Ok(()));
+    assert_eq!(
+        InvariantDeriveTest(0x35).validate(),
+        Err(ValidationError::Invariant("Self::check"))
+    );
+    assert_eq!(InvariantDeriveTest(0x35).high(), Nibble(0x3));
+    assert!(InvariantDeriveTest::try_new(0x35).is_err());
+}
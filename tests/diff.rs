@@ -0,0 +1,56 @@
+#![warn(clippy::pedantic)]
+
+use tartan_bitfield::{bitfield, FieldDiff};
+
+bitfield! {
+    pub struct DiffTest(u32) {
+        [0..4]   pub mode: u8,
+        [4..=11] pub count: u8,
+        [31]     pub enable,
+    }
+}
+
+#[test]
+fn test_diff_plain() {
+    let old = DiffTest::default().with_mode(3).with_count(0x12);
+    let new = old.with_count(0x7f).with_enable(true);
+    let diff = FieldDiff::new(old, new).with_color(false);
+    assert_eq!(diff.changed().collect::<Vec<_>>(), ["count", "enable"]);
+    assert_eq!(
+        diff.to_string(),
+        concat!(
+            "  field  | bits  |  old |  new\n",
+            "---------+-------+------+-----\n",
+            "  mode   | 0..4  |  0x3 |  0x3\n",
+            "* count  | 4..12 | 0x12 | 0x7f\n",
+            "* enable | 31    |  0x0 |  0x1\n",
+        ),
+    );
+}
+
+#[test]
+fn test_diff_color() {
+    let mut old = DiffTest::default().with_mode(3);
+    let new = old.with_enable(true);
+    let diff = FieldDiff::new(old, new).with_color(true);
+    assert_eq!(
+        diff.to_string(),
+        concat!(
+            "  field  | bits  | old | new\n",
+            "---------+-------+-----+----\n",
+            "  mode   | 0..4  | 0x3 | 0x3\n",
+            "  count  | 4..12 | 0x0 | 0x0\n",
+            "* \x1b[1menable\x1b[0m | 31    | ",
+            "\x1b[31m0x0\x1b[0m | \x1b[32m0x1\x1b[0m\n",
+        ),
+    );
+}
+
+#[test]
+fn test_diff_unchanged() {
+    let value = DiffTest(0x8000_0123);
+    let diff = FieldDiff::new(value, value);
+    assert_eq!(diff.changed().count(), 0);
+    assert!(!diff.to_string().contains('\x1b'));
+    assert!(!diff.to_string().contains('*'));
+}
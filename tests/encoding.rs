@@ -0,0 +1,89 @@
+#![warn(clippy::pedantic)]
+
+use tartan_bitfield::{Bcd, Gray, OneHot, Swapped};
+
+#[test]
+fn test_bcd() {
+    for i in 0..=99_u8 {
+        let bcd = Bcd::<u8>::from_binary(i);
+        assert_eq!(bcd.raw(), ((i / 10) << 4) | (i % 10));
+        assert_eq!(bcd.to_binary(), i);
+        assert_eq!(bcd.try_to_binary(), Some(i));
+        assert_eq!(Bcd::<u8>::try_from_binary(i), Some(bcd));
+    }
+    assert_eq!(Bcd::<u8>::try_from_binary(100), None);
+    assert_eq!(Bcd::<u8>::from_binary(123).raw(), 0x23);
+
+    assert_eq!(Bcd::<u8>::MAX, 99);
+    assert_eq!(Bcd::<u16>::MAX, 9999);
+    assert_eq!(Bcd::<u32>::MAX, 9999_9999);
+    assert_eq!(Bcd::<u64>::MAX, 9999_9999_9999_9999);
+    assert_eq!(Bcd::<u128>::MAX, 9999_9999_9999_9999_9999_9999_9999_9999);
+
+    assert_eq!(Bcd::<u16>::from_binary(2024).raw(), 0x2024);
+    assert_eq!(Bcd::<u32>::from_raw(0x1234_5678).to_binary(), 1234_5678);
+    assert_eq!(Bcd::<u64>::from_binary(Bcd::<u64>::MAX).raw(), 0x9999_9999_9999_9999);
+    assert_eq!(Bcd::<u128>::from_raw(u128::MAX).try_to_binary(), None);
+    let max = Bcd::<u128>::from_raw(0x9999_9999_9999_9999_9999_9999_9999_9999);
+    assert_eq!(max.try_to_binary(), Some(Bcd::<u128>::MAX));
+
+    for invalid in [0x0a_u16, 0x00f0, 0x0b00, 0xc000, 0xffff] {
+        assert_eq!(Bcd::<u16>::from_raw(invalid).try_to_binary(), None);
+    }
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_gray() {
+    let sequence = [
+        0b0000_u8, 0b0001, 0b0011, 0b0010, 0b0110, 0b0111, 0b0101, 0b0100,
+        0b1100, 0b1101, 0b1111, 0b1110, 0b1010, 0b1011, 0b1001, 0b1000,
+    ];
+    for (i, &gray) in (0_u8..).zip(sequence.iter()) {
+        assert_eq!(Gray::from_binary(i).raw(), gray);
+        assert_eq!(Gray::from_raw(gray).to_binary(), i);
+    }
+
+    for i in 0..=u16::MAX {
+        let gray = Gray::from_binary(i);
+        assert_eq!(gray.to_binary(), i);
+        if i > 0 {
+            let previous = Gray::from_binary(i - 1);
+            assert_eq!((gray.raw() ^ previous.raw()).count_ones(), 1);
+        }
+    }
+
+    assert_eq!(
+        Gray::from_raw(u128::MAX).to_binary(),
+        0xaaaa_aaaa_aaaa_aaaa_aaaa_aaaa_aaaa_aaaa,
+    );
+    assert_eq!(Gray::from_binary(u128::MAX).raw(), 1 << 127);
+}
+
+#[test]
+fn test_one_hot() {
+    for i in 0..8 {
+        assert_eq!(OneHot::<u8>::from_index(i).raw(), 1 << i);
+        assert_eq!(OneHot::from_raw(1_u8 << i).index(), Some(i));
+        assert_eq!(OneHot::from_raw(!(1_u8 << i)).index(), None);
+    }
+    for i in 0..128 {
+        assert_eq!(OneHot::<u128>::from_index(i).raw(), 1 << i);
+        assert_eq!(OneHot::from_raw(1_u128 << i).index(), Some(i));
+        assert_eq!(OneHot::from_raw(u128::MAX << i).index(), (i == 127).then_some(127));
+    }
+    assert_eq!(OneHot::from_raw(0_u32).index(), None);
+    assert_eq!(OneHot::from_raw(u32::MAX).index(), None);
+    assert_eq!(OneHot::from_raw(0x8000_0001_u32).index(), None);
+}
+
+#[test]
+fn test_swapped() {
+    assert_eq!(Swapped::new(0x1234_u16).raw(), 0x3412);
+    assert_eq!(Swapped::from_raw(0x3412_u16).get(), 0x1234);
+    assert_eq!(Swapped::new(0x1234_5678_u32).raw(), 0x7856_3412);
+    assert_eq!(Swapped::from_raw(0x0102_0304_0506_0708_u64).get(), 0x0807_0605_0403_0201);
+    assert_eq!(u16::from(Swapped::new(0xabcd_u16)), 0xcdab);
+    assert_eq!(Swapped::<u16>::from(0xcdab).get(), 0xabcd);
+    assert_eq!(format!("{:?}", Swapped::new(0xabcd_u16)), "Swapped(0xabcd)");
+}
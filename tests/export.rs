@@ -0,0 +1,126 @@
+#![warn(clippy::pedantic)]
+
+use tartan_bitfield::uint::u24;
+use tartan_bitfield::{bitfield, export, ByteOrder};
+
+bitfield! {
+    pub struct ExportTest(u32) {
+        [0..4]   pub mode,
+        [4..=11] pub count: u8,
+        [31]     pub enable,
+    }
+}
+
+#[test]
+fn test_export_json() {
+    let mut out = String::new();
+    export::write_json::<ExportTest, _, _>(&mut out).unwrap();
+    assert_eq!(
+        out,
+        concat!(
+            r#"{"name":"ExportTest","bits":32,"fields":["#,
+            r#"{"name":"mode","lsb":0,"msb":4,"width":4,"flag":false},"#,
+            r#"{"name":"count","lsb":4,"msb":12,"width":8,"flag":false},"#,
+            r#"{"name":"enable","lsb":31,"msb":32,"width":1,"flag":true}"#,
+            r#"]}"#,
+        ),
+    );
+    assert_eq!(export::to_json::<ExportTest, _>(), out);
+}
+
+#[test]
+fn test_export_toml() {
+    let out = export::to_toml::<ExportTest, _>();
+    let fields: Vec<&str> = out.split("\n\n").collect();
+    assert_eq!(fields[0], "name = \"ExportTest\"\nbits = 32");
+    assert_eq!(
+        fields[2],
+        "[[fields]]\nname = \"count\"\nlsb = 4\nmsb = 12\nwidth = 8\nflag = false",
+    );
+    assert_eq!(fields.len(), 4);
+}
+
+bitfield! {
+    pub struct WiresharkTest(u24) {
+        [0..12]  pub length: u16,
+        [23]     pub urgent,
+    }
+}
+
+#[test]
+fn test_export_wireshark() {
+    let mut out = String::new();
+    export::write_wireshark::<WiresharkTest, _, _>(
+        &mut out,
+        "test.hdr",
+        ByteOrder::Little,
+    )
+    .unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "local WiresharkTest_fields = {",
+            concat!(
+                r#"    length = ProtoField.uint24("test.hdr.length", "length", "#,
+                "base.HEX, nil, 0x000fff),",
+            ),
+            concat!(
+                r#"    urgent = ProtoField.bool("test.hdr.urgent", "urgent", 24, "#,
+                "nil, 0x800000),",
+            ),
+            "}",
+            "",
+            "local function add_WiresharkTest_fields(tree, range)",
+            "    tree:add_le(WiresharkTest_fields.length, range)",
+            "    tree:add_le(WiresharkTest_fields.urgent, range)",
+            "end",
+        ],
+    );
+}
+
+#[test]
+#[should_panic(expected = "at most 64 bits")]
+fn test_export_wireshark_too_wide() {
+    bitfield! {
+        pub struct Wide(u128) {
+            [100] pub high,
+        }
+    }
+
+    let mut out = String::new();
+    let _ = export::write_wireshark::<Wide, _, _>(&mut out, "wide", ByteOrder::Big);
+}
+
+bitfield! {
+    pub struct KaitaiTest(u32) {
+        [0..8]   pub low: u8,
+        [4..12]  pub overlap: u8,
+        [31]     pub top,
+    }
+}
+
+#[test]
+fn test_export_kaitai() {
+    let mut out = String::new();
+    export::write_kaitai::<KaitaiTest, _, _>(&mut out, ByteOrder::Big).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "meta:",
+            "  id: kaitai_test",
+            "  bit-endian: be",
+            "seq:",
+            "  - id: raw_value",
+            "    type: b32",
+            "instances:",
+            "  low:",
+            "    value: (raw_value >> 0) & 0xff",
+            "  overlap:",
+            "    value: (raw_value >> 4) & 0xff",
+            "  top:",
+            "    value: ((raw_value >> 31) & 0x1) != 0",
+        ],
+    );
+}
@@ -0,0 +1,61 @@
+#![warn(clippy::pedantic)]
+#![allow(clippy::float_cmp)]
+
+use tartan_bitfield::{Q, UQ};
+
+#[test]
+fn test_fixed_signed() {
+    type Q4_4 = Q<4, 4>;
+    assert_eq!(Q4_4::from(0x00_u8).to_f64(), 0.0);
+    assert_eq!(Q4_4::from(0x01_u8).to_f64(), 0.0625);
+    assert_eq!(Q4_4::from(0x7f_u8).to_f64(), 7.9375);
+    assert_eq!(Q4_4::from(0x80_u8).to_f64(), -8.0);
+    assert_eq!(Q4_4::from(0xff_u8).to_f64(), -0.0625);
+
+    // Bits beyond the format's width are ignored
+    assert_eq!(Q4_4::from(0xff18_u16).to_f64(), 1.5);
+    assert_eq!(Q4_4::from(0xff18_u16).raw(), 0x18);
+
+    assert_eq!(u8::from(Q4_4::from_f64(-0.0625)), 0xff);
+    assert_eq!(u16::from(Q4_4::from_f64(-0.0625)), 0xffff);
+    assert_eq!(u8::from(Q4_4::from_f32(2.03)), 0x20);
+    assert_eq!(u8::from(Q4_4::from_f32(2.04)), 0x21);
+    assert_eq!(u8::from(Q4_4::from_f32(-2.04)), 0xdf);
+
+    assert_eq!(Q::<1, 63>::from(u64::MAX).to_f64(), -(2.0_f64.powi(-63)));
+    assert_eq!(Q::<64, 0>::from(u64::MAX).raw(), -1);
+    assert_eq!(format!("{:?}", Q::<8, 8>::from_f64(-12.5)), "-12.5");
+}
+
+#[test]
+fn test_fixed_unsigned() {
+    type UQ2_6 = UQ<2, 6>;
+    assert_eq!(UQ2_6::from(0x00_u8).to_f64(), 0.0);
+    assert_eq!(UQ2_6::from(0x01_u8).to_f64(), 0.015_625);
+    assert_eq!(UQ2_6::from(0x80_u8).to_f64(), 2.0);
+    assert_eq!(UQ2_6::from(0xff_u8).to_f64(), 3.984_375);
+    assert_eq!(u8::from(UQ2_6::from_f64(1.25)), 0x50);
+
+    assert_eq!(UQ::<0, 64>::from(u64::MAX).to_f64(), 1.0 - 2.0_f64.powi(-64));
+    assert_eq!(UQ::<12, 0>::from(0xffff_u16).raw(), 0xfff);
+    assert_eq!(format!("{}", UQ::<8, 8>::from_f64(12.5)), "12.5");
+}
+
+#[test]
+fn test_fixed_from_float_wraps() {
+    assert_eq!(Q::<4, 0>::from_f64(9.0).raw(), -7);
+    assert_eq!(UQ::<4, 4>::from_f64(17.5).raw(), 0x18);
+    assert_eq!(UQ::<4, 4>::from_f64(-0.0625).raw(), 0xff);
+
+    // Beyond the range of `i64`
+    let big = 2.0_f64.powi(64) + 4096.0;
+    assert_eq!(UQ::<64, 0>::from_f64(big).raw(), 4096);
+    assert_eq!(Q::<64, 0>::from_f64(-big).raw(), -4096);
+    assert_eq!(UQ::<64, 0>::from_f64(2.0_f64.powi(63)).raw(), 1 << 63);
+    assert_eq!(UQ::<60, 4>::from_f64(2.0_f64.powi(60) + 256.0).raw(), 0x1000);
+    assert_eq!(UQ::<64, 0>::from_f64(2.0_f64.powi(200)).raw(), 0);
+
+    assert_eq!(UQ::<8, 8>::from_f64(f64::NAN).raw(), 0);
+    assert_eq!(Q::<8, 8>::from_f64(f64::INFINITY).raw(), 0);
+    assert_eq!(Q::<8, 8>::from_f64(f64::NEG_INFINITY).raw(), 0);
+}
@@ -0,0 +1,99 @@
+#![warn(clippy::pedantic)]
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation};
+use tartan_bitfield::{bitfield, ByteOrder, I2cInterface, RegisterInterface};
+
+bitfield! {
+    pub struct HalConfig(u16) {
+        [0..3]  pub mode: u8,
+        [3..6]  pub average_count: u8,
+        [15]    pub reset,
+    }
+}
+
+bitfield! {
+    pub struct HalStatus(u8) {
+        [0] pub ready,
+        [7] pub fault,
+    }
+}
+
+const SENSOR: u8 = 0x40;
+
+/// A device with byte-addressed registers, which records the transactions it sees
+struct FakeSensor {
+    memory: [u8; 8],
+    log: Vec<(u8, Vec<u8>)>,
+}
+
+impl ErrorType for FakeSensor {
+    type Error = ErrorKind;
+}
+
+impl I2c for FakeSensor {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), ErrorKind> {
+        if address != SENSOR {
+            return Err(ErrorKind::NoAcknowledge(
+                embedded_hal::i2c::NoAcknowledgeSource::Address,
+            ));
+        }
+        let mut pointer = None;
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    self.log.push((address, bytes.to_vec()));
+                    let start = usize::from(bytes[0]);
+                    let data = &bytes[1..];
+                    self.memory[start..start + data.len()].copy_from_slice(data);
+                    pointer = Some(start + data.len());
+                }
+                Operation::Read(bytes) => {
+                    let start = pointer.ok_or(ErrorKind::Other)?;
+                    bytes.copy_from_slice(&self.memory[start..start + bytes.len()]);
+                    pointer = Some(start + bytes.len());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_i2c_interface() {
+    let sensor = FakeSensor { memory: [0, 0x80, 0x09, 0x81, 0, 0, 0, 0], log: vec![] };
+    let mut interface = I2cInterface::new(sensor, SENSOR, ByteOrder::Big);
+    assert_eq!(interface.device(), SENSOR);
+
+    let config: HalConfig = interface.register(1).read().unwrap();
+    assert_eq!(config, HalConfig(0x8009));
+    assert_eq!(config.average_count(), 1);
+    assert!(interface.register::<HalStatus>(3).read().unwrap().fault());
+
+    interface.register::<HalConfig>(1).modify(|c| c.set_mode(6)).unwrap();
+    interface.register(4).write(HalStatus::default().with_ready(true)).unwrap();
+
+    let sensor = interface.into_inner();
+    assert_eq!(sensor.memory, [0, 0x80, 0x0e, 0x81, 0x01, 0, 0, 0]);
+    assert_eq!(sensor.log[3], (SENSOR, vec![1, 0x80, 0x0e]));
+    assert_eq!(sensor.log[4], (SENSOR, vec![4, 0x01]));
+}
+
+#[test]
+fn test_i2c_interface_byte_order() {
+    let sensor = FakeSensor { memory: [0; 8], log: vec![] };
+    let mut interface = I2cInterface::new(sensor, SENSOR, ByteOrder::Little);
+    interface.write_register(2, 0x1234_u16).unwrap();
+    assert_eq!(RegisterInterface::<u16>::read_register(&mut interface, 2), Ok(0x1234));
+    assert_eq!(interface.into_inner().memory[2..4], [0x34, 0x12]);
+}
+
+#[test]
+fn test_i2c_interface_error() {
+    let sensor = FakeSensor { memory: [0; 8], log: vec![] };
+    let mut interface = I2cInterface::new(sensor, 0x41, ByteOrder::Big);
+    assert!(interface.register::<HalConfig>(0).read().is_err());
+}
@@ -0,0 +1,120 @@
+#![warn(clippy::pedantic)]
+
+use tartan_bitfield::import::{
+    bitfield_from_c_defines, bitfield_from_c_struct, ImportError,
+};
+
+#[test]
+fn test_import_defines() {
+    let header = "
+        #define UART_BASE           0x4000
+        #define UART_CR_EN_POS      0u
+        #define UART_CR_EN_MSK      (1UL << UART_CR_EN_POS)
+        #define UART_CR_PARITY_POS  (1)
+        #define UART_CR_PARITY_MSK  (0x3 << UART_CR_PARITY_POS) // even/odd/none
+        #define UART_CR_BAUD_MASK   0x0FF0000u  /* divider */
+        #define UART_CR_MAX(x)      ((x) << 1)
+        #define UART_CR_ALL         (UART_CR_EN_MSK | UART_CR_PARITY_MSK)
+    ";
+    assert_eq!(
+        bitfield_from_c_defines(header, "UART_CR_", "struct Cr(u32)").unwrap(),
+        "bitfield! {
+    struct Cr(u32) {
+        [0] pub en,
+        [1..3] pub parity,
+        [16..24] pub baud,
+    }
+}
+",
+    );
+}
+
+#[test]
+fn test_import_defines_errors() {
+    assert_eq!(
+        bitfield_from_c_defines("#define A_X_MASK 1", "B_", "struct B(u8)"),
+        Err(ImportError::NoFields),
+    );
+    assert_eq!(
+        bitfield_from_c_defines("#define A_X_MASK 0x5", "A_", "struct A(u8)"),
+        Err(ImportError::NonContiguousMask("x".into())),
+    );
+    assert_eq!(
+        bitfield_from_c_defines(
+            "#define A_X_MASK 0x30\n#define A_X_SHIFT 2",
+            "A_",
+            "struct A(u8)",
+        ),
+        Err(ImportError::MismatchedShift("x".into())),
+    );
+    assert_eq!(
+        bitfield_from_c_defines(
+            "#define A_X_MASK 0x3\n#define A_X_SHIFT 127",
+            "A_",
+            "struct A(u128)",
+        ),
+        Err(ImportError::MismatchedShift("x".into())),
+    );
+    assert_eq!(
+        bitfield_from_c_defines("#define A_X_MASK 0x1f0", "A_", "struct A(u8)"),
+        Err(ImportError::Overflow),
+    );
+    assert_eq!(
+        bitfield_from_c_defines("#define A_X_MASK sizeof(int)", "A_", "struct A(u8)"),
+        Err(ImportError::InvalidValue("A_X_MASK".into())),
+    );
+}
+
+#[test]
+fn test_import_struct() {
+    let header = "
+        struct other { int x : 4; };
+
+        /* Interrupt status,
+         * as read from the controller */
+        typedef struct irq_status {
+            unsigned pending : 4;   // one per line
+            unsigned : 2;
+            bool overflow : 1;
+            unsigned long
+                vector : 0x10;
+        } irq_status_t;
+    ";
+    assert_eq!(
+        bitfield_from_c_struct(header, "irq_status", "pub struct IrqStatus(u32)")
+            .unwrap(),
+        "bitfield! {
+    pub struct IrqStatus(u32) {
+        [0..4] pub pending,
+        [6] pub overflow,
+        [7..23] pub vector,
+    }
+}
+",
+    );
+}
+
+#[test]
+fn test_import_struct_errors() {
+    let header = "struct s { unsigned a : 6; unsigned b : 3; int c; };";
+    assert_eq!(
+        bitfield_from_c_struct(header, "t", "struct T(u8)"),
+        Err(ImportError::StructNotFound),
+    );
+    assert_eq!(
+        bitfield_from_c_struct(header, "s", "struct S(u16)"),
+        Err(ImportError::UnsupportedMember("int c".into())),
+    );
+    assert_eq!(
+        bitfield_from_c_struct(
+            "struct s { unsigned a : 6; unsigned b : 3; };",
+            "s",
+            "struct S(u8)"
+        ),
+        Err(ImportError::Overflow),
+    );
+    assert_eq!(
+        bitfield_from_c_struct("struct s { unsigned : 3; };", "s", "struct S(u8)"),
+        Err(ImportError::NoFields),
+    );
+}
@@ -0,0 +1,117 @@
+#![warn(clippy::pedantic)]
+
+use tartan_bitfield::{bitfield, Register, RegisterInterface};
+
+bitfield! {
+    pub struct InterfaceTest(u16) {
+        [0..4]  pub mode: u8,
+        [15]    pub enable,
+    }
+}
+
+bitfield! {
+    pub struct InterfaceStatus(u8) {
+        [0] pub ready,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct BusError(u8);
+
+// A device with 16-bit and 8-bit registers that fails on accesses to unmapped addresses
+#[derive(Default)]
+struct Device {
+    wide: [u16; 2],
+    narrow: u8,
+    log: Vec<(&'static str, u8)>,
+}
+
+impl RegisterInterface<u16> for Device {
+    type Address = u8;
+    type Error = BusError;
+
+    fn read_register(&mut self, address: u8) -> Result<u16, BusError> {
+        self.log.push(("read", address));
+        self.wide.get(usize::from(address)).copied().ok_or(BusError(address))
+    }
+
+    fn write_register(&mut self, address: u8, value: u16) -> Result<(), BusError> {
+        self.log.push(("write", address));
+        *self.wide.get_mut(usize::from(address)).ok_or(BusError(address))? = value;
+        Ok(())
+    }
+}
+
+impl RegisterInterface<u8> for Device {
+    type Address = u8;
+    type Error = BusError;
+
+    fn read_register(&mut self, address: u8) -> Result<u8, BusError> {
+        match address {
+            0x10 => Ok(self.narrow),
+            _ => Err(BusError(address)),
+        }
+    }
+
+    fn write_register(&mut self, address: u8, value: u8) -> Result<(), BusError> {
+        match address {
+            0x10 => {
+                self.narrow = value;
+                Ok(())
+            }
+            _ => Err(BusError(address)),
+        }
+    }
+}
+
+#[test]
+fn test_register_read_write() {
+    let mut device = Device::default();
+    let mut reg = device.register::<InterfaceTest>(1);
+    assert_eq!(reg.address(), 1);
+    assert_eq!(reg.read(), Ok(InterfaceTest(0)));
+    reg.write(InterfaceTest::default().with_mode(5)).unwrap();
+    assert_eq!(reg.modify(|r| r.set_enable(true)), Ok(()));
+    assert_eq!(reg.modify(|r| r.mode()), Ok(5));
+    assert_eq!(device.wide, [0, 0x8005]);
+    assert_eq!(
+        device.log,
+        [("read", 1), ("write", 1), ("read", 1), ("write", 1), ("read", 1), ("write", 1)],
+    );
+}
+
+#[test]
+fn test_register_errors() {
+    let mut device = Device::default();
+    let mut reg = Register::<_, InterfaceTest, _>::new(&mut device, 2);
+    assert_eq!(reg.read(), Err(BusError(2)));
+    assert_eq!(reg.write(InterfaceTest(1)), Err(BusError(2)));
+
+    let mut called = false;
+    assert_eq!(reg.modify(|_| called = true), Err(BusError(2)));
+    assert!(!called);
+    assert_eq!(device.log, [("read", 2), ("write", 2), ("read", 2)]);
+}
+
+#[test]
+fn test_register_widths() {
+    let mut device = Device::default();
+    device.register::<InterfaceStatus>(0x10).write(InterfaceStatus(1)).unwrap();
+    assert_eq!(device.narrow, 1);
+    assert!(device.register::<InterfaceStatus>(0x10).read().unwrap().ready());
+    assert_eq!(device.register::<InterfaceStatus>(0).read(), Err(BusError(0)));
+    assert!(device.log.is_empty());
+}
+
+#[test]
+fn test_register_through_reference() {
+    fn configure<I: RegisterInterface<u16, Address = u8>>(
+        mut bus: I,
+    ) -> Result<(), I::Error> {
+        bus.register::<InterfaceTest>(0).modify(|r| r.set_mode(3))
+    }
+
+    let mut device = Device::default();
+    configure(&mut device).unwrap();
+    assert_eq!(device.wide, [3, 0]);
+}
@@ -0,0 +1,80 @@
+#![warn(clippy::pedantic)]
+
+use std::time::SystemTime;
+use tartan_bitfield::{bitfield, Journaled};
+
+bitfield! {
+    pub struct JournalTest(u32) {
+        [0..4]   pub mode: u8,
+        [4..=11] pub count: u8,
+        [8..16]  pub high: u8,
+        [31]     pub enable,
+    }
+}
+
+fn changes(journal: &Journaled<JournalTest, u32>) -> Vec<(&'static str, u128, u128)> {
+    journal.journal().map(|e| (e.field.name, e.old, e.new)).collect()
+}
+
+#[test]
+fn test_journal_entries() {
+    let start = SystemTime::now();
+    let mut reg = Journaled::new(JournalTest(0));
+    reg.update(|r| r.set_mode(2));
+    reg.set(reg.get());
+    reg.update(|r| {
+        r.set_count(0xff);
+        r.set_enable(true);
+    });
+    assert_eq!(reg.get(), JournalTest(0x8000_0ff2));
+
+    // Overlapping fields are recorded separately, and steps without changes are skipped
+    assert_eq!(
+        changes(&reg),
+        [("mode", 0, 2), ("count", 0, 0xff), ("high", 0, 0xf), ("enable", 0, 1)],
+    );
+    let entries: Vec<_> = reg.journal().collect();
+    assert!(entries.iter().all(|e| e.time >= start));
+    assert!(entries[0].time <= entries[1].time);
+    assert!(entries[1..].iter().all(|e| e.time == entries[1].time));
+}
+
+#[test]
+fn test_journal_undo_redo() {
+    let mut reg = Journaled::new(JournalTest(0));
+    assert!(!reg.undo());
+    reg.update(|r| r.set_mode(1));
+    reg.update(|r| r.set_mode(2));
+    reg.update(|r| r.set_mode(3));
+
+    assert!(reg.undo());
+    assert!(reg.undo());
+    assert_eq!(reg.get().mode(), 1);
+    assert_eq!(changes(&reg), [("mode", 0, 1)]);
+    assert!(reg.redo());
+    assert_eq!(reg.get().mode(), 2);
+
+    // A new change discards the step that is still undone
+    reg.update(|r| r.set_enable(true));
+    assert!(!reg.redo());
+    assert_eq!(changes(&reg), [("mode", 0, 1), ("mode", 1, 2), ("enable", 0, 1)]);
+
+    assert!(reg.undo());
+    assert!(reg.undo());
+    assert!(reg.undo());
+    assert!(!reg.undo());
+    assert_eq!(reg.get(), JournalTest(0));
+}
+
+#[test]
+fn test_journal_replay() {
+    let mut reg = Journaled::new(JournalTest(0x0000_0123));
+    reg.update(|r| r.set_mode(7));
+    reg.update(|r| r.set_enable(true));
+    assert_eq!(reg.replay(JournalTest(0x0000_ab00)), JournalTest(0x8000_ab07));
+
+    reg.clear();
+    assert_eq!(reg.journal().count(), 0);
+    assert!(!reg.undo());
+    assert_eq!(reg.into_inner(), JournalTest(0x8000_0127));
+}
@@ -0,0 +1,69 @@
+#![warn(clippy::pedantic)]
+
+use tartan_bitfield::{packed_array_bytes, PackedArray, PackedSlice};
+
+#[test]
+fn test_packed_array_round_trip() {
+    fn check<const WIDTH: u8, const LEN: usize, const BYTES: usize>() {
+        let mask = u32::MAX >> (32 - WIDTH);
+        let pattern = |i: usize| {
+            let i = u32::try_from(i).unwrap();
+            (0x9e37_79b9_u32.rotate_left(i) ^ i) & mask
+        };
+
+        let mut array = PackedArray::<WIDTH, LEN, BYTES>::new();
+        assert_eq!(array.len(), LEN);
+        assert!(array.iter().all(|x| x == 0));
+
+        for i in 0..LEN {
+            array.set(i, pattern(i));
+        }
+        for i in 0..LEN {
+            assert_eq!(array.get(i), pattern(i), "width {WIDTH}, index {i}");
+        }
+
+        // Overwrite in reverse order to catch writes that clobber neighbors
+        for i in (0..LEN).rev() {
+            array.set(i, !pattern(i));
+        }
+        for i in 0..LEN {
+            assert_eq!(array.get(i), !pattern(i) & mask, "width {WIDTH}, index {i}");
+        }
+
+        let copy = PackedArray::<WIDTH, LEN, BYTES>::from_bytes(array.into_bytes());
+        assert_eq!(copy, array);
+        assert!(copy.as_slice().iter().take(LEN).eq(array.iter()));
+    }
+
+    check::<1, 17, { packed_array_bytes(1, 17) }>();
+    check::<3, 11, { packed_array_bytes(3, 11) }>();
+    check::<4, 8, { packed_array_bytes(4, 8) }>();
+    check::<7, 9, { packed_array_bytes(7, 9) }>();
+    check::<8, 5, { packed_array_bytes(8, 5) }>();
+    check::<13, 7, { packed_array_bytes(13, 7) }>();
+    check::<25, 5, { packed_array_bytes(25, 5) }>();
+    check::<32, 3, { packed_array_bytes(32, 3) }>();
+}
+
+#[test]
+fn test_packed_slice() {
+    let mut buffer = [0xff_u8; 3];
+    let mut slice = PackedSlice::<_, 6>::new(&mut buffer[..]);
+    assert_eq!(slice.len(), 4);
+    slice.set(1, 0);
+    slice.set(2, 0x2a);
+    assert_eq!(buffer, [0x3f, 0xa0, 0xfe]);
+
+    let slice = PackedSlice::<_, 6>::new(&buffer);
+    assert!(slice.iter().eq([0x3f, 0x00, 0x2a, 0x3f]));
+    assert_eq!(format!("{slice:?}"), "[63, 0, 42, 63]");
+
+    assert!(PackedSlice::<_, 9>::new([0_u8]).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "index 4 out of bounds")]
+fn test_packed_array_out_of_bounds() {
+    let array = PackedArray::<5, 4, { packed_array_bytes(5, 4) }>::new();
+    let _ = array.get(4);
+}
@@ -0,0 +1,48 @@
+#![warn(clippy::pedantic)]
+
+use tartan_bitfield::{
+    bitfield, copy_bitfields_to_bytes, get_bits_batch, par_copy_bitfields_from_bytes,
+    par_copy_bitfields_to_bytes, par_get_bits_batch, ByteOrder, LengthMismatchError,
+};
+
+bitfield! {
+    pub struct ParallelTest(u32) {
+        [0..12]  pub channel: u16,
+        [12..31] pub reading: u32,
+        [31]     pub overflow,
+    }
+}
+
+#[test]
+fn test_par_get_bits_batch() {
+    let words: Vec<u32> = (0..50_000_u32).map(|i| i.wrapping_mul(0x9e37_79b9)).collect();
+    let mut expected = vec![0; words.len()];
+    let mut actual = vec![0; words.len()];
+    get_bits_batch(&words, 12, 31, &mut expected);
+    par_get_bits_batch(&words, 12, 31, &mut actual);
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_par_copy_bitfields() {
+    let values: Vec<ParallelTest> =
+        (0..10_000_u32).map(|i| ParallelTest(i.wrapping_mul(0x9e37_79b9))).collect();
+    let mut expected = vec![0; values.len() * 4];
+    let mut actual = vec![0; values.len() * 4];
+    copy_bitfields_to_bytes(&values, &mut expected, ByteOrder::Big).unwrap();
+    par_copy_bitfields_to_bytes(&values, &mut actual, ByteOrder::Big).unwrap();
+    assert_eq!(actual, expected);
+
+    let mut round_trip = vec![ParallelTest::default(); values.len()];
+    par_copy_bitfields_from_bytes(&mut round_trip, &actual, ByteOrder::Big).unwrap();
+    assert_eq!(round_trip, values);
+
+    assert_eq!(
+        par_copy_bitfields_from_bytes(&mut round_trip, &actual[1..], ByteOrder::Big),
+        Err(LengthMismatchError { expected: 40_000, actual: 39_999 })
+    );
+    assert_eq!(
+        par_copy_bitfields_to_bytes(&values[1..], &mut actual, ByteOrder::Big),
+        Err(LengthMismatchError { expected: 39_996, actual: 40_000 })
+    );
+}
@@ -0,0 +1,119 @@
+#![warn(clippy::pedantic)]
+
+use rand::rngs::mock::StepRng;
+use rand::Rng;
+use tartan_bitfield::uint::u24;
+use tartan_bitfield::{
+    bitfield, flip_field_bit, mutate_field, FieldLayout, RandomFields,
+};
+
+bitfield! {
+    pub struct RandomTest(u32) {
+        [0..4]   pub mode: u8,
+        [4..=11] pub count: u8,
+        [31]     pub enable,
+    }
+}
+
+bitfield! {
+    pub struct RandomWide(u128) {
+        [0..8]    pub low: u8,
+        [120..128] pub high: u8,
+    }
+}
+
+bitfield! {
+    pub struct RandomExact(u24) {
+        [0..4] pub nibble: u8,
+    }
+}
+
+#[test]
+fn test_random_standard() {
+    let mut rng = StepRng::new(0x0123_4567_89ab_cdef, 0);
+    let value: RandomTest = rng.gen();
+    assert_eq!(value, RandomTest(0x89ab_cdef));
+
+    let mut rng = StepRng::new(u64::MAX, 0);
+    let value: RandomWide = rng.gen();
+    assert_eq!(value, RandomWide(u128::MAX));
+    let value: RandomExact = rng.gen();
+    assert_eq!(value, RandomExact(u24::MAX));
+}
+
+#[test]
+fn test_random_fields() {
+    let mut rng = StepRng::new(u64::MAX, 0);
+    let value: RandomTest = rng.sample(RandomFields);
+    assert_eq!(value, RandomTest(0x8000_0fff));
+    let value: RandomWide = rng.sample(RandomFields);
+    assert_eq!(value, RandomWide(0xff00_0000_0000_0000_0000_0000_0000_00ff));
+    let value: RandomExact = rng.sample(RandomFields);
+    assert_eq!(value, RandomExact(u24::new(0xf)));
+}
+
+#[test]
+fn test_random_fields_varies() {
+    let rng = StepRng::new(0, 0x1111_1111_1111_1111);
+    let values: Vec<RandomTest> = rng.sample_iter(RandomFields).take(16).collect();
+    assert!(values.iter().all(|v| v.0 & !0x8000_0fff == 0));
+    assert!(values.windows(2).all(|pair| pair[0] != pair[1]));
+}
+
+bitfield! {
+    pub struct RandomEmpty(u8) {}
+}
+
+// The bits of `before` and `after` that differ, if they are all within one field
+fn changed_field(before: RandomTest, after: RandomTest) -> Option<&'static str> {
+    let diff = before.0 ^ after.0;
+    RandomTest::LAYOUT
+        .iter()
+        .find(|field| diff != 0 && u128::from(diff) & !field.mask() == 0)
+        .map(|field| field.name)
+}
+
+#[test]
+fn test_mutate_field() {
+    let mut rng = StepRng::new(7, 0x9e37_79b9_7f4a_7c15);
+    let mut seen = Vec::new();
+    let mut value = RandomTest(0x7000_0a5c);
+    for _ in 0..200 {
+        let mutated = mutate_field(value, &mut rng);
+        let field = changed_field(value, mutated).expect("exactly one field changed");
+        assert_eq!(mutated.0 & 0x7fff_f000, 0x7000_0000);
+        if !seen.contains(&field) {
+            seen.push(field);
+        }
+        value = mutated;
+    }
+    seen.sort_unstable();
+    assert_eq!(seen, ["count", "enable", "mode"]);
+
+    let mut wide = RandomWide(0);
+    for _ in 0..50 {
+        let mutated = mutate_field(wide, &mut rng);
+        assert_ne!(mutated, wide);
+        assert_eq!(mutated.0 & !0xff00_0000_0000_0000_0000_0000_0000_00ff, 0);
+        wide = mutated;
+    }
+
+    assert_eq!(mutate_field(RandomEmpty(0x5a), &mut rng), RandomEmpty(0x5a));
+}
+
+#[test]
+fn test_flip_field_bit() {
+    let mut rng = StepRng::new(3, 0x9e37_79b9_7f4a_7c15);
+    let mut flipped = 0;
+    let value = RandomTest(0x0000_f000);
+    for _ in 0..200 {
+        let mutated = flip_field_bit(value, &mut rng);
+        let diff = value.0 ^ mutated.0;
+        assert_eq!(diff.count_ones(), 1);
+        assert_eq!(diff & !0x8000_0fff, 0);
+        flipped |= diff;
+    }
+    assert_eq!(flipped, 0x8000_0fff);
+
+    assert_eq!(flip_field_bit(RandomEmpty(0x5a), &mut rng), RandomEmpty(0x5a));
+}
@@ -0,0 +1,69 @@
+#![warn(clippy::pedantic)]
+
+use tartan_bitfield::{bitfield, Bitfield, RegisterPair, SplitHalves};
+
+bitfield! {
+    pub struct SplitTest(u64) {
+        [0..62] pub count: u64,
+        [63]    pub enable,
+    }
+}
+
+// A free-running counter that advances every time the low register is read
+struct CounterRegs {
+    value: u64,
+    reads: usize,
+    writes: Vec<(&'static str, u32)>,
+}
+
+impl RegisterPair for CounterRegs {
+    type Half = u32;
+
+    fn read_high(&mut self) -> u32 {
+        SplitHalves::into_halves(self.value).0
+    }
+
+    fn read_low(&mut self) -> u32 {
+        let low = SplitHalves::into_halves(self.value).1;
+        self.value += 1;
+        self.reads += 1;
+        low
+    }
+
+    fn write_high(&mut self, value: u32) {
+        self.writes.push(("high", value));
+    }
+
+    fn write_low(&mut self, value: u32) {
+        self.writes.push(("low", value));
+    }
+}
+
+#[test]
+fn test_split_halves() {
+    // Integers are also bitfields of themselves, so name the trait to pick its methods
+    assert_eq!(<u16 as SplitHalves>::from_halves(0x12, 0x34), 0x1234);
+    assert_eq!(SplitHalves::into_halves(0x1234_5678_u32), (0x1234, 0x5678));
+    assert_eq!(<u128 as SplitHalves>::from_halves(1, 2), (1 << 64) | 2);
+
+    let x = SplitTest::from_halves(0x8000_0000, 0xffff_ffff);
+    assert!(x.enable());
+    assert_eq!(x.count(), 0xffff_ffff);
+    assert_eq!(x.into_halves(), (0x8000_0000, 0xffff_ffff));
+}
+
+#[test]
+fn test_register_pair() {
+    let mut regs = CounterRegs { value: 0x0000_0004_ffff_fffe, reads: 0, writes: vec![] };
+    let x: SplitTest = regs.read_value();
+    assert_eq!(x.count(), 0x0000_0004_ffff_fffe);
+    assert_eq!(regs.reads, 1);
+
+    // The low half wraps between reading the halves, so they are read again
+    let y: SplitTest = regs.read_value();
+    assert_eq!(y.count(), 0x0000_0005_0000_0000);
+    assert_eq!(regs.reads, 3);
+
+    regs.modify_value(|x: &mut SplitTest| x.set_enable(true));
+    assert_eq!(regs.writes, [("low", 0x0000_0001), ("high", 0x8000_0005)]);
+}
@@ -0,0 +1,57 @@
+#![warn(clippy::pedantic)]
+
+use tartan_bitfield::svd::{BitReader, FieldReader, Reg};
+use tartan_bitfield::uint::u3;
+use tartan_bitfield::{bitfield, svd_writer};
+
+bitfield! {
+    pub struct SvdTest(u16) {
+        [0]      pub en,
+        [1..4]   pub mode: u3,
+        [8..16]  pub prescaler: u8,
+    }
+}
+
+svd_writer!(pub trait SvdTestW for SvdTest);
+
+#[test]
+fn test_svd_reg() {
+    let reg = Reg::<SvdTest, u16>::new(SvdTest(0xff00));
+    assert_eq!(reg.read().prescaler().bits(), 0xff);
+    assert!(reg.read().en().bit_is_clear());
+
+    // Writes start from the default value
+    reg.write(|w| w.mode().variant(u3::new(5)));
+    assert_eq!(reg.read().bits(), 0x000a);
+
+    // Modifications start from the current value
+    reg.modify(|r, w| w.prescaler().bits(r.mode().bits() + 1));
+    assert!(!reg.read().en().bit());
+    assert_eq!(reg.read(), SvdTest(0x060a));
+
+    reg.reset();
+    assert_eq!(reg.read().bits(), 0);
+    assert_eq!(format!("{reg:?}"), format!("Reg({:?})", SvdTest(0)));
+}
+
+#[test]
+fn test_svd_writer() {
+    let reg = Reg::<SvdTest, u16>::default();
+    reg.write(|w| w.en().set_bit().prescaler().bits(0x12));
+    assert_eq!(reg.read(), SvdTest(0x1201));
+
+    reg.modify(|_, w| w.en().clear_bit().mode().bits(u3::new(7)));
+    assert_eq!(reg.read(), SvdTest(0x120e));
+
+    // The writer dereferences to the bitfield
+    reg.modify(|r, w| {
+        w.set_en(!r.en());
+        w
+    });
+    assert_eq!(reg.read(), SvdTest(0x120f));
+
+    reg.write(|w| w.bits(0xabcd));
+    assert_eq!(reg.read(), SvdTest(0xabcd));
+    reg.modify(|_, w| w.set(SvdTest(0x0100)).en().bit(true));
+    assert_eq!(reg.read(), SvdTest(0x0101));
+}
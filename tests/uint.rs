@@ -0,0 +1,129 @@
+#![warn(clippy::pedantic)]
+
+use tartan_bitfield::uint::{
+    u1, u12, u24, u3, u48, u63, u7, OutOfRangeError, ParseUIntError,
+};
+use tartan_bitfield::{bitfield, bitfield_tests, ParseFields, UInt, WriteFields};
+
+#[test]
+fn test_uint() {
+    assert_eq!(u3::MAX.value(), 7);
+    assert_eq!(u1::MAX.value(), 1);
+    assert_eq!(u63::MAX.value(), u64::MAX >> 1);
+    assert_eq!(UInt::<u8, 8>::MAX.value(), u8::MAX);
+    assert_eq!(u7::MIN.value(), 0);
+
+    assert_eq!(u3::new(5).value(), 5);
+    assert_eq!(u3::try_new(7), Some(u3::MAX));
+    assert_eq!(u3::try_new(8), None);
+    assert_eq!(u3::new_masked(0xfd).value(), 5);
+    assert_eq!(u8::from(u3::new(6)), 6);
+    assert_eq!(u12::try_from(0xfff), Ok(u12::MAX));
+    assert_eq!(u12::try_from(0x1000), Err(OutOfRangeError));
+    assert_eq!(OutOfRangeError.to_string(), "value does not fit in the number of bits");
+
+    assert!(u12::new(0x100) < u12::new(0x101));
+    assert_eq!(
+        format!("{:?} {} {:#x}", u12::MAX, u3::new(2), u12::new(0xabc)),
+        "4095 2 0xabc"
+    );
+}
+
+#[test]
+#[should_panic(expected = "value does not fit")]
+fn test_uint_new_panic() {
+    let _ = u3::new(8);
+}
+
+bitfield! {
+    pub struct UIntFieldTest(u32) {
+        [0..3]   pub mode: u3,
+        [4..16]  pub count: u16 as u12,
+        [16..32] pub rest,
+    }
+}
+
+#[test]
+fn test_uint_fields() {
+    let x = UIntFieldTest(0x1234_5678);
+    assert_eq!(x.mode(), u3::new(0));
+    assert_eq!(x.count(), u12::new(0x567));
+
+    let y = x.with_mode(u3::MAX).with_count(u12::new(0xabc));
+    assert_eq!(y, UIntFieldTest(0x1234_abcf));
+
+    let mut out = String::new();
+    y.write_fields(&mut out).unwrap();
+    assert_eq!(out, "mode=0x7, count=0xabc, rest=0x1234");
+    assert_eq!(
+        format!("{y:?}"),
+        "UIntFieldTest { <value>: 305441743, mode: 7, count: 2748, rest: 4660 }"
+    );
+
+    let z = UIntFieldTest::parse_fields("mode=5, count=0x123").unwrap();
+    assert_eq!(z, UIntFieldTest(0x1235));
+    assert!(UIntFieldTest::parse_fields("mode=8").is_err());
+}
+
+bitfield! {
+    pub struct UIntWideFieldTest(u16) {
+        [0..8]  pub low: u8 as u3,
+        [8..16] pub high: u8 as u7,
+    }
+}
+
+#[test]
+fn test_uint_wide_fields() {
+    // Bits of the field beyond the width of the interface type are discarded
+    let x = UIntWideFieldTest(0xa5ff);
+    assert_eq!(x.low(), u3::MAX);
+    assert_eq!(x.low().value(), 7);
+    assert_eq!(x.high(), u7::new(0x25));
+    assert_eq!(x.with_low(u3::new(2)), UIntWideFieldTest(0xa502));
+}
+
+bitfield! {
+    pub struct Sample24(u24) {
+        [0..20]  pub level,
+        [20..23] pub channel: u3,
+        [23]     pub clipped,
+    }
+}
+
+bitfield! {
+    pub struct MacAddress(u48) {
+        [0..24]  pub nic,
+        [24..48] pub oui: u24,
+        [40]     pub multicast,
+    }
+}
+
+bitfield_tests!(Sample24, MacAddress);
+
+#[test]
+fn test_uint_storage() {
+    let x = Sample24(u24::new(0xb1_2345));
+    assert_eq!(x.level(), 0x1_2345);
+    assert_eq!(x.channel(), u3::new(3));
+    assert!(x.clipped());
+    assert_eq!(x.with_level(0xf_ffff).with_clipped(false).0, u24::new(0x3f_ffff));
+    assert_eq!(u32::from(u24::from(x)), 0xb1_2345);
+
+    let mac = MacAddress(u48::new(0x0100_5e00_00fb));
+    assert_eq!(mac.oui(), u24::new(0x01_005e));
+    assert_eq!(mac.nic(), 0xfb);
+    assert!(mac.multicast());
+    assert_eq!(MacAddress::default().with_oui(u24::MAX).0, u48::new(0xffff_ff00_0000));
+    assert_eq!(mac.with_nic(0x12_3456).0, u48::new(0x0100_5e12_3456));
+    assert_eq!(x.with_channel(u3::new(4)).0, u24::new(0xc1_2345));
+
+    assert_eq!("0x800000".parse::<Sample24>(), Ok(Sample24(u24::new(0x80_0000))));
+    assert!("0x1000000".parse::<Sample24>().is_err());
+    assert_eq!(Sample24::from_str_radix("1000000", 16), Err(ParseUIntError::OutOfRange));
+    assert!(matches!("0xg".parse::<Sample24>(), Err(ParseUIntError::Invalid(_))));
+
+    // Only the declared number of bits are iterated and collected
+    assert_eq!(x.iter_bits().len(), 24);
+    assert_eq!(x.iter_bits().next_back(), Some(true));
+    assert_eq!(Sample24::from_bits([true; 32]), Sample24(u24::MAX));
+}
@@ -0,0 +1,32 @@
+#![warn(clippy::pedantic)]
+
+use tartan_bitfield::{bitfield, ParseFields, WriteFields};
+use ux::{u12, u3, u7};
+
+bitfield! {
+    pub struct UxFieldTest(u32) {
+        [0..3]   pub mode: u3,
+        [4..16]  pub count: u16 as u12,
+        [16..24] pub wide: u8 as u7,
+    }
+}
+
+#[test]
+fn test_ux_fields() {
+    let x = UxFieldTest(0x00ff_5678);
+    assert_eq!(x.mode(), u3::new(0));
+    assert_eq!(x.count(), u12::new(0x567));
+    // Bits of the field beyond the width of the interface type are discarded
+    assert_eq!(x.wide(), u7::MAX);
+
+    let y = x.with_mode(u3::MAX).with_count(u12::new(0xabc)).with_wide(u7::new(1));
+    assert_eq!(y, UxFieldTest(0x0001_abcf));
+
+    let mut out = String::new();
+    y.write_fields(&mut out).unwrap();
+    assert_eq!(out, "mode=0x7, count=0xabc, wide=0x1");
+
+    let z = UxFieldTest::parse_fields("mode=5, count=0x123").unwrap();
+    assert_eq!(z, UxFieldTest(0x1235));
+    assert!(UxFieldTest::parse_fields("mode=8").is_err());
+}
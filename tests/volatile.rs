@@ -0,0 +1,40 @@
+#![warn(clippy::pedantic)]
+
+use tartan_bitfield::svd::{BitReader, FieldReader, VolatileReg};
+use tartan_bitfield::{bitfield, svd_writer};
+
+bitfield! {
+    pub struct VolatileCr(u32) {
+        [0]    pub en,
+        [4..8] pub mode,
+    }
+}
+
+bitfield! {
+    pub struct VolatileSr(u32) {
+        [0] pub busy,
+        [1] pub done,
+    }
+}
+
+svd_writer!(pub trait VolatileCrW for VolatileCr);
+
+/// A block of registers with the same layout as the peripheral
+#[repr(C)]
+#[derive(Default)]
+struct RegisterBlock {
+    cr: VolatileReg<VolatileCr, u32>,
+    sr: VolatileReg<VolatileSr, u32>,
+}
+
+#[test]
+fn test_volatile_reg() {
+    assert_eq!(size_of::<RegisterBlock>(), 8);
+
+    let block = RegisterBlock::default();
+    block.cr.write(|w| w.en().set_bit().mode().bits(0x5));
+    block.cr.modify(|r, w| w.mode().bits(r.mode() + 1));
+    assert!(block.cr.read().en().bit_is_set());
+    assert_eq!(block.cr.read().bits(), 0x61);
+    assert!(block.sr.read().busy().bit_is_clear());
+}